@@ -7,6 +7,7 @@
 
 package app.visly.shard
 
+import androidx.annotation.Keep
 import org.json.JSONArray
 import org.json.JSONObject
 import org.json.JSONTokener
@@ -29,27 +30,29 @@ private fun JSONObject.toMap(): Map<String, JsonValue> {
 }
 
 sealed class JsonValue {
-    object Null : JsonValue() {
+    @Keep object Null : JsonValue() {
         override fun toString(): kotlin.String = JsonValue.toJsonString(this)
     }
 
-    data class String(val value: kotlin.String) : JsonValue()  {
+    @Keep data class String(val value: kotlin.String) : JsonValue()  {
         override fun toString(): kotlin.String = JsonValue.toJsonString(this)
     }
 
-    data class Boolean(val value: kotlin.Boolean) : JsonValue() {
+    @Keep data class Boolean(val value: kotlin.Boolean) : JsonValue() {
         override fun toString(): kotlin.String = JsonValue.toJsonString(this)
     }
 
-    data class Number(val value: Float) : JsonValue() {
+    @Keep data class Number(val value: Double) : JsonValue() {
+        constructor(value: Float) : this(value.toDouble())
+
         override fun toString(): kotlin.String = JsonValue.toJsonString(this)
     }
 
-    data class Object(val value: Map<kotlin.String, JsonValue>) : JsonValue() {
+    @Keep data class Object(val value: Map<kotlin.String, JsonValue>) : JsonValue() {
         override fun toString(): kotlin.String = JsonValue.toJsonString(this)
     }
 
-    data class Array(val value: List<JsonValue>) : JsonValue() {
+    @Keep data class Array(val value: List<JsonValue>) : JsonValue() {
         override fun toString(): kotlin.String = JsonValue.toJsonString(this)
     }
 
@@ -62,7 +65,7 @@ sealed class JsonValue {
             return when (value) {
                 is kotlin.String -> String(value)
                 is kotlin.Boolean -> Boolean(value)
-                is kotlin.Number -> Number(value.toFloat())
+                is kotlin.Number -> Number(value.toDouble())
                 is JSONObject -> Object(value.toMap())
                 is JSONArray -> Array(value.toList())
                 else -> Null
@@ -2,6 +2,7 @@ package app.visly.shard
 
 import android.content.Context
 import android.content.ContextWrapper
+import androidx.annotation.Keep
 
 internal interface ActionDelegate {
     fun on(action: String, value: JsonValue?)
@@ -9,8 +10,23 @@ internal interface ActionDelegate {
 
 class ShardContext(ctx: Context) : ContextWrapper(ctx) {
     internal var actionDelegate: ActionDelegate? = null
+    internal var root: ShardRoot? = null
+    internal var navigator: ShardNavigator? = null
 
-    fun dispatch(action: String, value: JsonValue?) {
+    /**
+     * Run an action declared in the document, such as the value of an on-click prop. The core
+     * performs built-in actions and calls back into dispatch(action, value) for the others.
+     */
+    fun dispatch(action: JsonValue) {
+        val navigator = this.navigator
+        if (navigator != null) {
+            navigator.dispatch(action)
+        } else {
+            this.root?.dispatch(action)
+        }
+    }
+
+    @Keep fun dispatch(action: String, value: JsonValue?) {
         this.actionDelegate?.on(action, value)
     }
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+package app.visly.shard
+
+import android.content.Context
+import android.graphics.PointF
+import android.util.Log
+import androidx.annotation.Keep
+
+/**
+ * A stack of roots driven by `push`, `pop` and `replace` actions in the documents, or by the
+ * methods of the same names. Targets are URLs or inline documents. Covered roots keep their
+ * state and scroll position, the host only animates between roots.
+ */
+class ShardNavigator(private val ctx: Context, private val host: Host) {
+    enum class Transition { PUSH, POP, REPLACE }
+
+    interface Host {
+        /**
+         * Show `to` in place of `from`, which is null for the first root. When popping, `to`
+         * should be scrolled back to `scrollPosition`, in pixels.
+         */
+        fun transition(transition: Transition, from: ShardRoot?, to: ShardRoot, scrollPosition: PointF)
+    }
+
+    @Keep private var rustPtr: Long = 0
+    private fun finalize() { free() }
+    private external fun bind(): Long
+    private external fun free()
+    private external fun pushTarget(target: JsonValue)
+    private external fun replaceTarget(target: JsonValue)
+    private external fun popCount(count: Int): Boolean
+    private external fun dispatchAction(action: JsonValue)
+    private external fun setScrollPosition(x: Float, y: Float)
+
+    private val roots: MutableList<ShardRoot> = mutableListOf()
+    private var rendered: ShardRoot? = null
+
+    init {
+        rustPtr = bind()
+    }
+
+    val top: ShardRoot? get() = roots.lastOrNull()
+
+    fun push(target: JsonValue) = pushTarget(target)
+
+    fun replace(target: JsonValue) = replaceTarget(target)
+
+    /**
+     * Pop up to count roots, always keeping the first one. Returns false if there was nothing to
+     * pop, in which case the host may want to close the navigator itself.
+     */
+    fun pop(count: Int = 1): Boolean = popCount(count)
+
+    /**
+     * Run an action on the top root. Navigation actions in a chain run after the rest of it.
+     */
+    fun dispatch(action: JsonValue) {
+        try {
+            dispatchAction(action)
+        } catch (e: Exception) {
+            Log.w("Shard", "Could not dispatch $action", e)
+        }
+        top?.let { it.view.requestLayout() }
+    }
+
+    /**
+     * Record where the top root is scrolled to, in pixels, to restore it when it is revealed again.
+     */
+    fun scrolled(x: Float, y: Float) {
+        val density = ctx.resources.displayMetrics.density
+        setScrollPosition(x / density, y / density)
+    }
+
+    @Keep private fun dispatch(action: String, value: JsonValue?) {
+        top?.ctx?.dispatch(action, value)
+    }
+
+    @Keep private fun render(target: JsonValue): ShardRoot {
+        val viewManager = ShardViewManager.instance
+        val result = when (target) {
+            is JsonValue.String -> viewManager.loadUrlBlocking(ctx, target.value)
+            else -> viewManager.loadJson(ctx, target)
+        }
+
+        if (result.isError()) {
+            throw result.error()
+        }
+
+        val root = result.success()
+        root.owned = false
+        root.ctx.navigator = this
+        rendered = root
+        return root
+    }
+
+    @Keep private fun transition(transition: Int, from: Long, to: Long, scrollX: Float, scrollY: Float) {
+        val fromRoot = roots.lastOrNull { it.rustPtr == from }
+        val toRoot = roots.lastOrNull { it.rustPtr == to } ?: rendered!!
+        rendered = null
+
+        val density = ctx.resources.displayMetrics.density
+        val kind = Transition.values()[transition]
+        host.transition(kind, fromRoot, toRoot, PointF(scrollX * density, scrollY * density))
+
+        when (kind) {
+            Transition.PUSH -> roots.add(toRoot)
+            Transition.REPLACE -> {
+                if (roots.isNotEmpty()) roots.removeAt(roots.lastIndex)
+                roots.add(toRoot)
+            }
+            Transition.POP -> while (roots.last() !== toRoot) roots.removeAt(roots.lastIndex)
+        }
+    }
+}
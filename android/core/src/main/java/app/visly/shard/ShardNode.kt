@@ -0,0 +1,17 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+package app.visly.shard
+
+import android.graphics.RectF
+import android.view.View
+
+/**
+ * A node matched by [ShardRoot.query]. The frame is relative to the root and in pixels. Nodes
+ * which were flattened into their parent have no view.
+ */
+data class ShardNode(val kind: String, val id: String?, val frame: RectF, val view: View?)
@@ -1,14 +1,157 @@
 package app.visly.shard
 
+import android.graphics.RectF
+import android.util.Log
 import android.view.View
 import androidx.annotation.Keep
-import java.lang.RuntimeException
 
 class ShardRoot(internal val ctx: ShardContext, @Keep internal val rustPtr: Long) {
-    private fun finalize() { free() }
+    // Roots shown by a ShardNavigator are owned by its stack
+    internal var owned = true
+
+    private fun finalize() { if (owned) free() }
     private external fun free()
     private external fun getView(): ShardView
     private external fun measure(size: Size)
+    private external fun setDarkMode(dark: Boolean)
+    private external fun getWarnings(): Array<String>
+    private external fun getPendingFragments(): Array<String>
+    private external fun loadFragmentJson(viewManager: ShardViewManager, ctx: ShardContext, id: String, json: String)
+    private external fun getViewById(id: String): ShardView?
+    private external fun getFrame(id: String): FloatArray?
+    private external fun setPropValue(id: String, key: String, value: JsonValue)
+    private external fun getState(): JsonValue
+    private external fun setStateValue(path: String, value: JsonValue)
+    private external fun applyPatchValue(viewManager: ShardViewManager, ctx: ShardContext, patch: JsonValue)
+    private external fun queryNodes(selector: String): Array<ShardNode>
+    private external fun hitTestNodes(x: Float, y: Float): Array<ShardNode>
+    private external fun dispatch(ctx: ShardContext, action: String)
+
+    init {
+        ctx.root = this
+    }
+
+    /**
+     * Problems in the document which did not stop it from rendering, each prefixed with the
+     * JSON pointer of the offending value.
+     */
+    val warnings: List<String> by lazy { getWarnings().toList() }
+
+    /**
+     * The fragments of lazy includes which are still showing their placeholder, in document order.
+     */
+    val pendingFragments: List<String> get() = getPendingFragments().toList()
+
+    /**
+     * Supply the JSON of a fragment, replacing the placeholders of lazy includes of id with it.
+     * The fragment is cached so later documents include it directly.
+     */
+    fun loadFragment(id: String, json: String) {
+        loadFragmentJson(ShardViewManager.instance, ctx, id, json)
+        view.requestLayout()
+    }
+
+    /**
+     * The view of the node with the given id, or null if there is no such node or it was
+     * flattened into its parent.
+     */
+    fun viewById(id: String): View? = getViewById(id)?.view
+
+    /**
+     * The frame of the node with the given id relative to the root, in pixels.
+     */
+    fun frameOf(id: String): RectF? {
+        val density = ctx.resources.displayMetrics.density
+        val frame = getFrame(id) ?: return null
+        return RectF(frame[0] * density, frame[2] * density, frame[1] * density, frame[3] * density)
+    }
+
+    /**
+     * Override a prop of the node with the given id. Call measure afterwards to lay out the
+     * root again.
+     */
+    fun setProp(id: String, key: String, value: JsonValue) {
+        setPropValue(id, key, value)
+        getViewById(id)?.let { it.impl.bindView(it.view) }
+    }
+
+    /**
+     * The current state of the document, as declared in its `state` section and changed by
+     * actions since.
+     */
+    val state: JsonValue get() = getState()
+
+    /**
+     * Set the value at a dot separated path of the state, such as `cart.quantity`, and update
+     * the props and conditional nodes bound to it.
+     */
+    fun setState(path: String, value: JsonValue) {
+        setStateValue(path, value)
+        view.requestLayout()
+    }
+
+    /**
+     * Apply a JSON Patch to the document, updating nodes in place where possible. A patch which
+     * fails to apply or render leaves the views untouched.
+     */
+    fun applyPatch(patch: JsonValue) {
+        try {
+            applyPatchValue(ShardViewManager.instance, ctx, patch)
+        } finally {
+            view.requestLayout()
+        }
+    }
+
+    /**
+     * The nodes matching a selector such as `list > .item[selected=true] text`, in document order.
+     */
+    fun query(selector: String): List<ShardNode> = queryNodes(selector).toList()
+
+    /**
+     * The nodes under a point relative to the root, in pixels, deepest first.
+     */
+    fun hitTest(x: Float, y: Float): List<ShardNode> {
+        val density = ctx.resources.displayMetrics.density
+        return hitTestNodes(x / density, y / density).toList()
+    }
+
+    @Keep private fun createNode(
+            kind: String,
+            id: String?,
+            start: Float,
+            end: Float,
+            top: Float,
+            bottom: Float,
+            view: ShardView?): ShardNode {
+        val density = ctx.resources.displayMetrics.density
+        return ShardNode(kind, id, RectF(start * density, top * density, end * density, bottom * density), view?.view)
+    }
+
+    /**
+     * Run an action, or an array of actions, declared in the document. Actions which are not
+     * built into the core are passed to the handlers registered on the root view.
+     */
+    fun dispatch(action: JsonValue) {
+        try {
+            dispatch(ctx, action.toString())
+        } catch (e: Exception) {
+            Log.w("Shard", "Could not dispatch $action", e)
+        }
+        view.requestLayout()
+    }
+
+    fun setTheme(dark: Boolean) {
+        setDarkMode(dark)
+
+        fun rebind(root: ShardView) {
+            root.impl.bindView(root.view)
+            for (child in root.children) {
+                rebind(child)
+            }
+        }
+
+        rebind(getView())
+    }
 
     fun measure(width: Float?, height: Float?): Size {
         val density = ctx.resources.displayMetrics.density
@@ -16,6 +159,7 @@ class ShardRoot(internal val ctx: ShardContext, @Keep internal val rustPtr: Long
 
         fun updateFrame(root: ShardView) {
             val view = root.view
+            root.impl.bindView(view)
             view.layoutParams = AbsoluteLayout.LayoutParams(
                     root.frame.width().toInt(),
                     root.frame.height().toInt(),
@@ -35,22 +179,5 @@ class ShardRoot(internal val ctx: ShardContext, @Keep internal val rustPtr: Long
         return getView().getSize()
     }
 
-    internal val view: View by lazy {
-        fun createHierarchy(root: ShardView): View {
-            val view = root.view
-            root.impl.bindView(view)
-
-            if (view is AbsoluteLayout) {
-                for (child in root.children) {
-                    view.addView(createHierarchy(child))
-                }
-            } else if (root.children.size > 0) {
-                throw RuntimeException("Only flexbox is allowed to specify children")
-            }
-
-            return view
-        }
-
-        createHierarchy(getView())
-    }
+    internal val view: View by lazy { getView().mount() }
 }
\ No newline at end of file
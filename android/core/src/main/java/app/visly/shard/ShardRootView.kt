@@ -2,6 +2,8 @@ package app.visly.shard
 
 import android.annotation.TargetApi
 import android.content.Context
+import android.content.Intent
+import android.net.Uri
 import android.os.Build
 import android.util.AttributeSet
 
@@ -13,7 +15,12 @@ class ShardRootView @JvmOverloads constructor(context: Context, attrs: Attribute
     private val actionHandlers: MutableMap<String, ActionHandler> = mutableMapOf()
     private val actionDelegate: ActionDelegate = object: ActionDelegate {
         override fun on(action: String, value: JsonValue?) {
-            actionHandlers[action]?.invoke(value)
+            val handler = actionHandlers[action]
+            if (handler != null) {
+                handler(value)
+            } else if (action == "open-url" && value is JsonValue.String) {
+                context.startActivity(Intent(Intent.ACTION_VIEW, Uri.parse(value.value)))
+            }
         }
     }
 
@@ -12,6 +12,7 @@ import android.graphics.Typeface
 import android.content.res.AssetManager
 import android.graphics.Paint
 import android.text.TextPaint
+import android.text.style.LineHeightSpan
 import android.text.style.MetricAffectingSpan
 
 
@@ -65,4 +66,16 @@ class ShardTextSpan(
         paint.isSubpixelText = true
     }
 
-}
\ No newline at end of file
+}
+
+/**
+ * Scales the height of the lines a run is on by a multiple of their natural height. Like
+ * lineHeightMultiple on iOS, the extra space goes above the text.
+ */
+class ShardLineHeightSpan(private val multiple: Float) : LineHeightSpan {
+    override fun chooseHeight(text: CharSequence, start: Int, end: Int, spanstartv: Int, lineHeight: Int, fm: Paint.FontMetricsInt) {
+        val extra = Math.round((fm.descent - fm.ascent) * (multiple - 1f))
+        fm.ascent -= extra
+        fm.top -= extra
+    }
+}
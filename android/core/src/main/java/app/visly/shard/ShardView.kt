@@ -10,6 +10,7 @@ package app.visly.shard
 import android.graphics.RectF
 import android.view.View
 import androidx.annotation.Keep
+import java.lang.RuntimeException
 import kotlin.math.ceil
 
 interface ShardViewImpl<T: View> {
@@ -34,6 +35,29 @@ class ShardView(private val ctx: ShardContext, internal val impl: ShardViewImpl<
         impl.createView()
     }
 
+    // Once the native hierarchy is built, children added and removed by the core are mirrored in it
+    private var mounted = false
+
+    internal fun mount(): View {
+        val view = this.view
+
+        if (!mounted) {
+            impl.bindView(view)
+
+            if (view is AbsoluteLayout) {
+                for (child in children) {
+                    view.addView(child.mount())
+                }
+            } else if (children.size > 0) {
+                throw RuntimeException("Only flexbox is allowed to specify children")
+            }
+
+            mounted = true
+        }
+
+        return view
+    }
+
     @Keep private fun setFrame(start: Float, end: Float, top: Float, bottom: Float) {
         val density = ctx.resources.displayMetrics.density
         this.frame = RectF(start * density, top * density, end * density, bottom * density)
@@ -41,10 +65,36 @@ class ShardView(private val ctx: ShardContext, internal val impl: ShardViewImpl<
 
     @Keep private fun addChild(child: ShardView) {
         children.add(child)
+
+        if (mounted) {
+            val view = this.view as? AbsoluteLayout
+                    ?: throw RuntimeException("Only flexbox is allowed to specify children")
+            view.addView(child.mount())
+        }
+    }
+
+    @Keep private fun insertChild(child: ShardView, index: Int) {
+        children.add(index, child)
+
+        if (mounted) {
+            val view = this.view as? AbsoluteLayout
+                    ?: throw RuntimeException("Only flexbox is allowed to specify children")
+            view.addView(child.mount(), index)
+        }
+    }
+
+    @Keep private fun removeChild(child: ShardView) {
+        children.remove(child)
+
+        if (mounted) {
+            val view = this.view as? AbsoluteLayout
+                    ?: throw RuntimeException("Only flexbox is allowed to specify children")
+            view.removeView(child.view)
+        }
     }
 
-    @Keep private fun setProp(key: String, value: String) {
-        impl.setProp(key, JsonValue.parse(value))
+    @Keep private fun setProp(key: String, value: JsonValue) {
+        impl.setProp(key, value)
     }
 
     @Keep private fun measure(width: Float, height: Float): Size {
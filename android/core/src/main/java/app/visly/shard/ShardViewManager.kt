@@ -9,15 +9,19 @@ package app.visly.shard
 
 import android.app.Application
 import android.content.Context
+import android.content.pm.ApplicationInfo
 import android.os.Build
 import android.os.Handler
 import android.os.Looper
+import android.util.Log
 import android.view.View
 import androidx.annotation.Keep
 import com.facebook.drawee.backends.pipeline.Fresco
 import com.facebook.soloader.SoLoader
+import kotlinx.coroutines.Dispatchers
 import kotlinx.coroutines.GlobalScope
 import kotlinx.coroutines.launch
+import kotlinx.coroutines.runBlocking
 import okhttp3.OkHttpClient
 import okhttp3.Request
 import app.visly.shard.viewimpl.FlexboxViewImpl
@@ -26,10 +30,30 @@ import app.visly.shard.viewimpl.TextViewImpl
 import app.visly.shard.viewimpl.SolidColorViewImpl
 import app.visly.shard.viewimpl.ScrollViewImpl
 import kotlinx.coroutines.Job
+import java.io.File
 import java.lang.Exception
 
+internal interface DocumentCallback {
+    @Keep fun onDocument(json: String)
+}
+
+@Keep
+internal class ShardResponse(
+        val status: Int,
+        val body: ByteArray,
+        val contentType: String?,
+        val etag: String?,
+        val cacheControl: String?)
+
 class ShardViewManager internal constructor() {
 
+    interface FragmentResolver {
+        /**
+         * The JSON of the fragment with id, included by documents with `{"$include": id}`.
+         */
+        @Keep fun resolve(id: String): String
+    }
+
     companion object {
         private var hasCalledInit = false
         lateinit var instance: ShardViewManager private set
@@ -57,6 +81,10 @@ class ShardViewManager internal constructor() {
     private external fun bind(): Long
     private external fun free()
     private external fun render(ctx: Context, json: String): Long
+    private external fun renderBytes(ctx: Context, bytes: ByteArray, contentType: String?): Long
+    private external fun loadDocument(url: String, cacheDir: String): String
+    private external fun loadDocumentStaleWhileRevalidate(url: String, cacheDir: String, callback: DocumentCallback)
+    private external fun bindFragmentResolver(resolver: FragmentResolver)
 
     private val httpClient = OkHttpClient()
     internal val implFactories: MutableMap<String, (ShardContext) -> ShardViewImpl<View>> = mutableMapOf()
@@ -69,40 +97,107 @@ class ShardViewManager internal constructor() {
         setViewImpl("scroll") { ScrollViewImpl(it) }
     }
 
+    /**
+     * Load a document through the core loader. A stale cached copy is shown right away, in which
+     * case completion is called a second time if revalidating it finds a newer document.
+     */
     fun loadUrl(ctx: Context, url: String, completion: (Result<ShardRoot>) -> Unit): Job {
         assert(isInitialized()) { "Must call ShardViewManager.init() from your Application class" }
 
         val handler = Handler(Looper.getMainLooper())
         return GlobalScope.launch {
             try {
-                val request = Request.Builder()
-                        .url(url)
-                        .header("content-type", "application/shard")
-                        .build()
-
-                val response = httpClient.newCall(request).execute()
-                val json = response.body()!!.string()
-                handler.post { completion(loadJson(ctx, json)) }
+                loadDocumentStaleWhileRevalidate(url, cacheDir(ctx), object : DocumentCallback {
+                    override fun onDocument(json: String) {
+                        handler.post { completion(loadJson(ctx, json)) }
+                    }
+                })
             } catch (e: Exception) {
                 handler.post { completion(Result.error(e)) }
             }
         }
     }
 
+    /**
+     * Load a document while blocking the calling thread, for navigators which render the targets
+     * of actions as they run.
+     */
+    internal fun loadUrlBlocking(ctx: Context, url: String): Result<ShardRoot> {
+        return try {
+            val json = runBlocking(Dispatchers.IO) { loadDocument(url, cacheDir(ctx)) }
+            loadJson(ctx, json)
+        } catch (e: Exception) {
+            Result.error(e)
+        }
+    }
+
+    private fun cacheDir(ctx: Context): String {
+        return File(ctx.cacheDir, "shard").path
+    }
+
+    /**
+     * Fetch a document for the loader, which caches it and falls back to the cached copy when
+     * offline. CBOR and MessagePack bodies are decoded by the loader.
+     */
+    @Keep private fun fetch(url: String, etag: String?): ShardResponse {
+        val request = Request.Builder()
+                .url(url)
+                .header("content-type", "application/shard")
+                .header("accept", "application/cbor, application/msgpack, application/json")
+
+        if (etag != null) {
+            request.header("if-none-match", etag)
+        }
+
+        httpClient.newCall(request.build()).execute().use { response ->
+            return ShardResponse(
+                    response.code(),
+                    response.body()?.bytes() ?: ByteArray(0),
+                    response.header("content-type"),
+                    response.header("etag"),
+                    response.header("cache-control"))
+        }
+    }
+
     fun loadJson(ctx: Context, json: JsonValue): Result<ShardRoot> {
         return loadJson(ctx, json.toString())
     }
 
     fun loadJson(ctx: Context, json: String): Result<ShardRoot> {
+        return load(ctx) { render(it, json) }
+    }
+
+    /**
+     * Load a document encoded as JSON, CBOR or MessagePack. The encoding is taken from
+     * contentType when it names one and detected from the first byte otherwise.
+     */
+    fun loadBytes(ctx: Context, bytes: ByteArray, contentType: String? = null): Result<ShardRoot> {
+        return load(ctx) { renderBytes(it, bytes, contentType) }
+    }
+
+    private fun load(ctx: Context, render: (ShardContext) -> Long): Result<ShardRoot> {
         assert(isInitialized()) { "Must call ShardViewManager.init() from your Application class" }
         return try {
             val ctx = ShardContext(ctx)
-            Result.success(ShardRoot(ctx, render(ctx, json)))
+            val root = ShardRoot(ctx, render(ctx))
+            if (ctx.applicationInfo.flags and ApplicationInfo.FLAG_DEBUGGABLE != 0) {
+                root.warnings.forEach { Log.w("Shard", it) }
+            }
+            Result.success(root)
         } catch (e: Exception) {
             Result.error(e)
         }
     }
 
+    /**
+     * Resolve the fragments included by documents loaded from now on. Fragments are resolved on
+     * the thread documents are loaded on, so call this from the main thread. Fragments resolved
+     * so far are dropped.
+     */
+    fun setFragmentResolver(resolver: FragmentResolver) {
+        bindFragmentResolver(resolver)
+    }
+
     @Suppress("UNCHECKED_CAST")
     fun setViewImpl(kind: String, implFactory: (ShardContext) -> ShardViewImpl<out View>) {
         implFactories[kind] = implFactory as (ShardContext) -> ShardViewImpl<View>
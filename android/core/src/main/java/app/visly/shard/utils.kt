@@ -14,6 +14,13 @@ data class Size(val width: Float, val height: Float)
 
 data class Color(val default: Int, val pressed: Int?)
 
+/**
+ * Parses CSS colors into ARGB ints. 8 digit hex colors are read as `#rrggbbaa`.
+ */
+object ColorParser {
+    @JvmStatic external fun parse(color: String): Int
+}
+
 fun dipsToPixels(ctx: Context, dips: Float): Float {
     val scale = ctx.resources.displayMetrics.density
     return Math.round(dips * scale).toFloat()
@@ -25,28 +32,36 @@ fun sipsToPixels(ctx: Context, dips: Float): Float {
 }
 
 // Patch android Color.parseColor() to handle #F00
+@Deprecated("Use ColorParser.parse, which reads 8 digit hex colors as #rrggbbaa rather than #aarrggbb")
 fun parseColorString(color: String): Int {
     return android.graphics.Color.parseColor(if(color.length == 4) {
         "#" + color[1] + color[1] + color[2] + color[2] + color[3] + color[3]
     } else color)
 }
 
+// The core normalizes colors it sets on views to #aarrggbb, other colors are parsed as CSS
+private fun parseViewColor(color: String): Int {
+    return ColorParser.parse(if (color.length == 9 && color.startsWith("#")) {
+        "#" + color.substring(3) + color.substring(1, 3)
+    } else color)
+}
+
 fun JsonValue.toColor(): Color {
     return when (this) {
-        is JsonValue.String -> Color(parseColorString(this.value), null)
+        is JsonValue.String -> Color(parseViewColor(this.value), null)
         is JsonValue.Object -> {
             val default = (this.value["default"] as JsonValue.String).value
             val pressed = (this.value["pressed"] as JsonValue.String?)?.value
             Color(
-                    parseColorString(default),
-                    if (pressed == null) null else parseColorString(pressed))
+                    parseViewColor(default),
+                    if (pressed == null) null else parseViewColor(pressed))
         }
         else -> throw IllegalArgumentException()
     }
 }
 
 fun JsonValue.Object.toDips(ctx: Context): Float {
-    val value = (this.value["value"] as JsonValue.Number).value
+    val value = (this.value["value"] as JsonValue.Number).value.toFloat()
     val unit = this.value["unit"]
 
     return when (unit) {
@@ -57,7 +72,7 @@ fun JsonValue.Object.toDips(ctx: Context): Float {
 }
 
 fun JsonValue.Object.toSips(ctx: Context): Float {
-    val value = (this.value["value"] as JsonValue.Number).value
+    val value = (this.value["value"] as JsonValue.Number).value.toFloat()
     val unit = this.value["unit"]
 
     return when (unit) {
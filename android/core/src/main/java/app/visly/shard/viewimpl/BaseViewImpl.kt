@@ -44,11 +44,7 @@ abstract class BaseViewImpl<T: View>(val ctx: ShardContext): ShardViewImpl<T> {
 
             "on-click" -> {
                 onClick = when (value) {
-                    is JsonValue.Object -> View.OnClickListener {
-                        val action = (value.value.get("action") as JsonValue.String).value
-                        val value = value.value.get("value")
-                        ctx.dispatch(action, value)
-                    }
+                    is JsonValue.Object, is JsonValue.Array -> View.OnClickListener { ctx.dispatch(value) }
                     else -> null
                 }
             }
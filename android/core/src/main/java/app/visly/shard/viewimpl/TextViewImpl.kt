@@ -10,7 +10,12 @@ package app.visly.shard.viewimpl
 import android.graphics.Color
 import android.graphics.Typeface
 import android.text.*
+import android.text.method.LinkMovementMethod
+import android.text.style.ClickableSpan
+import android.text.style.StrikethroughSpan
+import android.text.style.UnderlineSpan
 import android.util.TypedValue
+import android.view.View
 import android.widget.TextView
 import app.visly.shard.*
 import com.facebook.fbui.textlayoutbuilder.TextLayoutBuilder
@@ -68,69 +73,77 @@ class TextViewImpl(ctx: ShardContext): BaseViewImpl<TextView>(ctx) {
 
             "line-height" -> {
                 spacingMultiplier = when (value) {
-                    is JsonValue.Object -> (value.value["value"] as JsonValue.Number).value
+                    is JsonValue.Object -> (value.value["value"] as JsonValue.Number).value.toFloat()
                     else -> 1f
                 }
             }
 
-            "span" -> {
+            "runs" -> {
                 textSpan = when (value) {
-                    is JsonValue.Object -> makeSpan(value.value)
+                    is JsonValue.Array -> makeSpan(value.value)
                     else -> SpannableString("")
                 }
             }
         }
     }
 
-    fun makeSpan(props: Map<String, JsonValue>): Spannable {
+    fun makeSpan(runs: List<JsonValue>): Spannable {
         val span = SpannableStringBuilder()
 
-        when (val value = props["text"]) {
-            is JsonValue.String -> { span.append(value.value) }
-            is JsonValue.Array -> {
-                for (i in 0 until value.value.size) {
-                    when (val child = value.value.get(i)) {
-                        is JsonValue.Object -> span.append(makeSpan(child.value))
-                    }
-                }
+        for (run in runs) {
+            val props = (run as JsonValue.Object).value
+            val start = span.length
+            span.append((props["text"] as JsonValue.String).value)
+            val end = span.length
+
+            val fontFamily = when (val value = props["font-family"]) {
+                is JsonValue.String -> value.value
+                else -> null
             }
-        }
 
-        val fontColor = when (val value = props["font-color"]) {
-            null -> null
-            else -> value.toColor().default
-        }
+            val fontStyle = when (props["font-style"]) {
+                JsonValue.String("italic") -> Typeface.ITALIC
+                else -> Typeface.NORMAL
+            }
 
-        val fontFamily = when (val value = props["font-family"]) {
-            is JsonValue.String -> value.value
-            else -> null
-        }
+            val fontWeight = when (props["font-weight"]) {
+                JsonValue.String("bold") -> Typeface.BOLD
+                else -> Typeface.NORMAL
+            }
 
-        val fontStyle = when (val value = props["font-style"]) {
-            JsonValue.String("normal") -> Typeface.NORMAL
-            JsonValue.String("italic") -> Typeface.ITALIC
-            else -> null
-        }
+            val fontSize = (props["font-size"] as JsonValue.Object).toSips(ctx)
+            val fontColor = props["font-color"]!!.toColor().default
 
-        val fontWeight = when (val value = props["font-weight"]) {
-            JsonValue.String("regular") -> Typeface.NORMAL
-            JsonValue.String("bold") -> Typeface.BOLD
-            else -> null
-        }
+            span.setSpan(
+                    ShardTextSpan(fontStyle, fontWeight, fontSize, fontColor, fontFamily, ctx),
+                    start,
+                    end,
+                    Spanned.SPAN_INCLUSIVE_EXCLUSIVE)
 
-        val fontSize = when (val value = props["font-size"]) {
-            is JsonValue.Object -> value.toSips(ctx)
-            else -> null
-        }
+            when (val lineHeight = props["line-height"]) {
+                is JsonValue.Number ->
+                    span.setSpan(ShardLineHeightSpan(lineHeight.value.toFloat()), start, end, Spanned.SPAN_INCLUSIVE_EXCLUSIVE)
+            }
 
-        val start = 0
-        val end = span.length
+            when (props["text-decoration"]) {
+                JsonValue.String("underline") ->
+                    span.setSpan(UnderlineSpan(), start, end, Spanned.SPAN_INCLUSIVE_EXCLUSIVE)
+                JsonValue.String("line-through") ->
+                    span.setSpan(StrikethroughSpan(), start, end, Spanned.SPAN_INCLUSIVE_EXCLUSIVE)
+            }
 
-        span.setSpan(
-                ShardTextSpan(fontStyle, fontWeight, fontSize, fontColor, fontFamily, ctx),
-                start,
-                end,
-                Spanned.SPAN_INCLUSIVE_EXCLUSIVE)
+            when (val action = props["action"]) {
+                is JsonValue.Object, is JsonValue.Array -> {
+                    span.setSpan(object : ClickableSpan() {
+                        override fun onClick(widget: View) {
+                            ctx.dispatch(action)
+                        }
+
+                        override fun updateDrawState(paint: TextPaint) {}
+                    }, start, end, Spanned.SPAN_INCLUSIVE_EXCLUSIVE)
+                }
+            }
+        }
 
         return span
     }
@@ -147,5 +160,10 @@ class TextViewImpl(ctx: ShardContext): BaseViewImpl<TextView>(ctx) {
         view.maxLines = maxLines
         view.textAlignment = textAlign
         view.text = textSpan
+        view.movementMethod = if (textSpan.getSpans(0, textSpan.length, ClickableSpan::class.java).isNotEmpty()) {
+            LinkMovementMethod.getInstance()
+        } else {
+            null
+        }
     }
 }
\ No newline at end of file
@@ -7,6 +7,7 @@
 
 package app.visly.shard
 
+import org.junit.BeforeClass
 import org.junit.Test
 import org.junit.Assert.assertEquals
 import org.junit.runner.RunWith
@@ -14,6 +15,11 @@ import org.robolectric.RobolectricTestRunner
 
 @RunWith(RobolectricTestRunner::class)
 class UtilsTest {
+    companion object {
+        // The host build of the core, see testOptions in build.gradle
+        @BeforeClass @JvmStatic fun loadCore() = System.loadLibrary("shard")
+    }
+
 
     @Test
     fun testParseSimpleColor() {
@@ -14,6 +14,7 @@ import app.visly.shard.Color
 import app.visly.shard.Size
 import app.visly.shard.ShardContext
 import org.junit.Assert.assertEquals
+import org.junit.BeforeClass
 import org.junit.Test
 import org.junit.Before
 import org.junit.runner.RunWith
@@ -23,6 +24,11 @@ import org.robolectric.RobolectricTestRunner
 
 @RunWith(RobolectricTestRunner::class)
 class BaseViewImplTest {
+    companion object {
+        // The host build of the core, see testOptions in build.gradle
+        @BeforeClass @JvmStatic fun loadCore() = System.loadLibrary("shard")
+    }
+
     lateinit var viewImpl: BaseViewImpl<View>
 
     @Before
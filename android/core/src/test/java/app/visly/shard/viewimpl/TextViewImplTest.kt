@@ -11,6 +11,8 @@ import android.widget.TextView
 import androidx.test.core.app.ApplicationProvider
 import app.visly.shard.JsonValue
 import app.visly.shard.ShardContext
+import app.visly.shard.ShardLineHeightSpan
+import org.junit.BeforeClass
 import org.junit.Test
 import org.junit.Assert.assertEquals
 import org.junit.Before
@@ -19,6 +21,11 @@ import org.robolectric.RobolectricTestRunner
 
 @RunWith(RobolectricTestRunner::class)
 class TextViewImplTest {
+    companion object {
+        // The host build of the core, see testOptions in build.gradle
+        @BeforeClass @JvmStatic fun loadCore() = System.loadLibrary("shard")
+    }
+
     lateinit var context: ShardContext
 
     @Before
@@ -65,31 +72,39 @@ class TextViewImplTest {
     }
 
     @Test
-    fun testSetSpanSimple() {
+    fun testSetRunsSimple() {
         val viewImpl = TextViewImpl(context)
-        viewImpl.setProp("span", JsonValue.Object(mapOf(
-                "text" to JsonValue.String("hello")
-        )))
+        viewImpl.setProp("runs", JsonValue.Array(listOf(run("hello"))))
         assertEquals(viewImpl.textSpan.toString(), "hello")
     }
 
     @Test
-    fun testSetSpanComplex() {
+    fun testSetRunsComplex() {
         val viewImpl = TextViewImpl(context)
-        viewImpl.setProp("span", JsonValue.Object(mapOf(
-                "text" to JsonValue.Array(listOf(
-                        JsonValue.Object(mapOf(
-                                "text" to JsonValue.String("hello")
-                        )),
-                        JsonValue.Object(mapOf(
-                                "text" to JsonValue.String(" ")
-                        )),
-                        JsonValue.Object(mapOf(
-                                "text" to JsonValue.String("world")
-                        ))
-
-                ))
-        )))
+        viewImpl.setProp("runs", JsonValue.Array(listOf(run("hello"), run(" "), run("world"))))
         assertEquals(viewImpl.textSpan.toString(), "hello world")
     }
+
+    @Test
+    fun testSetRunsLineHeight() {
+        val viewImpl = TextViewImpl(context)
+        val lineHeight = JsonValue.Object(run("hello").value + ("line-height" to JsonValue.Number(1.5f)))
+        viewImpl.setProp("runs", JsonValue.Array(listOf(lineHeight, run(" world"))))
+
+        val spans = viewImpl.textSpan.getSpans(0, viewImpl.textSpan.length, ShardLineHeightSpan::class.java)
+        assertEquals(spans.size, 1)
+        assertEquals(viewImpl.textSpan.getSpanEnd(spans[0]), 5)
+    }
+
+    private fun run(text: String) = JsonValue.Object(mapOf(
+            "text" to JsonValue.String(text),
+            "font-weight" to JsonValue.String("regular"),
+            "font-style" to JsonValue.String("normal"),
+            "font-size" to JsonValue.Object(mapOf(
+                    "unit" to JsonValue.String("points"),
+                    "value" to JsonValue.Number(12f)
+            )),
+            "font-color" to JsonValue.String("#ff000000"),
+            "text-decoration" to JsonValue.String("none")
+    ))
 }
@@ -8,14 +8,16 @@ use core;
 
 use json::JsonValue;
 use std::any::Any;
+use std::cell::RefCell;
 use std::f32;
+use std::rc::Rc;
 use stretch::geometry::Rect;
 use stretch::geometry::Size;
 use stretch::number::*;
 use stretch::result::Result;
 
-use jni::objects::{GlobalRef, JObject, JString, JThrowable, JValue};
-use jni::sys::{jlong, jobject};
+use jni::objects::{GlobalRef, JClass, JObject, JString, JThrowable, JValue};
+use jni::sys::{jboolean, jbyteArray, jfloat, jfloatArray, jint, jlong, jobject, jobjectArray, JNI_TRUE};
 use jni::JNIEnv;
 
 pub struct JavaObject {
@@ -23,12 +25,28 @@ pub struct JavaObject {
     env: JNIEnv<'static>,
 }
 
+thread_local! {
+    /// Shared by the roots rendered on a thread, as JNIEnv and with it resolvers are per thread.
+    static FRAGMENTS: RefCell<Rc<core::Fragments>> = RefCell::default();
+}
+
 fn rust_obj(env: &JNIEnv, j_obj: JObject) -> Box<JavaObject> {
     let ptr = env.get_field(j_obj, "rustPtr", "J").unwrap();
     unsafe { Box::from_raw(ptr.j().unwrap() as *mut JavaObject) }
 }
 
-fn throw(env: &JNIEnv, err: Box<Any>) {
+fn root(env: &JNIEnv, instance: JObject) -> Box<core::Root> {
+    let ptr = env.get_field(instance, "rustPtr", "J").unwrap();
+    unsafe { Box::from_raw(ptr.j().unwrap() as *mut core::Root) }
+}
+
+/// A `JNIEnv` which objects created during a call can hold on to. It is only valid on the
+/// calling thread.
+fn thread_env(env: &JNIEnv) -> JNIEnv<'static> {
+    unsafe { JNIEnv::from_raw(env.get_native_interface()).unwrap() }
+}
+
+fn throw(env: &JNIEnv, err: Box<dyn Any>) {
     if let Some(exception) = err.downcast_ref::<GlobalRef>() {
         env.throw(JThrowable::from(exception.as_obj())).unwrap();
     } else if let Some(error) = err.downcast_ref::<&str>() {
@@ -45,7 +63,7 @@ impl JavaObject {
         Box::new(JavaObject { instance: env.new_global_ref(instance).unwrap(), env })
     }
 
-    fn call_method(&self, name: &str, signature: &str, params: &[jni::objects::JValue]) -> Result<JValue> {
+    fn call_method(&self, name: &str, signature: &str, params: &[jni::objects::JValue]) -> Result<JValue<'_>> {
         let result = self.env.call_method(self.instance.as_obj(), name, signature, params);
 
         match result {
@@ -60,8 +78,135 @@ impl JavaObject {
     }
 }
 
+fn java_value<'a>(env: &'a JNIEnv<'a>, value: &JsonValue) -> JObject<'a> {
+    match value {
+        JsonValue::Null => env
+            .get_static_field("app/visly/shard/JsonValue$Null", "INSTANCE", "Lapp/visly/shard/JsonValue$Null;")
+            .unwrap()
+            .l()
+            .unwrap(),
+
+        JsonValue::Boolean(value) => {
+            env.new_object("app/visly/shard/JsonValue$Boolean", "(Z)V", &[JValue::from(*value)]).unwrap()
+        }
+
+        JsonValue::Number(_) => env
+            .new_object("app/visly/shard/JsonValue$Number", "(D)V", &[JValue::from(value.as_f64().unwrap())])
+            .unwrap(),
+
+        JsonValue::Short(_) | JsonValue::String(_) => {
+            let string = env.new_string(value.as_str().unwrap()).unwrap();
+            let result = env
+                .new_object("app/visly/shard/JsonValue$String", "(Ljava/lang/String;)V", &[JValue::from(*string)])
+                .unwrap();
+            env.delete_local_ref(*string).unwrap();
+            result
+        }
+
+        JsonValue::Object(object) => {
+            let map = env.new_object("java/util/LinkedHashMap", "()V", &[]).unwrap();
+            for (key, value) in object.iter() {
+                let key = env.new_string(key).unwrap();
+                let value = java_value(env, value);
+                let previous = env
+                    .call_method(
+                        map,
+                        "put",
+                        "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+                        &[JValue::from(*key), JValue::from(value)],
+                    )
+                    .unwrap();
+                env.delete_local_ref(previous.l().unwrap()).unwrap();
+                env.delete_local_ref(*key).unwrap();
+                env.delete_local_ref(value).unwrap();
+            }
+
+            let result =
+                env.new_object("app/visly/shard/JsonValue$Object", "(Ljava/util/Map;)V", &[JValue::from(map)]).unwrap();
+            env.delete_local_ref(map).unwrap();
+            result
+        }
+
+        JsonValue::Array(array) => {
+            let list = env.new_object("java/util/ArrayList", "(I)V", &[JValue::from(array.len() as i32)]).unwrap();
+            for value in array {
+                let value = java_value(env, value);
+                env.call_method(list, "add", "(Ljava/lang/Object;)Z", &[JValue::from(value)]).unwrap();
+                env.delete_local_ref(value).unwrap();
+            }
+
+            let result = env
+                .new_object("app/visly/shard/JsonValue$Array", "(Ljava/util/List;)V", &[JValue::from(list)])
+                .unwrap();
+            env.delete_local_ref(list).unwrap();
+            result
+        }
+    }
+}
+
+/// The inverse of `java_value`, reading an `app.visly.shard.JsonValue` passed in by the host.
+fn rust_value(env: &JNIEnv, value: JObject) -> Result<JsonValue> {
+    let error = |err| jni_error(env, err);
+    let is = |class: &str| env.is_instance_of(value, class).map_err(error);
+    let get = |signature: &str| env.call_method(value, "getValue", signature, &[]).map_err(error);
+
+    if is("app/visly/shard/JsonValue$Boolean")? {
+        Ok(JsonValue::Boolean(get("()Z")?.z().map_err(error)?))
+    } else if is("app/visly/shard/JsonValue$Number")? {
+        Ok(JsonValue::from(get("()D")?.d().map_err(error)?))
+    } else if is("app/visly/shard/JsonValue$String")? {
+        let string = get("()Ljava/lang/String;")?.l().map_err(error)?;
+        let result: String = env.get_string(JString::from(string)).map_err(error)?.into();
+        env.delete_local_ref(string).map_err(error)?;
+        Ok(JsonValue::from(result))
+    } else if is("app/visly/shard/JsonValue$Object")? {
+        let map = get("()Ljava/util/Map;")?.l().map_err(error)?;
+        let entries = env.call_method(map, "entrySet", "()Ljava/util/Set;", &[]).and_then(JValue::l).map_err(error)?;
+        let iterator =
+            env.call_method(entries, "iterator", "()Ljava/util/Iterator;", &[]).and_then(JValue::l).map_err(error)?;
+
+        let mut object = JsonValue::new_object();
+        while env.call_method(iterator, "hasNext", "()Z", &[]).and_then(JValue::z).map_err(error)? {
+            let entry =
+                env.call_method(iterator, "next", "()Ljava/lang/Object;", &[]).and_then(JValue::l).map_err(error)?;
+            let key =
+                env.call_method(entry, "getKey", "()Ljava/lang/Object;", &[]).and_then(JValue::l).map_err(error)?;
+            let item =
+                env.call_method(entry, "getValue", "()Ljava/lang/Object;", &[]).and_then(JValue::l).map_err(error)?;
+            let key_string: String = env.get_string(JString::from(key)).map_err(error)?.into();
+            object[key_string.as_str()] = rust_value(env, item)?;
+            env.delete_local_ref(item).map_err(error)?;
+            env.delete_local_ref(key).map_err(error)?;
+            env.delete_local_ref(entry).map_err(error)?;
+        }
+
+        env.delete_local_ref(iterator).map_err(error)?;
+        env.delete_local_ref(entries).map_err(error)?;
+        env.delete_local_ref(map).map_err(error)?;
+        Ok(object)
+    } else if is("app/visly/shard/JsonValue$Array")? {
+        let list = get("()Ljava/util/List;")?.l().map_err(error)?;
+        let size = env.call_method(list, "size", "()I", &[]).and_then(JValue::i).map_err(error)?;
+
+        let mut array = JsonValue::new_array();
+        for i in 0..size {
+            let item = env
+                .call_method(list, "get", "(I)Ljava/lang/Object;", &[JValue::from(i)])
+                .and_then(JValue::l)
+                .map_err(error)?;
+            array.push(rust_value(env, item)?).unwrap();
+            env.delete_local_ref(item).map_err(error)?;
+        }
+
+        env.delete_local_ref(list).map_err(error)?;
+        Ok(array)
+    } else {
+        Ok(JsonValue::Null)
+    }
+}
+
 impl core::ShardViewManager for JavaObject {
-    fn create_view(&self, context: &Any, kind: &str) -> Result<Box<core::ShardView>> {
+    fn create_view(&self, context: &dyn Any, kind: &str) -> Result<Box<dyn core::ShardView>> {
         let kind = self.env.new_string(kind).unwrap();
         let context = context.downcast_ref::<GlobalRef>().unwrap();
 
@@ -79,7 +224,7 @@ impl core::ShardViewManager for JavaObject {
 }
 
 impl core::ShardView for JavaObject {
-    fn add_child(&mut self, child: &core::ShardView) -> Result<()> {
+    fn add_child(&mut self, child: &dyn core::ShardView) -> Result<()> {
         let child = child.as_any().downcast_ref::<JavaObject>().unwrap();
         let result =
             self.call_method("addChild", "(Lapp/visly/shard/ShardView;)V", &[JValue::from(child.instance.as_obj())]);
@@ -90,15 +235,42 @@ impl core::ShardView for JavaObject {
         }
     }
 
+    fn remove_child(&mut self, child: &dyn core::ShardView) -> Result<()> {
+        let child = child.as_any().downcast_ref::<JavaObject>().unwrap();
+        let result =
+            self.call_method("removeChild", "(Lapp/visly/shard/ShardView;)V", &[JValue::from(child.instance.as_obj())]);
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn insert_child(&mut self, child: &dyn core::ShardView, index: usize) -> Result<()> {
+        let child = child.as_any().downcast_ref::<JavaObject>().unwrap();
+        let result = self.call_method(
+            "insertChild",
+            "(Lapp/visly/shard/ShardView;I)V",
+            &[JValue::from(child.instance.as_obj()), JValue::Int(index as i32)],
+        );
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
     fn set_prop(&mut self, key: &str, value: &JsonValue) -> Result<()> {
         let key = self.env.new_string(key).unwrap();
-        let value = self.env.new_string(value.dump()).unwrap();
+        let value = java_value(&self.env, value);
 
         let result = self.call_method(
             "setProp",
-            "(Ljava/lang/String;Ljava/lang/String;)V",
-            &[JValue::from(JObject::from(key)), JValue::from(JObject::from(value))],
+            "(Ljava/lang/String;Lapp/visly/shard/JsonValue;)V",
+            &[JValue::from(JObject::from(key)), JValue::from(value)],
         );
+        self.env.delete_local_ref(JObject::from(key)).unwrap();
+        self.env.delete_local_ref(value).unwrap();
 
         match result {
             Ok(_) => Ok(()),
@@ -138,11 +310,130 @@ impl core::ShardView for JavaObject {
         }
     }
 
-    fn as_any(&self) -> &Any {
+    fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+impl core::ActionHandler for JavaObject {
+    fn on_action(&mut self, action: &str, value: &JsonValue) -> Result<()> {
+        let action = self.env.new_string(action).unwrap();
+        let value = if value.is_null() { JObject::null() } else { java_value(&self.env, value) };
+
+        let result = self.call_method(
+            "dispatch",
+            "(Ljava/lang/String;Lapp/visly/shard/JsonValue;)V",
+            &[JValue::from(JObject::from(action)), JValue::from(value)],
+        );
+        if !value.is_null() {
+            self.env.delete_local_ref(value).unwrap();
+        }
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Navigation is driven by an `app.visly.shard.ShardNavigator`, which renders targets into roots
+/// it gives up ownership of and passes transitions on to the app.
+impl core::NavigatorHost for JavaObject {
+    fn render(&mut self, target: &JsonValue) -> Result<Box<core::Root>> {
+        let target = java_value(&self.env, target);
+        let result = self.call_method(
+            "render",
+            "(Lapp/visly/shard/JsonValue;)Lapp/visly/shard/ShardRoot;",
+            &[JValue::from(target)],
+        );
+        self.env.delete_local_ref(target).unwrap();
+
+        let root = result?.l().unwrap();
+        let rust_ptr = self.env.get_field(root, "rustPtr", "J").unwrap();
+        self.env.delete_local_ref(root).unwrap();
+        Ok(unsafe { Box::from_raw(rust_ptr.j().unwrap() as *mut core::Root) })
+    }
+
+    fn transition(
+        &mut self,
+        transition: core::Transition,
+        from: Option<&core::NavigationEntry>,
+        to: &core::NavigationEntry,
+    ) -> Result<()> {
+        let transition = match transition {
+            core::Transition::Push => 0,
+            core::Transition::Pop => 1,
+            core::Transition::Replace => 2,
+        };
+        let from = from.map_or(0, |from| &*from.root as *const core::Root as jlong);
+        let result = self.call_method(
+            "transition",
+            "(IJJFF)V",
+            &[
+                JValue::from(transition),
+                JValue::from(from),
+                JValue::from(&*to.root as *const core::Root as jlong),
+                JValue::from(to.scroll_position.x),
+                JValue::from(to.scroll_position.y),
+            ],
+        );
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Documents are fetched by `ShardViewManager.fetch` on the thread loading them, which hands back an
+/// `app.visly.shard.ShardResponse`.
+impl core::Transport for JavaObject {
+    fn fetch(&mut self, request: &core::Request) -> Result<core::Response> {
+        let env = &self.env;
+        let error = |err| jni_error(env, err);
+        let url = JObject::from(env.new_string(&request.url).map_err(error)?);
+        let etag = match &request.etag {
+            Some(etag) => JObject::from(env.new_string(etag).map_err(error)?),
+            None => JObject::null(),
+        };
+        let result = self.call_method(
+            "fetch",
+            "(Ljava/lang/String;Ljava/lang/String;)Lapp/visly/shard/ShardResponse;",
+            &[JValue::from(url), JValue::from(etag)],
+        );
+        env.delete_local_ref(url).unwrap();
+        let response = result?.l().unwrap();
+
+        let string = |name: &str| -> Result<Option<String>> {
+            let value = env.get_field(response, name, "Ljava/lang/String;").and_then(JValue::l).map_err(error)?;
+            if value.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(env.get_string(JString::from(value)).map_err(error)?.into()))
+        };
+        let status = env.get_field(response, "status", "I").and_then(JValue::i).map_err(error)?;
+        let body = env.get_field(response, "body", "[B").and_then(JValue::l).map_err(error)?;
+        let body = env.convert_byte_array(body.into_inner()).map_err(error)?;
+        let content_type = string("contentType")?;
+
+        let decoded = core::Response::from_bytes(status as u16, &body, content_type.as_deref())?;
+        Ok(core::Response { etag: string("etag")?, cache_control: string("cacheControl")?, ..decoded })
+    }
+}
+
+/// Fragments are resolved by an `app.visly.shard.ShardViewManager.FragmentResolver`.
+impl core::FragmentResolver for JavaObject {
+    fn resolve(&self, id: &str) -> Result<String> {
+        let id = JObject::from(self.env.new_string(id).unwrap());
+        let result = self.call_method("resolve", "(Ljava/lang/String;)Ljava/lang/String;", &[JValue::from(id)]);
+        self.env.delete_local_ref(id).unwrap();
+
+        let json = JString::from(result?.l().unwrap());
+        let error = |err| jni_error(&self.env, err);
+        Ok(self.env.get_string(json).map_err(error)?.into())
+    }
+}
+
 #[no_mangle]
 #[allow(non_snake_case)]
 pub unsafe extern "C" fn Java_app_visly_shard_ShardViewManager_bind(env: JNIEnv<'static>, instance: JObject) -> jlong {
@@ -155,6 +446,120 @@ pub unsafe extern "C" fn Java_app_visly_shard_ShardViewManager_free(env: JNIEnv<
     let _view_manager = rust_obj(&env, instance);
 }
 
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardViewManager_loadDocument(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    url: JString,
+    cache_dir: JString,
+) -> jobject {
+    let url: String = env.get_string(url).unwrap().into();
+    let cache_dir: String = env.get_string(cache_dir).unwrap().into();
+    // Documents are loaded with a transport bound to the calling thread
+    let transport = JavaObject::new(thread_env(&env), instance);
+    let mut loader = core::DocumentLoader::new(transport, cache_dir.into());
+
+    match loader.load(&url) {
+        Ok(document) => env.new_string(document.body).unwrap().into_inner(),
+        Err(err) => {
+            throw(&env, err);
+            JObject::null().into_inner()
+        }
+    }
+}
+
+/// Like `loadDocument`, but a stale cached copy is passed to `callback` before revalidating so it
+/// can be shown right away, and the document again only if it changed.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardViewManager_loadDocumentStaleWhileRevalidate(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    url: JString,
+    cache_dir: JString,
+    callback: JObject,
+) {
+    let url: String = env.get_string(url).unwrap().into();
+    let cache_dir: String = env.get_string(cache_dir).unwrap().into();
+    let transport = JavaObject::new(thread_env(&env), instance);
+    let mut loader = core::DocumentLoader::new(transport, cache_dir.into());
+
+    // An exception thrown by the callback is taken over so loading can go on, and rethrown after
+    let mut callback_error = None;
+    let result = loader.load_stale_while_revalidate(&url, &mut |document| {
+        let json = JObject::from(env.new_string(document.body).unwrap());
+        if let Err(err) = env.call_method(callback, "onDocument", "(Ljava/lang/String;)V", &[JValue::from(json)]) {
+            callback_error.get_or_insert_with(|| jni_error(&env, err));
+        }
+        env.delete_local_ref(json).unwrap();
+    });
+
+    if let Some(err) = result.err().or(callback_error) {
+        throw(&env, err);
+    }
+}
+
+/// Resolve the fragments included by roots rendered on the calling thread from now on with
+/// `resolver`, dropping the fragments resolved so far.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardViewManager_bindFragmentResolver(
+    env: JNIEnv<'static>,
+    _instance: JObject,
+    resolver: JObject,
+) {
+    let resolver = JavaObject::new(env, resolver);
+    FRAGMENTS.with(|fragments| *fragments.borrow_mut() = Rc::new(core::Fragments::new(*resolver)));
+}
+
+const UI_MODE_NIGHT_MASK: i32 = 0x30;
+const UI_MODE_NIGHT_YES: i32 = 0x20;
+
+/// Turn a failed JNI call into an error for `throw`, taking over the Java exception it left pending.
+fn jni_error(env: &JNIEnv, err: jni::errors::Error) -> Box<dyn Any> {
+    if env.exception_check().unwrap_or(false) {
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        if let Ok(exception) = env.new_global_ref(*exception) {
+            return Box::new(exception);
+        }
+    }
+    Box::new(err.to_string())
+}
+
+fn render_context(env: &JNIEnv, context: JObject) -> Result<core::RenderContext> {
+    let error = |err| jni_error(env, err);
+    let mut render_context = core::RenderContext {
+        platform: Some(core::Platform::Android),
+        fragments: FRAGMENTS.with(|fragments| fragments.borrow().clone()),
+        ..Default::default()
+    };
+
+    let locale = env
+        .call_static_method("java/util/Locale", "getDefault", "()Ljava/util/Locale;", &[])
+        .and_then(JValue::l)
+        .map_err(error)?;
+    let tag =
+        env.call_method(locale, "toLanguageTag", "()Ljava/lang/String;", &[]).and_then(JValue::l).map_err(error)?;
+    render_context.locale = env.get_string(JString::from(tag)).map_err(error)?.into();
+
+    let resources = env
+        .call_method(context, "getResources", "()Landroid/content/res/Resources;", &[])
+        .and_then(JValue::l)
+        .map_err(error)?;
+    let configuration = env
+        .call_method(resources, "getConfiguration", "()Landroid/content/res/Configuration;", &[])
+        .and_then(JValue::l)
+        .map_err(error)?;
+    let ui_mode = env.get_field(configuration, "uiMode", "I").and_then(JValue::i).map_err(error)?;
+    if ui_mode & UI_MODE_NIGHT_MASK == UI_MODE_NIGHT_YES {
+        render_context.theme = core::ThemeMode::Dark;
+    }
+
+    Ok(render_context)
+}
+
 #[no_mangle]
 #[allow(non_snake_case)]
 pub extern "C" fn Java_app_visly_shard_ShardViewManager_render(
@@ -163,11 +568,58 @@ pub extern "C" fn Java_app_visly_shard_ShardViewManager_render(
     context: JObject,
     json: JString,
 ) -> jlong {
+    let render_context = match render_context(&env, context) {
+        Ok(render_context) => render_context,
+        Err(err) => {
+            throw(&env, err);
+            return 0;
+        }
+    };
     let view_manager = rust_obj(&env, instance);
     let context = env.new_global_ref(context).unwrap();
     let json = env.get_string(json).unwrap();
 
-    let root = core::render_root(Box::leak(view_manager), &context, json.to_str().unwrap());
+    let root =
+        core::render_root_with_context(Box::leak(view_manager), &context, &render_context, json.to_str().unwrap());
+
+    match root {
+        Ok(root) => Box::into_raw(Box::new(root)) as jlong,
+        Err(err) => {
+            throw(&env, err);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardViewManager_renderBytes(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    context: JObject,
+    bytes: jbyteArray,
+    content_type: JString,
+) -> jlong {
+    let render_context = match render_context(&env, context) {
+        Ok(render_context) => render_context,
+        Err(err) => {
+            throw(&env, err);
+            return 0;
+        }
+    };
+    let view_manager = rust_obj(&env, instance);
+    let context = env.new_global_ref(context).unwrap();
+    let bytes = env.convert_byte_array(bytes).unwrap();
+    let content_type: Option<String> =
+        if content_type.is_null() { None } else { Some(env.get_string(content_type).unwrap().into()) };
+
+    let root = core::render_root_from_bytes(
+        Box::leak(view_manager),
+        &context,
+        &render_context,
+        &bytes,
+        content_type.as_deref(),
+    );
 
     match root {
         Ok(root) => Box::into_raw(Box::new(root)) as jlong,
@@ -217,12 +669,319 @@ pub unsafe extern "C" fn Java_app_visly_shard_ShardRoot_measure(
     };
 }
 
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_setDarkMode(env: JNIEnv<'static>, instance: JObject, dark: jboolean) {
+    let mut root = root(&env, instance);
+
+    let result = root.set_theme_mode(if dark == JNI_TRUE { core::ThemeMode::Dark } else { core::ThemeMode::Light });
+    Box::leak(root);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_getWarnings(env: JNIEnv<'static>, instance: JObject) -> jobjectArray {
+    let root = root(&env, instance);
+
+    let warnings = env.new_object_array(root.warnings.len() as i32, "java/lang/String", JObject::null()).unwrap();
+    for (i, warning) in root.warnings.iter().enumerate() {
+        let warning = env.new_string(warning.to_string()).unwrap();
+        env.set_object_array_element(warnings, i as i32, JObject::from(warning)).unwrap();
+        env.delete_local_ref(JObject::from(warning)).unwrap();
+    }
+
+    Box::leak(root);
+    warnings
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_getPendingFragments(
+    env: JNIEnv<'static>,
+    instance: JObject,
+) -> jobjectArray {
+    let root = root(&env, instance);
+
+    let pending = root.pending_fragments();
+    let ids = env.new_object_array(pending.len() as i32, "java/lang/String", JObject::null()).unwrap();
+    for (i, id) in pending.iter().enumerate() {
+        let id = env.new_string(id).unwrap();
+        env.set_object_array_element(ids, i as i32, JObject::from(id)).unwrap();
+        env.delete_local_ref(JObject::from(id)).unwrap();
+    }
+
+    Box::leak(root);
+    ids
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_loadFragmentJson(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    view_manager: JObject,
+    ctx: JObject,
+    id: JString,
+    json: JString,
+) {
+    let mut root = root(&env, instance);
+    let view_manager = rust_obj(&env, view_manager);
+    let context = env.new_global_ref(ctx).unwrap();
+    let id: String = env.get_string(id).unwrap().into();
+    let json: String = env.get_string(json).unwrap().into();
+
+    let result = root.load_fragment(&*view_manager, &context, &id, &json);
+    Box::leak(view_manager);
+    Box::leak(root);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_getViewById(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    id: JString,
+) -> jobject {
+    let root = root(&env, instance);
+    let id: String = env.get_string(id).unwrap().into();
+
+    let view = root.view_by_id(&id).and_then(|view_node| view_node.shard_view.as_any().downcast_ref::<JavaObject>());
+    let local = match view {
+        Some(view) => env.new_local_ref::<JObject>(view.instance.as_obj()).unwrap().into_inner(),
+        None => JObject::null().into_inner(),
+    };
+
+    Box::leak(root);
+    local
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_getFrame(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    id: JString,
+) -> jfloatArray {
+    let root = root(&env, instance);
+    let id: String = env.get_string(id).unwrap().into();
+
+    let frame = match root.frame_of(&id) {
+        Some(frame) => {
+            let array = env.new_float_array(4).unwrap();
+            env.set_float_array_region(array, 0, &[frame.start, frame.end, frame.top, frame.bottom]).unwrap();
+            array
+        }
+        None => JObject::null().into_inner(),
+    };
+
+    Box::leak(root);
+    frame
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_setPropValue(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    id: JString,
+    key: JString,
+    value: JObject,
+) {
+    let mut root = root(&env, instance);
+    let id: String = env.get_string(id).unwrap().into();
+    let key: String = env.get_string(key).unwrap().into();
+
+    let result = rust_value(&env, value).and_then(|value| root.set_prop(&id, &key, value));
+    Box::leak(root);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_getState(env: JNIEnv<'static>, instance: JObject) -> jobject {
+    let root = root(&env, instance);
+    let state = java_value(&env, root.state()).into_inner();
+    Box::leak(root);
+    state
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_setStateValue(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    path: JString,
+    value: JObject,
+) {
+    let mut root = root(&env, instance);
+    let path: String = env.get_string(path).unwrap().into();
+
+    let result = rust_value(&env, value).and_then(|value| root.set_state(&path, value));
+    Box::leak(root);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_applyPatchValue(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    view_manager: JObject,
+    ctx: JObject,
+    patch: JObject,
+) {
+    let mut root = root(&env, instance);
+    let view_manager = rust_obj(&env, view_manager);
+    let context = env.new_global_ref(ctx).unwrap();
+
+    let result = rust_value(&env, patch).and_then(|patch| root.apply_patch(&*view_manager, &context, &patch));
+    Box::leak(view_manager);
+    Box::leak(root);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+/// Wrap nodes in `ShardNode` objects created by the root, which scales their frames.
+fn java_nodes(env: &JNIEnv, instance: JObject, nodes: &[&core::ViewNode]) -> jobjectArray {
+    let array = env.new_object_array(nodes.len() as i32, "app/visly/shard/ShardNode", JObject::null()).unwrap();
+    for (i, view_node) in nodes.iter().enumerate() {
+        let kind = JObject::from(env.new_string(view_node.kind()).unwrap());
+        let id = match view_node.id() {
+            Some(id) => JObject::from(env.new_string(id).unwrap()),
+            None => JObject::null(),
+        };
+        let view = match view_node.shard_view.as_any().downcast_ref::<JavaObject>() {
+            Some(view) => view.instance.as_obj(),
+            None => JObject::null(),
+        };
+        let frame = view_node.frame();
+
+        let node = env
+            .call_method(
+                instance,
+                "createNode",
+                "(Ljava/lang/String;Ljava/lang/String;FFFFLapp/visly/shard/ShardView;)Lapp/visly/shard/ShardNode;",
+                &[
+                    JValue::from(kind),
+                    JValue::from(id),
+                    JValue::from(frame.start),
+                    JValue::from(frame.end),
+                    JValue::from(frame.top),
+                    JValue::from(frame.bottom),
+                    JValue::from(view),
+                ],
+            )
+            .unwrap()
+            .l()
+            .unwrap();
+        env.set_object_array_element(array, i as i32, node).unwrap();
+        env.delete_local_ref(node).unwrap();
+        env.delete_local_ref(kind).unwrap();
+        if !id.is_null() {
+            env.delete_local_ref(id).unwrap();
+        }
+    }
+
+    array
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_hitTestNodes(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    x: jfloat,
+    y: jfloat,
+) -> jobjectArray {
+    let root = root(&env, instance);
+    let array = java_nodes(&env, instance, &root.hit_test(x, y));
+    Box::leak(root);
+    array
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_queryNodes(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    selector: JString,
+) -> jobjectArray {
+    let root = root(&env, instance);
+    let selector: String = env.get_string(selector).unwrap().into();
+
+    let nodes = match root.query(&selector) {
+        Ok(nodes) => nodes,
+        Err(err) => {
+            Box::leak(root);
+            throw(&env, err);
+            return JObject::null().into_inner();
+        }
+    };
+
+    let array = java_nodes(&env, instance, &nodes);
+    Box::leak(root);
+    array
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardRoot_dispatch(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    ctx: JObject,
+    action: JString,
+) {
+    let mut root = root(&env, instance);
+    let action: String = env.get_string(action).unwrap().into();
+    let mut handler = JavaObject::new(thread_env(&env), ctx);
+
+    let result = match json::parse(&action) {
+        Ok(action) => root.dispatch(&action, &mut *handler),
+        Err(err) => Err(Box::new(err.to_string()) as Box<dyn Any>),
+    };
+    Box::leak(root);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
 #[no_mangle]
 #[allow(non_snake_case)]
 pub unsafe extern "C" fn Java_app_visly_shard_ShardRoot_free(env: JNIEnv<'static>, instance: JObject) {
     let _root = rust_obj(&env, instance);
 }
 
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ColorParser_parse(env: JNIEnv<'static>, _class: JClass, color: JString) -> jint {
+    let color: String = env.get_string(color).unwrap().into();
+
+    match core::Color::parse(&color) {
+        Ok(color) => color.to_argb() as jint,
+        Err(err) => {
+            throw(&env, err);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 #[allow(non_snake_case)]
 pub unsafe extern "C" fn Java_app_visly_shard_ShardView_bind(env: JNIEnv<'static>, instance: JObject) -> jlong {
@@ -234,3 +993,109 @@ pub unsafe extern "C" fn Java_app_visly_shard_ShardView_bind(env: JNIEnv<'static
 pub unsafe extern "C" fn Java_app_visly_shard_ShardView_free(env: JNIEnv<'static>, instance: JObject) {
     let _shard_view = rust_obj(&env, instance);
 }
+
+fn navigator(env: &JNIEnv, instance: JObject) -> Box<core::ShardNavigator> {
+    let ptr = env.get_field(instance, "rustPtr", "J").unwrap();
+    unsafe { Box::from_raw(ptr.j().unwrap() as *mut core::ShardNavigator) }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardNavigator_bind(_env: JNIEnv<'static>, _instance: JObject) -> jlong {
+    Box::into_raw(Box::new(core::ShardNavigator::new())) as jlong
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardNavigator_free(env: JNIEnv<'static>, instance: JObject) {
+    let _navigator = navigator(&env, instance);
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardNavigator_pushTarget(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    target: JObject,
+) {
+    let mut navigator = navigator(&env, instance);
+    let mut host = JavaObject::new(thread_env(&env), instance);
+
+    let result = rust_value(&env, target).and_then(|target| navigator.push(&mut *host, &target));
+    Box::leak(navigator);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardNavigator_replaceTarget(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    target: JObject,
+) {
+    let mut navigator = navigator(&env, instance);
+    let mut host = JavaObject::new(thread_env(&env), instance);
+
+    let result = rust_value(&env, target).and_then(|target| navigator.replace(&mut *host, &target));
+    Box::leak(navigator);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardNavigator_popCount(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    count: jint,
+) -> jboolean {
+    let mut navigator = navigator(&env, instance);
+    let mut host = JavaObject::new(thread_env(&env), instance);
+
+    let result = navigator.pop(&mut *host, count.max(0) as usize);
+    Box::leak(navigator);
+
+    match result {
+        Ok(popped) => popped as jboolean,
+        Err(err) => {
+            throw(&env, err);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardNavigator_dispatchAction(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    action: JObject,
+) {
+    let mut navigator = navigator(&env, instance);
+    let mut host = JavaObject::new(thread_env(&env), instance);
+
+    let result = rust_value(&env, action).and_then(|action| navigator.dispatch(&mut *host, &action));
+    Box::leak(navigator);
+
+    if let Err(err) = result {
+        throw(&env, err);
+    }
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Java_app_visly_shard_ShardNavigator_setScrollPosition(
+    env: JNIEnv<'static>,
+    instance: JObject,
+    x: jfloat,
+    y: jfloat,
+) {
+    let mut navigator = navigator(&env, instance);
+    navigator.set_scroll_position(x, y);
+    Box::leak(navigator);
+}
@@ -10,18 +10,24 @@ use std::f32;
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
 use std::ptr;
+use std::rc::Rc;
 use stretch::geometry::Rect;
 use stretch::geometry::Size;
 use stretch::number::*;
 use stretch::result::Result;
 
+mod value;
+pub use crate::value::*;
+
 pub struct IOSViewManager {
     swift_ptr: *const c_void,
-    create_view: fn(*const c_void, *const c_void, *const c_char, *mut *const c_char) -> *mut IOSView,
+    create_view: extern "C" fn(*const c_void, *const c_void, *const c_char, *mut *const c_char) -> *mut IOSView,
+    /// Shared by every root the view manager renders.
+    fragments: Rc<core::Fragments>,
 }
 
 impl core::ShardViewManager for IOSViewManager {
-    fn create_view(&self, context: &Any, kind: &str) -> Result<Box<core::ShardView>> {
+    fn create_view(&self, context: &dyn Any, kind: &str) -> Result<Box<dyn core::ShardView>> {
         let context = context.downcast_ref::<*const c_void>().unwrap();
         let kind = CString::new(kind).unwrap();
         let create_view = self.create_view;
@@ -46,14 +52,16 @@ pub struct CSize {
 #[repr(C)]
 pub struct IOSView {
     swift_ptr: *const c_void,
-    set_frame: fn(*const c_void, f32, f32, f32, f32, *mut *const c_char) -> (),
-    set_prop: fn(*const c_void, *const c_char, *const c_char, *mut *const c_char) -> (),
-    add_child: fn(*const c_void, *const c_void, *mut *const c_char) -> (),
-    measure: fn(*const c_void, *const CSize, *mut *const c_char) -> CSize,
+    set_frame: extern "C" fn(*const c_void, f32, f32, f32, f32, *mut *const c_char) -> (),
+    set_prop: extern "C" fn(*const c_void, *const c_char, IOSValue, *mut *const c_char) -> (),
+    add_child: extern "C" fn(*const c_void, *const c_void, *mut *const c_char) -> (),
+    remove_child: extern "C" fn(*const c_void, *const c_void, *mut *const c_char) -> (),
+    insert_child: extern "C" fn(*const c_void, *const c_void, usize, *mut *const c_char) -> (),
+    measure: extern "C" fn(*const c_void, *const CSize, *mut *const c_char) -> CSize,
 }
 
 impl core::ShardView for IOSView {
-    fn add_child(&mut self, child: &core::ShardView) -> Result<()> {
+    fn add_child(&mut self, child: &dyn core::ShardView) -> Result<()> {
         let add_child = self.add_child;
         let child = child.as_any().downcast_ref::<IOSView>().unwrap();
 
@@ -68,6 +76,36 @@ impl core::ShardView for IOSView {
         }
     }
 
+    fn remove_child(&mut self, child: &dyn core::ShardView) -> Result<()> {
+        let remove_child = self.remove_child;
+        let child = child.as_any().downcast_ref::<IOSView>().unwrap();
+
+        let mut err: *const c_char = ptr::null();
+        remove_child(self.swift_ptr, child.swift_ptr, &mut err as *mut *const c_char);
+
+        if err.is_null() {
+            Ok(())
+        } else {
+            let err_string = unsafe { CStr::from_ptr(err).to_str().unwrap() };
+            Err(Box::new(err_string))
+        }
+    }
+
+    fn insert_child(&mut self, child: &dyn core::ShardView, index: usize) -> Result<()> {
+        let insert_child = self.insert_child;
+        let child = child.as_any().downcast_ref::<IOSView>().unwrap();
+
+        let mut err: *const c_char = ptr::null();
+        insert_child(self.swift_ptr, child.swift_ptr, index, &mut err as *mut *const c_char);
+
+        if err.is_null() {
+            Ok(())
+        } else {
+            let err_string = unsafe { CStr::from_ptr(err).to_str().unwrap() };
+            Err(Box::new(err_string))
+        }
+    }
+
     fn measure(&self, constraints: Size<Number>) -> Result<Size<f32>> {
         let measure = self.measure;
         let width = constraints.width.or_else(f32::NAN);
@@ -86,10 +124,10 @@ impl core::ShardView for IOSView {
 
     fn set_prop(&mut self, key: &str, value: &JsonValue) -> Result<()> {
         let key = CString::new(key).unwrap();
-        let value = CString::new(value.dump()).unwrap();
+        let value = IOSValue::new(value);
         let set_prop = self.set_prop;
         let mut err: *const c_char = ptr::null();
-        set_prop(self.swift_ptr, key.as_ptr(), value.as_ptr(), &mut err as *mut *const c_char);
+        set_prop(self.swift_ptr, key.as_ptr(), value, &mut err as *mut *const c_char);
 
         if err.is_null() {
             Ok(())
@@ -112,7 +150,7 @@ impl core::ShardView for IOSView {
         }
     }
 
-    fn as_any(&self) -> &Any {
+    fn as_any(&self) -> &dyn Any {
         self
     }
 }
@@ -122,6 +160,68 @@ pub struct IOSRoot {
     root_ptr: *mut c_void,
 }
 
+#[repr(C)]
+pub struct IOSRenderContext {
+    locale: *const c_char,
+    dark_mode: bool,
+}
+
+impl IOSRenderContext {
+    fn to_render_context(&self) -> core::RenderContext {
+        core::RenderContext {
+            platform: Some(core::Platform::IOS),
+            locale: c_str(self.locale).to_str().unwrap().to_string(),
+            theme: if self.dark_mode { core::ThemeMode::Dark } else { core::ThemeMode::Light },
+            ..Default::default()
+        }
+    }
+}
+
+/// A string Swift passed in, valid for as long as Swift keeps it alive.
+fn c_str<'a>(string: *const c_char) -> &'a CStr {
+    unsafe { CStr::from_ptr(string) }
+}
+
+/// Bytes Swift passed in, valid for as long as Swift keeps them alive.
+fn from_swift_bytes<'a>(bytes: *const u8, len: usize) -> &'a [u8] {
+    if bytes.is_null() {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(bytes, len) }
+    }
+}
+
+/// An object Swift holds a pointer to, such as a view manager or navigator.
+fn from_swift<'a, T>(ptr: *mut T) -> &'a mut T {
+    unsafe { &mut *ptr }
+}
+
+/// Free an object created by one of the `_new` functions.
+fn free<T>(ptr: *mut T) {
+    unsafe { drop(Box::from_raw(ptr)) }
+}
+
+/// Free a string returned to Swift, such as a loaded document.
+fn free_string(string: *mut c_char) {
+    unsafe { drop(CString::from_raw(string)) }
+}
+
+fn set_error(error: *mut *const c_char, err: Box<dyn Any>) {
+    let message = if let Some(error) = err.downcast_ref::<&str>() {
+        *error
+    } else if let Some(error) = err.downcast_ref::<String>() {
+        error.as_str()
+    } else {
+        "Unknown native error"
+    };
+
+    if !error.is_null() {
+        unsafe {
+            *error = CString::new(message).unwrap().into_raw();
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn shard_root_measure(root: IOSRoot, size: CSize, error: *mut *const c_char) {
     let mut root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
@@ -130,21 +230,47 @@ pub extern "C" fn shard_root_measure(root: IOSRoot, size: CSize, error: *mut *co
         height: if size.height.is_nan() { Number::Undefined } else { Number::Defined(size.height) },
     });
 
-    match result {
-        Ok(_) => {
-            Box::leak(root);
-        }
-        Err(err) => unsafe {
-            let message = if let Some(error) = err.downcast_ref::<&str>() {
-                *error
-            } else if let Some(error) = err.downcast_ref::<String>() {
-                error.as_str()
-            } else {
-                "Unknown native error"
-            };
+    Box::leak(root);
 
-            *error = CString::new(message).unwrap().into_raw();
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shard_root_set_dark_mode(root: IOSRoot, dark_mode: bool, error: *mut *const c_char) {
+    let mut root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
+    let result = root.set_theme_mode(if dark_mode { core::ThemeMode::Dark } else { core::ThemeMode::Light });
+    Box::leak(root);
+
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
+#[repr(C)]
+pub struct IOSColor {
+    red: f32,
+    green: f32,
+    blue: f32,
+    alpha: f32,
+}
+
+#[no_mangle]
+pub extern "C" fn shard_color_parse(color: *const c_char, error: *mut *const c_char) -> IOSColor {
+    let color = c_str(color).to_str().unwrap();
+
+    match core::Color::parse(color) {
+        Ok(color) => IOSColor {
+            red: color.r as f32 / 255.0,
+            green: color.g as f32 / 255.0,
+            blue: color.b as f32 / 255.0,
+            alpha: color.a as f32 / 255.0,
         },
+        Err(err) => {
+            set_error(error, err);
+            IOSColor { red: 0.0, green: 0.0, blue: 0.0, alpha: 0.0 }
+        }
     }
 }
 
@@ -157,6 +283,267 @@ pub extern "C" fn shard_root_get_view(root: IOSRoot) -> *const c_void {
     swift_ptr
 }
 
+#[no_mangle]
+pub extern "C" fn shard_root_warnings_len(root: IOSRoot) -> usize {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    root.warnings.len()
+}
+
+/// The JSON pointer of a warning, valid until the root is freed.
+#[no_mangle]
+pub extern "C" fn shard_root_warning_path(root: IOSRoot, index: usize) -> IOSString {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    IOSString::new(root.warnings.get(index).map(|warning| warning.path.as_str()))
+}
+
+/// The message of a warning, valid until the root is freed.
+#[no_mangle]
+pub extern "C" fn shard_root_warning_message(root: IOSRoot, index: usize) -> IOSString {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    IOSString::new(root.warnings.get(index).map(|warning| warning.message.as_str()))
+}
+
+#[no_mangle]
+pub extern "C" fn shard_root_pending_fragments_len(root: IOSRoot) -> usize {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    root.pending_fragments().len()
+}
+
+/// The id of a fragment of a lazy include still showing its placeholder, valid until the root
+/// changes.
+#[no_mangle]
+pub extern "C" fn shard_root_pending_fragment(root: IOSRoot, index: usize) -> IOSString {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    IOSString::new(root.pending_fragments().get(index).copied())
+}
+
+/// Supply the JSON of a fragment, replacing the placeholders of lazy includes of `id` with it.
+#[no_mangle]
+pub extern "C" fn shard_root_load_fragment(
+    root: IOSRoot,
+    view_manager: *mut IOSViewManager,
+    context: *const c_void,
+    id: *const c_char,
+    json: *const c_char,
+    error: *mut *const c_char,
+) {
+    let mut root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
+    let view_manager = from_swift(view_manager);
+    let id = c_str(id).to_str().unwrap();
+    let json = c_str(json).to_str().unwrap();
+
+    let result = root.load_fragment(view_manager, &context, id, json);
+    Box::leak(root);
+
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
+#[repr(C)]
+pub struct CRect {
+    start: f32,
+    end: f32,
+    top: f32,
+    bottom: f32,
+}
+
+/// The view of the node with `id`, or null if there is none or the node was flattened.
+#[no_mangle]
+pub extern "C" fn shard_root_view_by_id(root: IOSRoot, id: *const c_char) -> *const c_void {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    let id = c_str(id).to_str().unwrap();
+
+    match root.view_by_id(id).and_then(|view_node| view_node.shard_view.as_any().downcast_ref::<IOSView>()) {
+        Some(view) => view.swift_ptr,
+        None => ptr::null(),
+    }
+}
+
+/// Write the frame of the node with `id` relative to the root into `frame`. Returns false if
+/// there is no such node.
+#[no_mangle]
+pub extern "C" fn shard_root_frame_of(root: IOSRoot, id: *const c_char, frame: *mut CRect) -> bool {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    let id = c_str(id).to_str().unwrap();
+
+    match root.frame_of(id) {
+        Some(rect) => {
+            *from_swift(frame) = CRect { start: rect.start, end: rect.end, top: rect.top, bottom: rect.bottom };
+            true
+        }
+        None => false,
+    }
+}
+
+#[repr(C)]
+pub struct IOSNode {
+    kind: IOSString,
+    id: IOSString,
+    frame: CRect,
+    view: *const c_void,
+}
+
+/// Call `on_node` with every node matching `selector` in document order. The strings of a node
+/// are only valid during the call.
+#[no_mangle]
+pub extern "C" fn shard_root_query(
+    root: IOSRoot,
+    selector: *const c_char,
+    swift_ptr: *const c_void,
+    on_node: extern "C" fn(*const c_void, IOSNode) -> (),
+    error: *mut *const c_char,
+) {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    let selector = c_str(selector).to_str().unwrap();
+
+    match root.query(selector) {
+        Ok(nodes) => each_node(&nodes, swift_ptr, on_node),
+        Err(err) => set_error(error, err),
+    }
+}
+
+/// Call `on_node` with every node under a point, deepest first.
+#[no_mangle]
+pub extern "C" fn shard_root_hit_test(
+    root: IOSRoot,
+    x: f32,
+    y: f32,
+    swift_ptr: *const c_void,
+    on_node: extern "C" fn(*const c_void, IOSNode) -> (),
+) {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    each_node(&root.hit_test(x, y), swift_ptr, on_node);
+}
+
+fn each_node(
+    nodes: &[&core::ViewNode],
+    swift_ptr: *const c_void,
+    on_node: extern "C" fn(*const c_void, IOSNode) -> (),
+) {
+    for view_node in nodes {
+        let frame = view_node.frame();
+        let view = view_node.shard_view.as_any().downcast_ref::<IOSView>();
+        on_node(
+            swift_ptr,
+            IOSNode {
+                kind: IOSString::new(Some(view_node.kind())),
+                id: IOSString::new(view_node.id()),
+                frame: CRect { start: frame.start, end: frame.end, top: frame.top, bottom: frame.bottom },
+                view: view.map_or(ptr::null(), |view| view.swift_ptr),
+            },
+        );
+    }
+}
+
+struct IOSActionHandler {
+    swift_ptr: *const c_void,
+    on_action: extern "C" fn(*const c_void, *const c_char, IOSValue, *mut *const c_char) -> (),
+}
+
+impl core::ActionHandler for IOSActionHandler {
+    fn on_action(&mut self, action: &str, value: &JsonValue) -> Result<()> {
+        let action = CString::new(action).unwrap();
+        let value = IOSValue::new(value);
+        let on_action = self.on_action;
+        let mut err: *const c_char = ptr::null();
+        on_action(self.swift_ptr, action.as_ptr(), value, &mut err as *mut *const c_char);
+
+        if err.is_null() {
+            Ok(())
+        } else {
+            let err_string = unsafe { CStr::from_ptr(err).to_str().unwrap() };
+            Err(Box::new(err_string))
+        }
+    }
+}
+
+/// Run the action encoded as JSON in `action`, calling `on_action` for those not built into the core.
+#[no_mangle]
+pub extern "C" fn shard_root_dispatch(
+    root: IOSRoot,
+    action: *const c_char,
+    swift_ptr: *const c_void,
+    on_action: extern "C" fn(*const c_void, *const c_char, IOSValue, *mut *const c_char) -> (),
+    error: *mut *const c_char,
+) {
+    let mut root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
+    let action = c_str(action).to_str().unwrap();
+
+    let result = match json::parse(action) {
+        Ok(action) => root.dispatch(&action, &mut IOSActionHandler { swift_ptr, on_action }),
+        Err(err) => Err(Box::new(err.to_string()) as Box<dyn Any>),
+    };
+    Box::leak(root);
+
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
+/// Override a prop of the node with the given id, taking over `value`.
+#[no_mangle]
+pub extern "C" fn shard_root_set_prop(
+    root: IOSRoot,
+    id: *const c_char,
+    key: *const c_char,
+    value: IOSValue,
+    error: *mut *const c_char,
+) {
+    let mut root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
+    let id = c_str(id).to_str().unwrap();
+    let key = c_str(key).to_str().unwrap();
+
+    let result = root.set_prop(id, key, value.take());
+    Box::leak(root);
+
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
+/// The current state of the root, valid until the root changes.
+#[no_mangle]
+pub extern "C" fn shard_root_state(root: IOSRoot) -> IOSValue {
+    let root = unsafe { &*(root.root_ptr as *const core::Root) };
+    IOSValue::new(root.state())
+}
+
+/// Set the value at a dot separated path of the state, taking over `value`.
+#[no_mangle]
+pub extern "C" fn shard_root_set_state(root: IOSRoot, path: *const c_char, value: IOSValue, error: *mut *const c_char) {
+    let mut root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
+    let path = c_str(path).to_str().unwrap();
+
+    let result = root.set_state(path, value.take());
+    Box::leak(root);
+
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
+/// Apply a JSON Patch to the document of the root, taking over `patch`. A patch which fails to apply
+/// or render leaves the views untouched.
+#[no_mangle]
+pub extern "C" fn shard_root_apply_patch(
+    root: IOSRoot,
+    view_manager: *mut IOSViewManager,
+    context: *const c_void,
+    patch: IOSValue,
+    error: *mut *const c_char,
+) {
+    let mut root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
+    let view_manager = from_swift(view_manager);
+
+    let result = root.apply_patch(view_manager, &context, &patch.take());
+    Box::leak(root);
+
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn shard_root_free(root: IOSRoot) {
     let _root: Box<core::Root> = unsafe { Box::from_raw(root.root_ptr as *mut core::Root) };
@@ -165,33 +552,89 @@ pub extern "C" fn shard_root_free(root: IOSRoot) {
 #[no_mangle]
 pub extern "C" fn shard_view_new(
     swift_ptr: *const c_void,
-    set_frame: fn(*const c_void, f32, f32, f32, f32, *mut *const c_char) -> (),
-    set_prop: fn(*const c_void, *const c_char, *const c_char, *mut *const c_char) -> (),
-    add_child: fn(*const c_void, *const c_void, *mut *const c_char) -> (),
-    measure: fn(*const c_void, *const CSize, *mut *const c_char) -> CSize,
+    set_frame: extern "C" fn(*const c_void, f32, f32, f32, f32, *mut *const c_char) -> (),
+    set_prop: extern "C" fn(*const c_void, *const c_char, IOSValue, *mut *const c_char) -> (),
+    add_child: extern "C" fn(*const c_void, *const c_void, *mut *const c_char) -> (),
+    remove_child: extern "C" fn(*const c_void, *const c_void, *mut *const c_char) -> (),
+    insert_child: extern "C" fn(*const c_void, *const c_void, usize, *mut *const c_char) -> (),
+    measure: extern "C" fn(*const c_void, *const CSize, *mut *const c_char) -> CSize,
 ) -> *mut IOSView {
-    Box::into_raw(Box::new(IOSView { swift_ptr, set_frame, set_prop, add_child, measure }))
+    Box::into_raw(Box::new(IOSView { swift_ptr, set_frame, set_prop, add_child, remove_child, insert_child, measure }))
 }
 
 #[no_mangle]
 pub extern "C" fn shard_view_free(view: *mut IOSView) {
-    unsafe {
-        Box::from_raw(view);
-    }
+    free(view);
 }
 
 #[no_mangle]
 pub extern "C" fn shard_view_manager_new(
     swift_ptr: *const c_void,
-    create_view: fn(*const c_void, *const c_void, *const c_char, *mut *const c_char) -> *mut IOSView,
+    create_view: extern "C" fn(*const c_void, *const c_void, *const c_char, *mut *const c_char) -> *mut IOSView,
 ) -> *const IOSViewManager {
-    Box::into_raw(Box::new(IOSViewManager { swift_ptr, create_view }))
+    Box::into_raw(Box::new(IOSViewManager { swift_ptr, create_view, fragments: Rc::default() }))
 }
 
 #[no_mangle]
 pub extern "C" fn shard_view_manager_free(view_manager: *mut IOSViewManager) {
-    unsafe {
-        Box::from_raw(view_manager);
+    free(view_manager);
+}
+
+/// Fragments are resolved by Swift, which returns their JSON.
+struct IOSFragmentResolver {
+    swift_ptr: *const c_void,
+    resolve: extern "C" fn(*const c_void, *const c_char, *mut *const c_char) -> *const c_char,
+}
+
+impl core::FragmentResolver for IOSFragmentResolver {
+    fn resolve(&self, id: &str) -> Result<String> {
+        let id = CString::new(id).unwrap();
+        let mut err: *const c_char = ptr::null();
+        let json = (self.resolve)(self.swift_ptr, id.as_ptr(), &mut err as *mut *const c_char);
+
+        swift_error(err)?;
+        if json.is_null() {
+            return Err(Box::new(format!("no fragment {}", id.to_string_lossy())));
+        }
+        Ok(unsafe { CStr::from_ptr(json).to_string_lossy().into_owned() })
+    }
+}
+
+/// Resolve the fragments included by roots rendered from now on with `resolve`, dropping the
+/// fragments resolved so far.
+#[no_mangle]
+pub extern "C" fn shard_view_manager_set_fragment_resolver(
+    view_manager: *mut IOSViewManager,
+    swift_ptr: *const c_void,
+    resolve: extern "C" fn(*const c_void, *const c_char, *mut *const c_char) -> *const c_char,
+) {
+    let view_manager = from_swift(view_manager);
+    view_manager.fragments = Rc::new(core::Fragments::new(IOSFragmentResolver { swift_ptr, resolve }));
+}
+
+#[no_mangle]
+pub extern "C" fn shard_render_bytes(
+    view_manager: *mut IOSViewManager,
+    context: *const c_void,
+    render_context: IOSRenderContext,
+    bytes: *const u8,
+    len: usize,
+    content_type: *const c_char,
+    error: *mut *const c_char,
+) -> IOSRoot {
+    let view_manager = from_swift(view_manager);
+    let bytes = from_swift_bytes(bytes, len);
+    let content_type = if content_type.is_null() { None } else { c_str(content_type).to_str().ok() };
+    let mut render_context = render_context.to_render_context();
+    render_context.fragments = view_manager.fragments.clone();
+    let result = core::render_root_from_bytes(view_manager, &context, &render_context, bytes, content_type);
+
+    match result {
+        Ok(root) => IOSRoot { root_ptr: Box::into_raw(Box::new(root)) as *mut c_void },
+        Err(err) => {
+            set_error(error, err);
+            IOSRoot { root_ptr: ptr::null_mut() }
+        }
     }
 }
 
@@ -199,28 +642,271 @@ pub extern "C" fn shard_view_manager_free(view_manager: *mut IOSViewManager) {
 pub extern "C" fn shard_render(
     view_manager: *mut IOSViewManager,
     context: *const c_void,
+    render_context: IOSRenderContext,
     json: *const c_char,
     error: *mut *const c_char,
 ) -> IOSRoot {
-    let view_manager = unsafe { Box::from_raw(view_manager) };
-    let json = unsafe { CStr::from_ptr(json).to_str().unwrap() };
-    let result = core::render_root(Box::leak(view_manager), &context, json);
+    let view_manager = from_swift(view_manager);
+    let json = c_str(json).to_str().unwrap();
+    let mut render_context = render_context.to_render_context();
+    render_context.fragments = view_manager.fragments.clone();
+    let result = core::render_root_with_context(view_manager, &context, &render_context, json);
 
     match result {
         Ok(root) => IOSRoot { root_ptr: Box::into_raw(Box::new(root)) as *mut c_void },
         Err(err) => {
-            let message = if let Some(error) = err.downcast_ref::<&str>() {
-                *error
-            } else if let Some(error) = err.downcast_ref::<String>() {
-                error.as_str()
-            } else {
-                "Unknown native error"
-            };
-
-            unsafe {
-                *error = CString::new(message).unwrap().into_raw();
-            }
+            set_error(error, err);
             IOSRoot { root_ptr: ptr::null_mut() }
         }
     }
 }
+
+/// The response to a request of an `IOSTransport`, filled in by `shard_response_set`.
+pub struct IOSResponse {
+    response: Option<Result<core::Response>>,
+}
+
+/// Documents are fetched by Swift, which blocks until the response arrives and passes it to
+/// `shard_response_set`.
+struct IOSTransport {
+    swift_ptr: *const c_void,
+    fetch: extern "C" fn(*const c_void, *const c_char, *const c_char, *mut IOSResponse, *mut *const c_char) -> (),
+}
+
+impl core::Transport for IOSTransport {
+    fn fetch(&mut self, request: &core::Request) -> Result<core::Response> {
+        let url = CString::new(request.url.as_str()).unwrap();
+        let etag = request.etag.as_ref().map(|etag| CString::new(etag.as_str()).unwrap());
+        let mut response = IOSResponse { response: None };
+        let mut err: *const c_char = ptr::null();
+        (self.fetch)(
+            self.swift_ptr,
+            url.as_ptr(),
+            etag.as_ref().map_or(ptr::null(), |etag| etag.as_ptr()),
+            &mut response,
+            &mut err as *mut *const c_char,
+        );
+
+        swift_error(err)?;
+        response.response.unwrap_or_else(|| Err(Box::new(format!("no response loading {}", request.url))))
+    }
+}
+
+fn optional_str<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        None
+    } else {
+        c_str(string).to_str().ok()
+    }
+}
+
+/// Fill in the response to a request of a transport. The body is decoded right away, so none of
+/// the arguments need to outlive the call.
+#[no_mangle]
+pub extern "C" fn shard_response_set(
+    response: *mut IOSResponse,
+    status: u16,
+    body: *const u8,
+    len: usize,
+    content_type: *const c_char,
+    etag: *const c_char,
+    cache_control: *const c_char,
+) {
+    let response = from_swift(response);
+    let body = from_swift_bytes(body, len);
+    let decoded = core::Response::from_bytes(status, body, optional_str(content_type)).map(|decoded| core::Response {
+        etag: optional_str(etag).map(str::to_string),
+        cache_control: optional_str(cache_control).map(str::to_string),
+        ..decoded
+    });
+    response.response = Some(decoded);
+}
+
+/// Load a document through a `DocumentLoader` caching in `cache_dir`, with `fetch` making the
+/// requests. Returns the document as JSON, to be freed with `shard_string_free`.
+#[no_mangle]
+pub extern "C" fn shard_load_document(
+    url: *const c_char,
+    cache_dir: *const c_char,
+    swift_ptr: *const c_void,
+    fetch: extern "C" fn(*const c_void, *const c_char, *const c_char, *mut IOSResponse, *mut *const c_char) -> (),
+    error: *mut *const c_char,
+) -> *mut c_char {
+    let url = c_str(url).to_str().unwrap();
+    let cache_dir = c_str(cache_dir).to_str().unwrap();
+    let mut loader = core::DocumentLoader::new(Box::new(IOSTransport { swift_ptr, fetch }), cache_dir.into());
+
+    match loader.load(url) {
+        Ok(document) => CString::new(document.body).unwrap().into_raw(),
+        Err(err) => {
+            set_error(error, err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Like `shard_load_document`, but a stale cached copy is passed to `on_document` before
+/// revalidating so it can be shown right away, and the document again only if it changed. The
+/// document passed to `on_document` is only valid during the call.
+#[no_mangle]
+pub extern "C" fn shard_load_document_stale_while_revalidate(
+    url: *const c_char,
+    cache_dir: *const c_char,
+    swift_ptr: *const c_void,
+    fetch: extern "C" fn(*const c_void, *const c_char, *const c_char, *mut IOSResponse, *mut *const c_char) -> (),
+    on_document: extern "C" fn(*const c_void, IOSString) -> (),
+    error: *mut *const c_char,
+) {
+    let url = c_str(url).to_str().unwrap();
+    let cache_dir = c_str(cache_dir).to_str().unwrap();
+    let mut loader = core::DocumentLoader::new(Box::new(IOSTransport { swift_ptr, fetch }), cache_dir.into());
+
+    let result = loader.load_stale_while_revalidate(url, &mut |document| {
+        on_document(swift_ptr, IOSString::new(Some(&document.body)));
+    });
+
+    if let Err(err) = result {
+        set_error(error, err);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shard_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        free_string(string);
+    }
+}
+
+#[repr(C)]
+pub enum IOSTransition {
+    IOSTransitionPush,
+    IOSTransitionPop,
+    IOSTransitionReplace,
+}
+
+/// Navigation is driven by a Swift `ShardNavigator`, which renders targets into roots it gives
+/// up ownership of and passes transitions on to the app.
+pub struct IOSNavigatorHost {
+    swift_ptr: *const c_void,
+    render: extern "C" fn(*const c_void, IOSValue, *mut *const c_char) -> IOSRoot,
+    transition: extern "C" fn(*const c_void, IOSTransition, IOSRoot, IOSRoot, f32, f32, *mut *const c_char) -> (),
+    on_action: extern "C" fn(*const c_void, *const c_char, IOSValue, *mut *const c_char) -> (),
+}
+
+pub struct IOSNavigator {
+    navigator: core::ShardNavigator,
+    host: IOSNavigatorHost,
+}
+
+fn swift_error(err: *const c_char) -> Result<()> {
+    if err.is_null() {
+        Ok(())
+    } else {
+        let err_string = unsafe { CStr::from_ptr(err).to_str().unwrap() };
+        Err(Box::new(err_string))
+    }
+}
+
+impl core::ActionHandler for IOSNavigatorHost {
+    fn on_action(&mut self, action: &str, value: &JsonValue) -> Result<()> {
+        let mut handler = IOSActionHandler { swift_ptr: self.swift_ptr, on_action: self.on_action };
+        handler.on_action(action, value)
+    }
+}
+
+impl core::NavigatorHost for IOSNavigatorHost {
+    fn render(&mut self, target: &JsonValue) -> Result<Box<core::Root>> {
+        let render = self.render;
+        let mut err: *const c_char = ptr::null();
+        let root = render(self.swift_ptr, IOSValue::new(target), &mut err as *mut *const c_char);
+        swift_error(err)?;
+        if root.root_ptr.is_null() {
+            return Err(Box::new("render returned no root"));
+        }
+        Ok(unsafe { Box::from_raw(root.root_ptr as *mut core::Root) })
+    }
+
+    fn transition(
+        &mut self,
+        transition: core::Transition,
+        from: Option<&core::NavigationEntry>,
+        to: &core::NavigationEntry,
+    ) -> Result<()> {
+        let transition = match transition {
+            core::Transition::Push => IOSTransition::IOSTransitionPush,
+            core::Transition::Pop => IOSTransition::IOSTransitionPop,
+            core::Transition::Replace => IOSTransition::IOSTransitionReplace,
+        };
+        let ios_root =
+            |entry: &core::NavigationEntry| IOSRoot { root_ptr: &*entry.root as *const core::Root as *mut c_void };
+        let from = from.map_or(IOSRoot { root_ptr: ptr::null_mut() }, ios_root);
+        let (x, y) = (to.scroll_position.x, to.scroll_position.y);
+
+        let transition_fn = self.transition;
+        let mut err: *const c_char = ptr::null();
+        transition_fn(self.swift_ptr, transition, from, ios_root(to), x, y, &mut err as *mut *const c_char);
+        swift_error(err)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shard_navigator_new(
+    swift_ptr: *const c_void,
+    render: extern "C" fn(*const c_void, IOSValue, *mut *const c_char) -> IOSRoot,
+    transition: extern "C" fn(*const c_void, IOSTransition, IOSRoot, IOSRoot, f32, f32, *mut *const c_char) -> (),
+    on_action: extern "C" fn(*const c_void, *const c_char, IOSValue, *mut *const c_char) -> (),
+) -> *mut IOSNavigator {
+    let host = IOSNavigatorHost { swift_ptr, render, transition, on_action };
+    Box::into_raw(Box::new(IOSNavigator { navigator: core::ShardNavigator::new(), host }))
+}
+
+#[no_mangle]
+pub extern "C" fn shard_navigator_free(navigator: *mut IOSNavigator) {
+    free(navigator);
+}
+
+/// Render `target`, a URL or an inline document, and show it on top. Takes over `target`.
+#[no_mangle]
+pub extern "C" fn shard_navigator_push(navigator: *mut IOSNavigator, target: IOSValue, error: *mut *const c_char) {
+    let navigator = from_swift(navigator);
+    if let Err(err) = navigator.navigator.push(&mut navigator.host, &target.take()) {
+        set_error(error, err);
+    }
+}
+
+/// Render `target`, a URL or an inline document, and show it in place of the top. Takes over `target`.
+#[no_mangle]
+pub extern "C" fn shard_navigator_replace(navigator: *mut IOSNavigator, target: IOSValue, error: *mut *const c_char) {
+    let navigator = from_swift(navigator);
+    if let Err(err) = navigator.navigator.replace(&mut navigator.host, &target.take()) {
+        set_error(error, err);
+    }
+}
+
+/// Pop up to `count` roots, always keeping the first one. Returns false if there was nothing to pop.
+#[no_mangle]
+pub extern "C" fn shard_navigator_pop(navigator: *mut IOSNavigator, count: usize, error: *mut *const c_char) -> bool {
+    let navigator = from_swift(navigator);
+    match navigator.navigator.pop(&mut navigator.host, count) {
+        Ok(popped) => popped,
+        Err(err) => {
+            set_error(error, err);
+            false
+        }
+    }
+}
+
+/// Run an action on the top root, taking over `action`. Navigation actions in a chain run after the rest of it.
+#[no_mangle]
+pub extern "C" fn shard_navigator_dispatch(navigator: *mut IOSNavigator, action: IOSValue, error: *mut *const c_char) {
+    let navigator = from_swift(navigator);
+    if let Err(err) = navigator.navigator.dispatch(&mut navigator.host, &action.take()) {
+        set_error(error, err);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shard_navigator_set_scroll_position(navigator: *mut IOSNavigator, x: f32, y: f32) {
+    let navigator = from_swift(navigator);
+    navigator.navigator.set_scroll_position(x, y);
+}
@@ -0,0 +1,167 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use json::JsonValue;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+static NULL: JsonValue = JsonValue::Null;
+
+/// Handle to a JSON value. Handles passed to Swift are borrowed and only valid for the duration
+/// of the call they are passed to. Handles built with the `shard_value_new_*` functions are owned
+/// by Swift until they are handed to a function which takes them over.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct IOSValue {
+    value_ptr: *const c_void,
+}
+
+#[repr(C)]
+pub enum IOSValueType {
+    IOSValueNull,
+    IOSValueBoolean,
+    IOSValueNumber,
+    IOSValueString,
+    IOSValueObject,
+    IOSValueArray,
+}
+
+#[repr(C)]
+pub struct IOSString {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl IOSValue {
+    pub fn new(value: &JsonValue) -> IOSValue {
+        IOSValue { value_ptr: value as *const JsonValue as *const c_void }
+    }
+
+    fn owned(value: JsonValue) -> IOSValue {
+        IOSValue { value_ptr: Box::into_raw(Box::new(value)) as *const c_void }
+    }
+
+    fn json(&self) -> &JsonValue {
+        unsafe { &*(self.value_ptr as *const JsonValue) }
+    }
+
+    /// Take over a value built with the `shard_value_new_*` functions.
+    pub(crate) fn take(self) -> JsonValue {
+        *unsafe { Box::from_raw(self.value_ptr as *mut JsonValue) }
+    }
+}
+
+impl IOSString {
+    pub(crate) fn new(value: Option<&str>) -> IOSString {
+        match value {
+            Some(value) => IOSString { ptr: value.as_ptr(), len: value.len() },
+            None => IOSString { ptr: ptr::null(), len: 0 },
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_type(value: IOSValue) -> IOSValueType {
+    match value.json() {
+        JsonValue::Null => IOSValueType::IOSValueNull,
+        JsonValue::Boolean(_) => IOSValueType::IOSValueBoolean,
+        JsonValue::Number(_) => IOSValueType::IOSValueNumber,
+        JsonValue::Short(_) | JsonValue::String(_) => IOSValueType::IOSValueString,
+        JsonValue::Object(_) => IOSValueType::IOSValueObject,
+        JsonValue::Array(_) => IOSValueType::IOSValueArray,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_as_bool(value: IOSValue) -> bool {
+    value.json().as_bool().unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_as_number(value: IOSValue) -> f64 {
+    value.json().as_f64().unwrap_or(0.0)
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_as_string(value: IOSValue) -> IOSString {
+    IOSString::new(value.json().as_str())
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_len(value: IOSValue) -> usize {
+    value.json().len()
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_at(value: IOSValue, index: usize) -> IOSValue {
+    IOSValue::new(&value.json()[index])
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_key_at(value: IOSValue, index: usize) -> IOSString {
+    IOSString::new(value.json().entries().nth(index).map(|(key, _)| key))
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_value_at(value: IOSValue, index: usize) -> IOSValue {
+    match value.json().entries().nth(index) {
+        Some((_, value)) => IOSValue::new(value),
+        None => IOSValue::new(&NULL),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_new_null() -> IOSValue {
+    IOSValue::owned(JsonValue::Null)
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_new_bool(value: bool) -> IOSValue {
+    IOSValue::owned(JsonValue::Boolean(value))
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_new_number(value: f64) -> IOSValue {
+    IOSValue::owned(JsonValue::from(value))
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_new_string(value: *const c_char) -> IOSValue {
+    let value = crate::c_str(value);
+    IOSValue::owned(JsonValue::from(value.to_string_lossy().into_owned()))
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_new_object() -> IOSValue {
+    IOSValue::owned(JsonValue::new_object())
+}
+
+#[no_mangle]
+pub extern "C" fn shard_value_new_array() -> IOSValue {
+    IOSValue::owned(JsonValue::new_array())
+}
+
+/// Append `item` to an array built with `shard_value_new_array`, taking over `item`.
+/// Returns false, dropping `item`, when `array` is not an array.
+#[no_mangle]
+pub extern "C" fn shard_value_push(array: IOSValue, item: IOSValue) -> bool {
+    let array = unsafe { &mut *(array.value_ptr as *mut JsonValue) };
+    array.push(item.take()).is_ok()
+}
+
+/// Set `key` of an object built with `shard_value_new_object`, taking over `item`.
+#[no_mangle]
+pub extern "C" fn shard_value_insert(object: IOSValue, key: *const c_char, item: IOSValue) {
+    let object = unsafe { &mut *(object.value_ptr as *mut JsonValue) };
+    let key = crate::c_str(key);
+    object[key.to_string_lossy().as_ref()] = item.take();
+}
+
+/// Free a value built with the `shard_value_new_*` functions which was not taken over.
+#[no_mangle]
+pub extern "C" fn shard_value_free(value: IOSValue) {
+    value.take();
+}
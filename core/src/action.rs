@@ -0,0 +1,78 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::lint;
+use json::JsonValue;
+use stretch::result::Result;
+
+/// Performs actions which the core does not handle itself, `open-url` and any action defined by
+/// the app. Actions reach the handler in the order they are chained and with a validated value.
+pub trait ActionHandler {
+    fn on_action(&mut self, action: &str, value: &JsonValue) -> Result<()>;
+}
+
+/// Check an action against the schema. An action is either `{"action": name, "value": value}`
+/// or an array of actions run in order. Returns the JSON pointer below `path` of the offending
+/// value and the reason when it does not match.
+pub(crate) fn validate(action: &JsonValue, path: &str) -> std::result::Result<(), (String, String)> {
+    if action.is_array() {
+        for (i, action) in action.members().enumerate() {
+            validate(action, &format!("{}/{}", path, i))?;
+        }
+        return Ok(());
+    }
+
+    let name = match action["action"].as_str() {
+        Some(name) if action.is_object() => name,
+        _ => return Err((path.to_string(), "expected an action or an array of actions".to_string())),
+    };
+
+    let value = &action["value"];
+    let valid = match name {
+        "open-url" => value.is_string(),
+        "set-prop" => value["id"].is_string() && value["key"].is_string() && value.has_key("value"),
+        "set-theme" => *value == "light" || *value == "dark",
+        "set-state" => value.is_object(),
+        "toggle-state" => value.is_string(),
+        "increment-state" => value.is_object() && value.entries().all(|(_, by)| by.is_number()),
+        "push" | "replace" => value.is_string() || value.is_object(),
+        "pop" => value.is_null() || value.as_usize().is_some_and(|count| count > 0),
+        _ => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err((lint::pointer(path, "value"), format!("invalid value {} for action {}", value.dump(), name)))
+    }
+}
+
+/// The actions of a chain in the order they run.
+pub(crate) fn chain(action: &JsonValue) -> Vec<&JsonValue> {
+    if action.is_array() {
+        action.members().flat_map(chain).collect()
+    } else {
+        vec![action]
+    }
+}
+
+pub(crate) fn is_action_prop(key: &str) -> bool {
+    key.starts_with("on-")
+}
+
+/// Drop action props which do not match the schema so views only ever see valid actions. The
+/// problems are reported as warnings by `lint`.
+pub(crate) fn compile_props(props: &mut JsonValue) {
+    let invalid: Vec<String> = props
+        .entries()
+        .filter(|(key, value)| is_action_prop(key) && validate(value, "").is_err())
+        .map(|(key, _)| key.to_string())
+        .collect();
+
+    for key in invalid {
+        props.remove(&key);
+    }
+}
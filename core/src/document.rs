@@ -0,0 +1,140 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::action;
+use crate::color::Color;
+use crate::state;
+use crate::strings::Strings;
+use crate::text;
+use crate::theme::Theme;
+use crate::variants;
+use crate::warning::{self, Warning};
+use crate::RenderContext;
+use json::JsonValue;
+use stretch::result::Result;
+
+/// Document level tables which props can reference.
+pub(crate) struct Document {
+    strings: Strings,
+    theme: Theme,
+}
+
+impl Document {
+    pub fn new(json: &JsonValue) -> Document {
+        Document { strings: Strings::new(json["strings"].clone()), theme: Theme::new(json["theme"].clone()) }
+    }
+
+    /// The props to send to the view of a node after merging variants, resolving references,
+    /// compiling text spans, dropping invalid actions and normalizing colors. Invalid colors are
+    /// dropped with a warning, `path` being the path of the node.
+    pub fn props(
+        &self,
+        json: &JsonValue,
+        render_context: &RenderContext,
+        path: &str,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<JsonValue> {
+        let mut props = self.resolve(&variants::merged(json, "props", render_context), render_context);
+        text::compile_props(json["kind"].as_str().unwrap_or_default(), &mut props)?;
+        action::compile_props(&mut props);
+
+        let mut messages = vec![];
+        let mut invalid = vec![];
+        for (key, value) in props.entries_mut() {
+            if is_color_key(key) {
+                let mut warn = |message: String| messages.push((key.to_string(), message));
+                match normalize_color(value, &mut warn) {
+                    Ok(color) => *value = color,
+                    Err(error) => {
+                        warn(format!("{}, dropping {}", warning::describe(&*error), key));
+                        invalid.push(key.to_string());
+                    }
+                }
+            }
+        }
+
+        for key in invalid {
+            props.remove(&key);
+        }
+
+        for (key, message) in messages {
+            let warning = Warning { path: format!("{}/props/{}", path, key), message };
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+
+        Ok(props)
+    }
+
+    /// Whether the `if` of a node, if it has one, resolves to a truthy value.
+    pub fn is_visible(&self, json: &JsonValue, render_context: &RenderContext) -> bool {
+        !json.has_key("if") || state::is_truthy(&self.resolve(&json["if"], render_context))
+    }
+
+    pub fn resolve(&self, value: &JsonValue, render_context: &RenderContext) -> JsonValue {
+        match value {
+            JsonValue::Object(object) if object.get("$t").is_some() => {
+                match self.strings.translate(&render_context.locale, value) {
+                    Some(text) => text.into(),
+                    None => value["$t"].clone(),
+                }
+            }
+            JsonValue::Object(object) if object.get("$theme").is_some() => {
+                self.theme.resolve(render_context.theme, value).unwrap_or(JsonValue::Null)
+            }
+            JsonValue::Object(object) if object.get("$state").is_some() => state::resolve(&render_context.state, value),
+            JsonValue::Object(object) => {
+                let mut resolved = JsonValue::new_object();
+                for (key, value) in object.iter() {
+                    resolved[key] = self.resolve(value, render_context);
+                }
+                resolved
+            }
+            JsonValue::Array(array) => {
+                JsonValue::Array(array.iter().map(|value| self.resolve(value, render_context)).collect())
+            }
+            value => value.clone(),
+        }
+    }
+}
+
+fn is_color_key(key: &str) -> bool {
+    key == "color" || key.ends_with("-color")
+}
+
+/// Colors are either a single color or a `{"default": color, "pressed": color}` object.
+fn normalize_color(value: &JsonValue, warn: &mut dyn FnMut(String)) -> Result<JsonValue> {
+    match value {
+        JsonValue::Object(object) => {
+            let mut normalized = JsonValue::new_object();
+            for (state, value) in object.iter() {
+                normalized[state] = normalize_color(value, warn)?;
+            }
+            Ok(normalized)
+        }
+        JsonValue::Short(_) | JsonValue::String(_) => {
+            let color = value.as_str().unwrap();
+            if is_ambiguous_hex(color) {
+                warn(format!("{} is read as #rrggbbaa, not #aarrggbb, write it as rgba() to be explicit", color));
+            }
+            Ok(Color::parse(color)?.to_argb_hex().into())
+        }
+        value => Ok(value.clone()),
+    }
+}
+
+/// Whether a color is 8 digit hex, which documents used to write as `#aarrggbb`, and reading it
+/// as CSS `#rrggbbaa` gives a different color.
+fn is_ambiguous_hex(color: &str) -> bool {
+    match color.trim().strip_prefix('#') {
+        Some(hex) if hex.len() == 8 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            let hex = hex.to_ascii_lowercase();
+            format!("{}{}", &hex[2..], &hex[..2]) != hex
+        }
+        _ => false,
+    }
+}
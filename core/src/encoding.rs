@@ -0,0 +1,413 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use json::JsonValue;
+use stretch::result::Result;
+
+const MAX_DEPTH: usize = 256;
+
+/// The encodings a document can be sent in. CBOR (RFC 8949) and MessagePack carry the same
+/// values as JSON, byte strings, extension types and non-string map keys are rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+impl Encoding {
+    /// The encoding of a content type such as `application/cbor; charset=binary`.
+    pub fn from_content_type(content_type: &str) -> Option<Encoding> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/json" | "application/shard" | "text/json" => Some(Encoding::Json),
+            "application/cbor" => Some(Encoding::Cbor),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => Some(Encoding::MessagePack),
+            _ if mime.ends_with("+json") => Some(Encoding::Json),
+            _ if mime.ends_with("+cbor") => Some(Encoding::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Detect the encoding of a document from its first byte. Documents are objects, and the
+    /// bytes an object can start with do not overlap between the three encodings.
+    pub fn detect(bytes: &[u8]) -> Option<Encoding> {
+        let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+        match bytes.iter().find(|byte| !byte.is_ascii_whitespace())? {
+            b'{' => Some(Encoding::Json),
+            // Maps, or the self-describe tag 55799 which may precede any CBOR item
+            0xa0..=0xbb | 0xbf | 0xd9 => Some(Encoding::Cbor),
+            0x80..=0x8f | 0xde | 0xdf => Some(Encoding::MessagePack),
+            _ => None,
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> Result<JsonValue> {
+        let result = match self {
+            Encoding::Json => match std::str::from_utf8(bytes) {
+                Ok(json) => json::parse(json).map_err(|err| err.to_string()),
+                Err(err) => Err(err.to_string()),
+            },
+            Encoding::Cbor => Decoder { bytes, offset: 0 }.document(Decoder::cbor),
+            Encoding::MessagePack => Decoder { bytes, offset: 0 }.document(Decoder::message_pack),
+        };
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(reason) => Err(Box::new(format!("invalid {:?} document: {}", self, reason))),
+        }
+    }
+
+    pub fn encode(self, value: &JsonValue) -> Vec<u8> {
+        let mut bytes = vec![];
+        match self {
+            Encoding::Json => bytes.extend_from_slice(value.dump().as_bytes()),
+            Encoding::Cbor => encode_cbor(value, &mut bytes),
+            Encoding::MessagePack => encode_message_pack(value, &mut bytes),
+        }
+        bytes
+    }
+}
+
+type Decoded = std::result::Result<JsonValue, String>;
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn document(mut self, item: fn(&mut Decoder<'a>, usize) -> Decoded) -> Decoded {
+        let value = item(&mut self, 0)?;
+        if self.offset != self.bytes.len() {
+            return Err(format!("unexpected trailing bytes at offset {}", self.offset));
+        }
+        Ok(value)
+    }
+
+    fn take(&mut self, len: usize) -> std::result::Result<&'a [u8], String> {
+        match self.bytes.get(self.offset..self.offset.saturating_add(len)) {
+            Some(bytes) => {
+                self.offset += len;
+                Ok(bytes)
+            }
+            None => Err(format!("unexpected end of input at offset {}", self.offset)),
+        }
+    }
+
+    fn uint(&mut self, len: usize) -> std::result::Result<u64, String> {
+        Ok(self.take(len)?.iter().fold(0, |value, byte| value << 8 | u64::from(*byte)))
+    }
+
+    /// A length which is checked against the remaining input so it is safe to allocate for.
+    fn len(&mut self, len: u64) -> std::result::Result<usize, String> {
+        if len > (self.bytes.len() - self.offset) as u64 {
+            return Err(format!("length {} exceeds the input at offset {}", len, self.offset));
+        }
+        Ok(len as usize)
+    }
+
+    fn string(&mut self, len: usize) -> std::result::Result<String, String> {
+        let offset = self.offset;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| format!("invalid utf-8 string at offset {}", offset))
+    }
+
+    fn cbor(&mut self, depth: usize) -> Decoded {
+        if depth > MAX_DEPTH {
+            return Err(format!("nested more than {} deep", MAX_DEPTH));
+        }
+
+        let offset = self.offset;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        let argument = match info {
+            0..=23 => Some(u64::from(info)),
+            24..=27 => Some(self.uint(1 << (info - 24))?),
+            31 if [2, 3, 4, 5, 7].contains(&major) => None,
+            _ => return Err(format!("invalid additional info {} at offset {}", info, offset)),
+        };
+
+        match (major, argument) {
+            (0, Some(value)) => Ok(value.into()),
+            (1, Some(value)) if value <= i64::MAX as u64 => Ok((-1 - value as i64).into()),
+            (1, Some(value)) => Ok((-1.0 - value as f64).into()),
+            (3, Some(len)) => {
+                let len = self.len(len)?;
+                Ok(self.string(len)?.into())
+            }
+            (3, None) => {
+                let mut string = String::new();
+                while !self.cbor_break()? {
+                    match self.cbor(depth + 1)? {
+                        JsonValue::Short(chunk) => string.push_str(&chunk),
+                        JsonValue::String(chunk) => string.push_str(&chunk),
+                        _ => return Err(format!("invalid string chunk at offset {}", offset)),
+                    }
+                }
+                Ok(string.into())
+            }
+            (4, len) => {
+                let mut array = JsonValue::new_array();
+                let len = len.map(|len| self.len(len)).transpose()?;
+                while !self.cbor_end(len, array.len())? {
+                    array.push(self.cbor(depth + 1)?).unwrap();
+                }
+                Ok(array)
+            }
+            (5, len) => {
+                let mut object = JsonValue::new_object();
+                let len = len.map(|len| self.len(len)).transpose()?;
+                let mut entries = 0;
+                while !self.cbor_end(len, entries)? {
+                    let key_offset = self.offset;
+                    let key = match self.cbor(depth + 1)? {
+                        JsonValue::Short(key) => key.to_string(),
+                        JsonValue::String(key) => key,
+                        _ => return Err(format!("expected a string key at offset {}", key_offset)),
+                    };
+                    object[key.as_str()] = self.cbor(depth + 1)?;
+                    entries += 1;
+                }
+                Ok(object)
+            }
+            (6, Some(_)) => self.cbor(depth + 1),
+            (7, Some(value)) => match (info, value) {
+                (25, _) => Ok(f16_to_f64(value as u16).into()),
+                (26, _) => Ok(f64::from(f32::from_bits(value as u32)).into()),
+                (27, _) => Ok(f64::from_bits(value).into()),
+                (_, 20) => Ok(false.into()),
+                (_, 21) => Ok(true.into()),
+                (_, 22) | (_, 23) => Ok(JsonValue::Null),
+                _ => Err(format!("unsupported simple value {} at offset {}", value, offset)),
+            },
+            (7, None) => Err(format!("unexpected break at offset {}", offset)),
+            (2, _) => Err(format!("unsupported byte string at offset {}", offset)),
+            _ => Err(format!("unsupported item 0x{:02x} at offset {}", initial, offset)),
+        }
+    }
+
+    /// Whether an array or map of `len` items, or of indefinite length if `None`, is complete after
+    /// `count` items. The break ending an indefinite length item is consumed.
+    fn cbor_end(&mut self, len: Option<usize>, count: usize) -> std::result::Result<bool, String> {
+        match len {
+            Some(len) => Ok(count >= len),
+            None => self.cbor_break(),
+        }
+    }
+
+    /// Consume the break ending an indefinite length item, if it is next.
+    fn cbor_break(&mut self) -> std::result::Result<bool, String> {
+        match self.bytes.get(self.offset) {
+            Some(0xff) => {
+                self.offset += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(format!("unexpected end of input at offset {}", self.offset)),
+        }
+    }
+
+    fn message_pack(&mut self, depth: usize) -> Decoded {
+        if depth > MAX_DEPTH {
+            return Err(format!("nested more than {} deep", MAX_DEPTH));
+        }
+
+        let offset = self.offset;
+        let format = self.take(1)?[0];
+
+        let (len, container) = match format {
+            0x00..=0x7f => return Ok(u64::from(format).into()),
+            0xe0..=0xff => return Ok(i64::from(format as i8).into()),
+            0xc0 => return Ok(JsonValue::Null),
+            0xc2 => return Ok(false.into()),
+            0xc3 => return Ok(true.into()),
+            0xca => return Ok(f64::from(f32::from_bits(self.uint(4)? as u32)).into()),
+            0xcb => return Ok(f64::from_bits(self.uint(8)?).into()),
+            0xcc..=0xcf => return Ok(self.uint(1 << (format - 0xcc))?.into()),
+            0xd0..=0xd3 => {
+                let size = 1 << (format - 0xd0);
+                let value = self.uint(size)?;
+                // Sign extend from the size of the value
+                let shift = 64 - size * 8;
+                return Ok((((value << shift) as i64) >> shift).into());
+            }
+            0xa0..=0xbf => (u64::from(format & 0x1f), b's'),
+            0xd9..=0xdb => (self.uint(1 << (format - 0xd9))?, b's'),
+            0x90..=0x9f => (u64::from(format & 0x0f), b'a'),
+            0xdc | 0xdd => (self.uint(2 << (format - 0xdc))?, b'a'),
+            0x80..=0x8f => (u64::from(format & 0x0f), b'm'),
+            0xde | 0xdf => (self.uint(2 << (format - 0xde))?, b'm'),
+            0xc4..=0xc6 => return Err(format!("unsupported binary at offset {}", offset)),
+            _ => return Err(format!("unsupported format 0x{:02x} at offset {}", format, offset)),
+        };
+
+        let len = self.len(len)?;
+        match container {
+            b's' => Ok(self.string(len)?.into()),
+            b'a' => {
+                let mut array = JsonValue::new_array();
+                for _ in 0..len {
+                    array.push(self.message_pack(depth + 1)?).unwrap();
+                }
+                Ok(array)
+            }
+            _ => {
+                let mut object = JsonValue::new_object();
+                for _ in 0..len {
+                    let key_offset = self.offset;
+                    let key = match self.message_pack(depth + 1)? {
+                        JsonValue::Short(key) => key.to_string(),
+                        JsonValue::String(key) => key,
+                        _ => return Err(format!("expected a string key at offset {}", key_offset)),
+                    };
+                    object[key.as_str()] = self.message_pack(depth + 1)?;
+                }
+                Ok(object)
+            }
+        }
+    }
+}
+
+fn f16_to_f64(half: u16) -> f64 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((half >> 10) & 0x1f);
+    let fraction = f64::from(half & 0x03ff);
+
+    sign * match exponent {
+        0 => fraction * 2f64.powi(-24),
+        31 if fraction == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + fraction / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+/// A number as an integer when it has no fractional part and fits in 64 bits, negative
+/// integers being returned as their magnitude.
+fn integer(value: &JsonValue) -> Option<(bool, u64)> {
+    match value {
+        JsonValue::Number(number) => match number.as_parts() {
+            (positive, mantissa, 0) if positive || mantissa != 0 => Some((positive, mantissa)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn cbor_head(major: u8, argument: u64, bytes: &mut Vec<u8>) {
+    let major = major << 5;
+    match argument {
+        0..=23 => bytes.push(major | argument as u8),
+        24..=0xff => bytes.extend_from_slice(&[major | 24, argument as u8]),
+        0x100..=0xffff => {
+            bytes.push(major | 25);
+            bytes.extend_from_slice(&(argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            bytes.push(major | 26);
+            bytes.extend_from_slice(&(argument as u32).to_be_bytes());
+        }
+        _ => {
+            bytes.push(major | 27);
+            bytes.extend_from_slice(&argument.to_be_bytes());
+        }
+    }
+}
+
+fn encode_cbor(value: &JsonValue, bytes: &mut Vec<u8>) {
+    match value {
+        JsonValue::Null => bytes.push(0xf6),
+        JsonValue::Boolean(false) => bytes.push(0xf4),
+        JsonValue::Boolean(true) => bytes.push(0xf5),
+        JsonValue::Number(_) => match integer(value) {
+            Some((true, magnitude)) => cbor_head(0, magnitude, bytes),
+            Some((false, magnitude)) => cbor_head(1, magnitude - 1, bytes),
+            None => {
+                bytes.push(0xfb);
+                bytes.extend_from_slice(&value.as_f64().unwrap_or_default().to_be_bytes());
+            }
+        },
+        JsonValue::Short(_) | JsonValue::String(_) => {
+            let string = value.as_str().unwrap_or_default();
+            cbor_head(3, string.len() as u64, bytes);
+            bytes.extend_from_slice(string.as_bytes());
+        }
+        JsonValue::Array(array) => {
+            cbor_head(4, array.len() as u64, bytes);
+            for member in array {
+                encode_cbor(member, bytes);
+            }
+        }
+        JsonValue::Object(_) => {
+            cbor_head(5, value.len() as u64, bytes);
+            for (key, value) in value.entries() {
+                cbor_head(3, key.len() as u64, bytes);
+                bytes.extend_from_slice(key.as_bytes());
+                encode_cbor(value, bytes);
+            }
+        }
+    }
+}
+
+/// Write a MessagePack string, array or map header, `formats` being the fix format followed by
+/// the 8, 16 and 32 bit length formats, `None` where the type has no such format.
+fn message_pack_head(formats: [Option<u8>; 4], fix_max: usize, len: usize, bytes: &mut Vec<u8>) {
+    match (len, formats) {
+        (len, [Some(fix), ..]) if len <= fix_max => bytes.push(fix | len as u8),
+        (len, [_, Some(format), ..]) if len <= 0xff => bytes.extend_from_slice(&[format, len as u8]),
+        (len, [_, _, Some(format), _]) if len <= 0xffff => {
+            bytes.push(format);
+            bytes.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        (len, [_, _, _, format]) => {
+            bytes.push(format.unwrap_or_default());
+            bytes.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn encode_message_pack(value: &JsonValue, bytes: &mut Vec<u8>) {
+    match value {
+        JsonValue::Null => bytes.push(0xc0),
+        JsonValue::Boolean(false) => bytes.push(0xc2),
+        JsonValue::Boolean(true) => bytes.push(0xc3),
+        JsonValue::Number(_) => match integer(value) {
+            Some((true, magnitude)) if magnitude <= 0x7f => bytes.push(magnitude as u8),
+            Some((true, magnitude)) => {
+                bytes.push(0xcf);
+                bytes.extend_from_slice(&magnitude.to_be_bytes());
+            }
+            Some((false, magnitude)) if magnitude <= 32 => bytes.push((-(magnitude as i64)) as u8),
+            Some((false, magnitude)) if magnitude <= 1 << 63 => {
+                bytes.push(0xd3);
+                bytes.extend_from_slice(&(magnitude as i64).wrapping_neg().to_be_bytes());
+            }
+            _ => {
+                bytes.push(0xcb);
+                bytes.extend_from_slice(&value.as_f64().unwrap_or_default().to_be_bytes());
+            }
+        },
+        JsonValue::Short(_) | JsonValue::String(_) => {
+            let string = value.as_str().unwrap_or_default();
+            message_pack_head([Some(0xa0), Some(0xd9), Some(0xda), Some(0xdb)], 31, string.len(), bytes);
+            bytes.extend_from_slice(string.as_bytes());
+        }
+        JsonValue::Array(array) => {
+            message_pack_head([Some(0x90), None, Some(0xdc), Some(0xdd)], 15, array.len(), bytes);
+            for member in array {
+                encode_message_pack(member, bytes);
+            }
+        }
+        JsonValue::Object(_) => {
+            message_pack_head([Some(0x80), None, Some(0xde), Some(0xdf)], 15, value.len(), bytes);
+            for (key, value) in value.entries() {
+                message_pack_head([Some(0xa0), Some(0xd9), Some(0xda), Some(0xdb)], 31, key.len(), bytes);
+                bytes.extend_from_slice(key.as_bytes());
+                encode_message_pack(value, bytes);
+            }
+        }
+    }
+}
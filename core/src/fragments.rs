@@ -0,0 +1,75 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use json::JsonValue;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use stretch::result::Result;
+
+/// Supplies the fragments referenced by `{"$include": "fragment-id"}` nodes.
+pub trait FragmentResolver {
+    /// The JSON of the node with `id`, for example read through a `DocumentLoader`.
+    fn resolve(&self, id: &str) -> Result<String>;
+}
+
+/// Fragments included into documents. Each fragment is resolved and parsed once and then shared
+/// by every root which includes it, until it is invalidated.
+#[derive(Default)]
+pub struct Fragments {
+    resolver: Option<Box<dyn FragmentResolver>>,
+    cache: RefCell<HashMap<String, Rc<JsonValue>>>,
+}
+
+impl Fragments {
+    pub fn new<R: FragmentResolver + 'static>(resolver: R) -> Fragments {
+        Fragments { resolver: Some(Box::new(resolver)), cache: RefCell::default() }
+    }
+
+    /// Drop a cached fragment so the next render including it resolves it again.
+    pub fn invalidate(&self, id: &str) {
+        self.cache.borrow_mut().remove(id);
+    }
+
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// The fragment with `id`, resolving it if it is not cached.
+    pub(crate) fn get(&self, id: &str) -> Result<Rc<JsonValue>> {
+        if let Some(fragment) = self.cached(id) {
+            return Ok(fragment);
+        }
+
+        match &self.resolver {
+            Some(resolver) => self.insert(id, &resolver.resolve(id)?),
+            None => Err(Box::new(format!("no fragment resolver to include {}", id))),
+        }
+    }
+
+    pub(crate) fn cached(&self, id: &str) -> Option<Rc<JsonValue>> {
+        self.cache.borrow().get(id).cloned()
+    }
+
+    /// Parse and cache the JSON of the fragment with `id`, replacing any cached copy.
+    pub(crate) fn insert(&self, id: &str, json: &str) -> Result<Rc<JsonValue>> {
+        let fragment = match json::parse(json) {
+            Ok(fragment) if fragment.is_object() => Rc::new(fragment),
+            Ok(fragment) => return Err(Box::new(format!("expected fragment {} to be a node, got {}", id, fragment))),
+            Err(err) => return Err(Box::new(format!("invalid fragment {}: {}", id, err))),
+        };
+
+        self.cache.borrow_mut().insert(id.to_string(), fragment.clone());
+        Ok(fragment)
+    }
+}
+
+impl fmt::Debug for Fragments {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.cache.borrow().keys()).finish()
+    }
+}
@@ -0,0 +1,42 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::ViewNode;
+use stretch::style::{Display, Node, Overflow};
+
+/// Collect the nodes under a point into `path`, deepest first. Later siblings are drawn on top
+/// of earlier ones so they are tested first. Children may extend outside their parent unless it
+/// clips them. Visibility and clipping come from the style the renderer resolved for the node,
+/// so `if`, @media and platform layouts are taken into account.
+pub(crate) fn hit_test<'a>(
+    view_node: &'a ViewNode,
+    style: &Node,
+    x: f32,
+    y: f32,
+    path: &mut Vec<&'a ViewNode>,
+) -> bool {
+    if style.display == Display::None {
+        return false;
+    }
+
+    let frame = view_node.frame;
+    let inside = x >= frame.start && x < frame.end && y >= frame.top && y < frame.bottom;
+    if !inside && style.overflow != Overflow::Visible {
+        return false;
+    }
+
+    let hit_child = view_node
+        .children
+        .iter()
+        .zip(style.children.iter())
+        .rev()
+        .any(|(child, style)| hit_test(child, style, x, y, path));
+    if hit_child || inside {
+        path.push(view_node);
+    }
+
+    hit_child || inside
+}
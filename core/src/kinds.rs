@@ -0,0 +1,82 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::variants;
+use crate::RenderContext;
+use json::JsonValue;
+use std::collections::HashMap;
+use std::fmt;
+use stretch::result::Result;
+
+const MAX_EXPANSION_DEPTH: usize = 32;
+
+type Expand = dyn Fn(&JsonValue) -> Result<JsonValue>;
+
+/// Composite kinds registered by the embedding app. A composite kind expands a node into a
+/// subtree of other kinds before it is rendered, so no view manager ever sees it.
+#[derive(Default)]
+pub struct KindRegistry {
+    kinds: HashMap<String, Box<Expand>>,
+}
+
+impl KindRegistry {
+    pub fn new() -> KindRegistry {
+        KindRegistry::default()
+    }
+
+    /// Register `expand` for `kind`. It is called with the node, its `props` already merged for
+    /// the current platform and width, and returns the node to render in its place. Props are
+    /// passed unresolved so `$t` and `$theme` references in the subtree keep following the root.
+    pub fn register<F>(&mut self, kind: &str, expand: F)
+    where
+        F: Fn(&JsonValue) -> Result<JsonValue> + 'static,
+    {
+        self.kinds.insert(kind.to_string(), Box::new(expand));
+    }
+
+    pub fn contains(&self, kind: &str) -> bool {
+        self.kinds.contains_key(kind)
+    }
+
+    /// Expand `json` until its kind is no longer a composite. Layout set on the composite node
+    /// overrides the layout of the node it expands to, and its `id` and `class` carry over unless
+    /// the expansion sets its own. Returns `None` if `json` is not a composite.
+    pub(crate) fn expand(&self, json: &JsonValue, render_context: &RenderContext) -> Result<Option<JsonValue>> {
+        let mut expanded: Option<JsonValue> = None;
+
+        for _ in 0..MAX_EXPANSION_DEPTH {
+            let node = expanded.as_ref().unwrap_or(json);
+
+            let expand = match node["kind"].as_str().and_then(|kind| self.kinds.get(kind)) {
+                Some(expand) => expand,
+                None => return Ok(expanded),
+            };
+
+            let mut input = node.clone();
+            input["props"] = variants::merged(node, "props", render_context);
+
+            let mut result = expand(&input)?;
+            for (key, value) in variants::merged(node, "layout", render_context).entries() {
+                result["layout"][key] = value.clone();
+            }
+            for key in &["id", "class"] {
+                if node.has_key(key) && !result.has_key(key) {
+                    result[*key] = node[*key].clone();
+                }
+            }
+
+            expanded = Some(result);
+        }
+
+        Err(Box::new(format!("expansion of kind {} is too deep", json["kind"])))
+    }
+}
+
+impl fmt::Debug for KindRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.kinds.keys()).finish()
+    }
+}
@@ -0,0 +1,109 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::variants;
+use crate::RenderContext;
+use crate::ShardView;
+use json::object;
+use json::JsonValue;
+use std::any::Any;
+use stretch::geometry::{Rect, Size};
+use stretch::number::{Number, OrElse};
+use stretch::result::Result;
+
+/// Stand-in for nodes which only take part in layout. No native view is created for them,
+/// their children are added to the nearest ancestor with a native view instead.
+pub struct LayoutView;
+
+impl ShardView for LayoutView {
+    fn add_child(&mut self, _: &dyn ShardView) -> Result<()> {
+        Ok(())
+    }
+
+    fn remove_child(&mut self, _: &dyn ShardView) -> Result<()> {
+        Ok(())
+    }
+
+    fn insert_child(&mut self, _: &dyn ShardView, _: usize) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_prop(&mut self, _: &str, _: &JsonValue) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_frame(&mut self, _: Rect<f32>) -> Result<()> {
+        Ok(())
+    }
+
+    fn measure(&self, constraints: Size<Number>) -> Result<Size<f32>> {
+        Ok(Size { width: constraints.width.or_else(0.0), height: constraints.height.or_else(0.0) })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A `flexbox` node without props in any variant has nothing to draw, neither have the kinds
+/// implemented by the core. Nodes with an `id` keep a view for hosts to address, and so do
+/// nodes which clip or scroll their children in any variant.
+pub(crate) fn is_layout_only(json: &JsonValue) -> bool {
+    (is_virtual(json) || (json["kind"] == "flexbox" && !variants::has_section(json, "props")))
+        && !json.has_key("id")
+        && variants::sections(json, "layout")
+            .all(|layout| layout["overflow"].is_null() || layout["overflow"] == "visible")
+}
+
+/// Kinds which only exist in the layout tree and are never created by the view manager:
+///
+/// - `spacer` fills the free space of its parent
+/// - `gap` takes `size` points along the main axis of its parent
+/// - `hstack` and `vstack` lay out their children in a row or column
+pub(crate) fn is_virtual(json: &JsonValue) -> bool {
+    match json["kind"].as_str() {
+        Some(kind) => ["spacer", "gap", "hstack", "vstack"].contains(&kind),
+        None => false,
+    }
+}
+
+/// The layout of a node, its own layout merged over the preset of its kind. The layout of
+/// a `gap` depends on the layout of its parent.
+pub(crate) fn resolve(
+    json: &JsonValue,
+    props: &JsonValue,
+    parent_layout: &JsonValue,
+    render_context: &RenderContext,
+) -> JsonValue {
+    let preset = match json["kind"].as_str() {
+        Some("spacer") => object! { "flex-grow" => 1, "flex-shrink" => 1 },
+        Some("hstack") => object! { "flex-direction" => "row" },
+        Some("vstack") => object! { "flex-direction" => "column" },
+        Some("gap") => {
+            let size = object! { "unit" => "points", "value" => props["size"].as_f32().unwrap_or(0.0) };
+            let axis = match parent_layout["flex-direction"].as_str() {
+                Some("column") | Some("column-reverse") => "height",
+                _ => "width",
+            };
+            object! { axis => size, "flex-shrink" => 0 }
+        }
+        _ => JsonValue::Null,
+    };
+
+    let mut layout = variants::merged(json, "layout", render_context);
+    for (key, value) in preset.entries() {
+        if !layout.has_key(key) {
+            layout[key] = value.clone();
+        }
+    }
+
+    layout
+}
+
+/// Whether the layout of a node has to be resolved again when its parent's layout changes.
+pub(crate) fn depends_on_parent(json: &JsonValue) -> bool {
+    json["kind"] == "gap"
+}
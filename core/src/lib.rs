@@ -10,99 +10,1270 @@ use stretch::number::Number;
 use stretch::result::Result;
 use stretch::style::Dimension;
 
+use json::object;
 use json::JsonValue;
 use std::any::Any;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+mod action;
+mod color;
+mod document;
+mod encoding;
+mod fragments;
+mod hit;
+mod kinds;
+mod layout;
+mod lint;
+mod loader;
+mod markdown;
+mod navigator;
+mod patch;
+mod selector;
+mod state;
+mod strings;
+mod text;
+mod theme;
+mod variants;
+mod warning;
+
+pub use crate::action::ActionHandler;
+pub use crate::color::Color;
+use crate::document::Document;
+pub use crate::encoding::Encoding;
+pub use crate::fragments::{FragmentResolver, Fragments};
+pub use crate::kinds::KindRegistry;
+pub use crate::layout::LayoutView;
+pub use crate::loader::{DocumentLoader, LoadedDocument, Request, Response, Source, Transport};
+pub use crate::navigator::{NavigationEntry, NavigatorHost, ShardNavigator, Transition};
+pub use crate::strings::{plural_category, PluralCategory, Strings};
+pub use crate::text::{parse_markdown, parse_span, FontSize, FontStyle, FontWeight, TextDecoration, TextRun};
+pub use crate::theme::{Theme, ThemeMode};
+pub use crate::variants::Platform;
+pub use crate::warning::Warning;
 
 pub trait ShardView: Any {
-    fn add_child(&mut self, child: &ShardView) -> Result<()>;
+    fn add_child(&mut self, child: &dyn ShardView) -> Result<()>;
+
+    /// Views that never lose children, such as views of documents that are not patched, can rely on this default.
+    fn remove_child(&mut self, _child: &dyn ShardView) -> Result<()> {
+        Err(Box::new("this view does not support removing children"))
+    }
+
+    /// Insert `child` before the child at `index`, for children added by a patch.
+    fn insert_child(&mut self, _child: &dyn ShardView, _index: usize) -> Result<()> {
+        Err(Box::new("this view does not support inserting children"))
+    }
+
     fn set_prop(&mut self, key: &str, value: &JsonValue) -> Result<()>;
     fn set_frame(&mut self, frame: Rect<f32>) -> Result<()>;
     fn measure(&self, constraints: Size<Number>) -> Result<Size<f32>>;
-    fn as_any(&self) -> &Any;
+    fn as_any(&self) -> &dyn Any;
 }
 
 pub trait ShardViewManager {
-    fn create_view(&self, context: &Any, kind: &str) -> Result<Box<ShardView>>;
+    fn create_view(&self, context: &dyn Any, kind: &str) -> Result<Box<dyn ShardView>>;
 }
 
 pub struct ViewNode {
-    pub shard_view: Box<ShardView>,
+    pub shard_view: Box<dyn ShardView>,
     pub children: Vec<ViewNode>,
+    json: JsonValue,
+    props: JsonValue,
+    layout_only: bool,
+    frame: Rect<f32>,
+}
+
+impl ViewNode {
+    /// Layout only nodes have a `LayoutView` instead of a native view.
+    pub fn is_layout_only(&self) -> bool {
+        self.layout_only
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.json["id"].as_str()
+    }
+
+    pub fn kind(&self) -> &str {
+        self.json["kind"].as_str().unwrap_or_default()
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.json["class"].as_str().unwrap_or_default().split_whitespace()
+    }
+
+    /// The value of a prop as it was last set on the view.
+    pub fn prop(&self, key: &str) -> &JsonValue {
+        &self.props[key]
+    }
+
+    /// The frame of the node relative to the root as of the last `Root::measure`.
+    pub fn frame(&self) -> Rect<f32> {
+        self.frame
+    }
 }
 
 pub struct Root {
     pub view_node: ViewNode,
     pub stretch_node: stretch::style::Node,
+    pub warnings: Vec<Warning>,
+    document: Document,
+    render_context: RenderContext,
+    ids: HashMap<String, Vec<usize>>,
+    /// The document the root was rendered from, with patches applied.
+    source: JsonValue,
 }
 
 impl Root {
     pub fn measure(&mut self, size: Size<Number>) -> Result<()> {
-        set_frame(&mut self.view_node, &stretch::compute(&self.stretch_node, size)?)
+        let width = match size.width {
+            Number::Defined(width) => Some(width),
+            Number::Undefined => None,
+        };
+
+        if self.render_context.width != width {
+            self.render_context.width = width;
+            update(
+                &mut self.view_node,
+                &mut self.stretch_node,
+                &self.document,
+                &self.render_context,
+                Update::Media,
+                &JsonValue::Null,
+                false,
+                "/root",
+                &mut self.warnings,
+            )?;
+        }
+
+        set_frame(&mut self.view_node, &stretch::compute(&self.stretch_node, size)?, (0.0, 0.0), (0.0, 0.0))
+    }
+
+    pub fn view_by_id(&self, id: &str) -> Option<&ViewNode> {
+        let indices = self.ids.get(id)?;
+        Some(indices.iter().fold(&self.view_node, |view_node, i| &view_node.children[*i]))
+    }
+
+    pub fn frame_of(&self, id: &str) -> Option<Rect<f32>> {
+        self.view_by_id(id).map(ViewNode::frame)
+    }
+
+    /// The nodes under a point in root coordinates, deepest first, as of the last `measure`.
+    pub fn hit_test(&self, x: f32, y: f32) -> Vec<&ViewNode> {
+        let mut path = vec![];
+        hit::hit_test(&self.view_node, &self.stretch_node, x, y, &mut path);
+        path
+    }
+
+    /// The nodes matching a selector such as `list > .item[selected=true] text`, in document
+    /// order. See `selector.rs` for the supported syntax.
+    pub fn query(&self, selector: &str) -> Result<Vec<&ViewNode>> {
+        Ok(selector::Selector::parse(selector)?.query(&self.view_node))
+    }
+
+    /// Override a prop of the node with `id`. The value goes through the same resolution as
+    /// props from the document and survives theme and media updates. Call `measure` to lay out
+    /// the root again afterwards.
+    pub fn set_prop(&mut self, id: &str, key: &str, value: JsonValue) -> Result<()> {
+        let indices = match self.ids.get(id) {
+            Some(indices) => indices,
+            None => return Err(Box::new(format!("no node with id {}", id))),
+        };
+
+        let mut view_node = &mut self.view_node;
+        let mut stretch_node = &mut self.stretch_node;
+        let mut path = "/root".to_string();
+        stretch_node.layout_cache.replace(None);
+
+        for i in indices {
+            view_node = &mut view_node.children[*i];
+            stretch_node = &mut stretch_node.children[*i];
+            path = format!("{}/children/{}", path, i);
+            stretch_node.layout_cache.replace(None);
+        }
+
+        if view_node.layout_only {
+            return Err(Box::new(format!("node {} is layout only and has no view", id)));
+        }
+
+        view_node.json["props"][key] = value;
+        apply_props(view_node, &self.document, &self.render_context, &path, &mut self.warnings)?;
+        Ok(())
+    }
+
+    /// Run an action, or a chain of actions, such as the value of an `on-click` prop. `set-prop`
+    /// and `set-theme` are performed by the core, other actions are passed to `handler`. A chain
+    /// stops at the first action which fails. Call `measure` afterwards to lay out the root again.
+    pub fn dispatch(&mut self, action: &JsonValue, handler: &mut dyn ActionHandler) -> Result<()> {
+        if let Err((path, reason)) = action::validate(action, "") {
+            return Err(Box::new(format!("invalid action at {}: {}", path, reason)));
+        }
+
+        let mut result = Ok(());
+        let mut state_changed = false;
+
+        for action in action::chain(action) {
+            let value = &action["value"];
+            result = match action["action"].as_str().unwrap_or_default() {
+                "set-prop" => self.set_prop(
+                    value["id"].as_str().unwrap_or_default(),
+                    value["key"].as_str().unwrap_or_default(),
+                    value["value"].clone(),
+                ),
+                "set-theme" => self.set_theme_mode(if *value == "dark" { ThemeMode::Dark } else { ThemeMode::Light }),
+                name @ ("set-state" | "toggle-state" | "increment-state") => {
+                    state::apply(&mut self.render_context.state, name, value);
+                    state_changed = true;
+                    Ok(())
+                }
+                name => handler.on_action(name, value),
+            };
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if state_changed {
+            self.update_state()?;
+        }
+
+        result
+    }
+
+    pub fn state(&self) -> &JsonValue {
+        &self.render_context.state
+    }
+
+    /// Set the value at a dot separated path of the state and update the props and conditional
+    /// nodes bound to it. Call `measure` afterwards to lay out the root again.
+    pub fn set_state(&mut self, path: &str, value: JsonValue) -> Result<()> {
+        state::set(&mut self.render_context.state, path, value);
+        self.update_state()
+    }
+
+    fn update_state(&mut self) -> Result<()> {
+        update(
+            &mut self.view_node,
+            &mut self.stretch_node,
+            &self.document,
+            &self.render_context,
+            Update::State,
+            &JsonValue::Null,
+            false,
+            "/root",
+            &mut self.warnings,
+        )?;
+        Ok(())
+    }
+
+    pub fn render_context(&self) -> &RenderContext {
+        &self.render_context
+    }
+
+    /// The fragments of lazy includes which are still showing their placeholder, in document order.
+    pub fn pending_fragments(&self) -> Vec<&str> {
+        let mut pending = vec![];
+        pending_fragments(&self.view_node, &mut pending);
+        pending
+    }
+
+    /// Supply the JSON of a fragment, replacing the placeholders of lazy includes of `id` with it.
+    /// The fragment is cached so later renders include it directly. Replaced views are added
+    /// after those of their siblings. Call `measure` afterwards to lay out the root again, only
+    /// the replaced subtrees and their ancestors are laid out from scratch.
+    pub fn load_fragment(
+        &mut self,
+        platform: &dyn ShardViewManager,
+        context: &dyn Any,
+        id: &str,
+        json: &str,
+    ) -> Result<()> {
+        self.render_context.fragments.insert(id, json)?;
+
+        let mut renderer = Renderer {
+            platform,
+            context,
+            render_context: &self.render_context,
+            document: &self.document,
+            warnings: vec![],
+            in_placeholder: false,
+            includes: vec![],
+        };
+        let layout = resolve_layout(
+            &self.view_node.json,
+            &self.view_node.props,
+            &JsonValue::Null,
+            &self.document,
+            &self.render_context,
+        );
+        let result = load_fragment(
+            &mut renderer,
+            &mut self.view_node,
+            &mut self.stretch_node,
+            &mut LayoutView,
+            &layout,
+            "/root",
+            id,
+        );
+        self.warnings.append(&mut renderer.warnings);
+
+        // A fragment which fails to render is not kept around for later renders to fail on
+        if result.is_err() {
+            self.render_context.fragments.invalidate(id);
+        }
+
+        self.index_ids();
+        result.map(|_| ())
+    }
+
+    /// Apply a JSON Patch (RFC 6902) to the document the root was rendered from, see `patch.rs`
+    /// for the format. Operations on `/state` apply to the current state. Nodes which keep their
+    /// kind are updated in place, setting only the props which changed, while nodes which change
+    /// kind and added children are rendered again. The root node has to keep its kind. Call
+    /// `measure` afterwards to lay out the root again.
+    ///
+    /// The patched document is resolved and rendered before any view is touched, so a patch which
+    /// fails to apply or render leaves the root unchanged. Errors of the views themselves while the
+    /// changes are made are returned as they happen.
+    pub fn apply_patch(&mut self, platform: &dyn ShardViewManager, context: &dyn Any, patch: &JsonValue) -> Result<()> {
+        let mut source = self.source.clone();
+        source["state"] = self.render_context.state.clone();
+        patch::apply(&mut source, patch)?;
+
+        let document_changed = source["strings"] != self.source["strings"] || source["theme"] != self.source["theme"];
+        let document = if document_changed { Some(Document::new(&source)) } else { None };
+        let mut render_context = self.render_context.clone();
+        render_context.state = source["state"].clone();
+        let refresh = document_changed || render_context.state != self.render_context.state;
+
+        let mut renderer = Renderer {
+            platform,
+            context,
+            render_context: &render_context,
+            document: document.as_ref().unwrap_or(&self.document),
+            warnings: vec![],
+            in_placeholder: false,
+            includes: vec![],
+        };
+        let patch = plan_patch(
+            &mut renderer,
+            &self.view_node,
+            &self.stretch_node,
+            &self.source["root"],
+            &source["root"],
+            None,
+            refresh,
+            "/root",
+        )?;
+        let warnings = renderer.warnings;
+
+        if let Some(document) = document {
+            self.document = document;
+        }
+        self.render_context = render_context;
+        self.source = source;
+        for warning in warnings {
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+
+        let result = commit_patch(&mut self.view_node, &mut self.stretch_node, &mut LayoutView, 0, patch);
+        self.index_ids();
+        result.map(|_| ())
+    }
+
+    /// Index ids again after nodes were rendered, warning only about new duplicates.
+    fn index_ids(&mut self) {
+        let mut warnings = vec![];
+        self.ids.clear();
+        index_ids(&self.view_node, &mut vec![], "/root", &mut self.ids, &mut warnings);
+        for warning in warnings {
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+    }
+
+    pub fn set_theme_mode(&mut self, theme: ThemeMode) -> Result<()> {
+        if self.render_context.theme != theme {
+            self.render_context.theme = theme;
+            update(
+                &mut self.view_node,
+                &mut self.stretch_node,
+                &self.document,
+                &self.render_context,
+                Update::Props,
+                &JsonValue::Null,
+                false,
+                "/root",
+                &mut self.warnings,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Update {
+    Props,
+    Media,
+    State,
+}
+
+/// Update the props and layout of a subtree after the render context changed. Returns whether
+/// anything changed, in which case cached layouts are cleared.
+#[allow(clippy::too_many_arguments)]
+fn update(
+    view_node: &mut ViewNode,
+    stretch_node: &mut stretch::style::Node,
+    document: &Document,
+    render_context: &RenderContext,
+    update_kind: Update,
+    parent_layout: &JsonValue,
+    parent_changed: bool,
+    path: &str,
+    warnings: &mut Vec<Warning>,
+) -> Result<bool> {
+    let has_media = view_node.json.has_key("@media");
+    let mut dirty = false;
+
+    if update_kind != Update::Media || has_media {
+        dirty |= apply_props(view_node, document, render_context, path, warnings)?;
+    }
+
+    let layout = resolve_layout(&view_node.json, &view_node.props, parent_layout, document, render_context);
+    let toggled = view_node.json.has_key("if")
+        && (layout["display"] == "none") != (stretch_node.display == stretch::style::Display::None);
+    let changed = has_media || toggled || (parent_changed && layout::depends_on_parent(&view_node.json));
+
+    if changed {
+        let raw_shard_view = &*view_node.shard_view as *const dyn ShardView;
+        let children = std::mem::take(&mut stretch_node.children);
+        *stretch_node = parse_layout(&layout, raw_shard_view, children)?;
+        dirty = true;
+    }
+
+    for (i, (view_node, stretch_node)) in
+        view_node.children.iter_mut().zip(stretch_node.children.iter_mut()).enumerate()
+    {
+        let path = format!("{}/children/{}", path, i);
+        dirty |=
+            update(view_node, stretch_node, document, render_context, update_kind, &layout, changed, &path, warnings)?;
+    }
+
+    if dirty {
+        stretch_node.layout_cache.replace(None);
+    }
+
+    Ok(dirty)
+}
+
+fn pending_fragments<'a>(view_node: &'a ViewNode, pending: &mut Vec<&'a str>) {
+    if let Some(id) = view_node.json["$lazy"]["$include"].as_str() {
+        if !pending.contains(&id) {
+            pending.push(id);
+        }
+    }
+
+    for child in &view_node.children {
+        pending_fragments(child, pending);
+    }
+}
+
+/// Render lazy includes of fragment `id` below `view_node`, whose resolved layout is `layout`, in
+/// place of their placeholders. `native_parent` is the view the views of a layout only `view_node`
+/// are added to. Returns whether anything was replaced, in which case cached layouts are cleared.
+fn load_fragment(
+    renderer: &mut Renderer,
+    view_node: &mut ViewNode,
+    stretch_node: &mut stretch::style::Node,
+    native_parent: &mut dyn ShardView,
+    layout: &JsonValue,
+    path: &str,
+    id: &str,
+) -> Result<bool> {
+    let ViewNode { shard_view, children, layout_only, .. } = view_node;
+    let native_view: &mut dyn ShardView = if *layout_only { native_parent } else { &mut **shard_view };
+    let mut loaded = false;
+
+    for (i, (child, child_stretch_node)) in children.iter_mut().zip(stretch_node.children.iter_mut()).enumerate() {
+        let path = format!("{}/children/{}", path, i);
+
+        if child.json["$lazy"]["$include"] == id {
+            let include = child.json["$lazy"].clone();
+            let (view_node, stretch_node) = renderer.render(&include, Some(layout), &path)?;
+            remove_child(native_view, child)?;
+            add_child(native_view, &view_node)?;
+            *child = view_node;
+            *child_stretch_node = stretch_node;
+            loaded = true;
+        } else {
+            let layout = resolve_layout(&child.json, &child.props, layout, renderer.document, renderer.render_context);
+            loaded |= load_fragment(renderer, child, child_stretch_node, native_view, &layout, &path, id)?;
+        }
+    }
+
+    if loaded {
+        stretch_node.layout_cache.replace(None);
+    }
+
+    Ok(loaded)
+}
+
+/// Changes to a rendered node worked out by `plan_patch` and made by `commit_patch`.
+enum Patch {
+    /// Keep the node, replacing its document node, props and layout where given. The children in
+    /// `removed` are dropped and those in `inserted` rendered in their place, `children` holding the
+    /// changes to each child in their new order.
+    Keep {
+        json: Option<JsonValue>,
+        props: Option<JsonValue>,
+        layout: Option<stretch::style::Node>,
+        removed: Range<usize>,
+        inserted: Range<usize>,
+        children: Vec<Patch>,
+    },
+    /// Replace the node, or insert it when it is new, with a new rendering.
+    Render(ViewNode, stretch::style::Node),
+}
+
+/// Work out how to bring the subtree rendered from the document node `old` up to date with `new`
+/// without touching any view, `parent_layout` being the resolved layout of its parent or `None`
+/// for the root. Nodes rendered directly from `old` which keep their kind and view are updated in
+/// place, others are rendered again. With `refresh` the document or state changed as well, so the
+/// props of unchanged nodes are resolved again.
+#[allow(clippy::too_many_arguments)]
+fn plan_patch(
+    renderer: &mut Renderer,
+    view_node: &ViewNode,
+    stretch_node: &stretch::style::Node,
+    old: &JsonValue,
+    new: &JsonValue,
+    parent_layout: Option<&JsonValue>,
+    refresh: bool,
+    path: &str,
+) -> Result<Patch> {
+    if old == new {
+        let parent_changed = parent_layout.is_some();
+        let parent_layout = parent_layout.unwrap_or(&JsonValue::Null);
+        return plan_update(renderer, view_node, stretch_node, parent_layout, parent_changed, refresh, path);
+    }
+
+    let in_place = is_rendering_of(view_node, old)
+        && new["kind"] == old["kind"]
+        && !new.has_key("$include")
+        && (parent_layout.is_none() || layout::is_layout_only(new) == view_node.layout_only);
+
+    if !in_place {
+        if parent_layout.is_none() {
+            return Err(Box::new("cannot patch the root node into a different kind, render the document again"));
+        }
+
+        let (view_node, stretch_node) = renderer.render(new, parent_layout, path)?;
+        return Ok(Patch::Render(view_node, stretch_node));
+    }
+
+    let json = without_children(new);
+    let props = renderer.document.props(&json, renderer.render_context, path, &mut renderer.warnings)?;
+    let layout = resolve_layout(
+        &json,
+        &props,
+        parent_layout.unwrap_or(&JsonValue::Null),
+        renderer.document,
+        renderer.render_context,
+    );
+    let raw_shard_view = &*view_node.shard_view as *const dyn ShardView;
+    let patched_stretch_node = parse_layout(&layout, raw_shard_view, vec![])?;
+
+    let old_children: Vec<&JsonValue> = old["children"].members().collect();
+    let new_children: Vec<&JsonValue> = new["children"].members().collect();
+
+    // Keep the unchanged children at either end and render those in between again
+    let (prefix, suffix) = if old_children.len() == new_children.len() {
+        (old_children.len(), 0)
+    } else {
+        let prefix = old_children.iter().zip(&new_children).take_while(|(old, new)| old == new).count();
+        let suffix = old_children[prefix..]
+            .iter()
+            .rev()
+            .zip(new_children[prefix..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+        (prefix, suffix)
+    };
+    let removed = prefix..old_children.len() - suffix;
+    let inserted = prefix..new_children.len() - suffix;
+
+    let mut children = vec![];
+    for (i, child) in new_children.iter().enumerate() {
+        let path = format!("{}/children/{}", path, i);
+        children.push(if inserted.contains(&i) {
+            let (child, child_stretch_node) = renderer.render(child, Some(&layout), &path)?;
+            Patch::Render(child, child_stretch_node)
+        } else {
+            let old_index = if i < prefix { i } else { i + old_children.len() - new_children.len() };
+            plan_patch(
+                renderer,
+                &view_node.children[old_index],
+                &stretch_node.children[old_index],
+                old_children[old_index],
+                child,
+                Some(&layout),
+                refresh,
+                &path,
+            )?
+        });
     }
+
+    Ok(Patch::Keep {
+        json: Some(json),
+        props: Some(props),
+        layout: Some(patched_stretch_node),
+        removed,
+        inserted,
+        children,
+    })
 }
 
-fn set_frame(view_node: &mut ViewNode, layout: &stretch::layout::Node) -> Result<()> {
-    view_node.shard_view.set_frame(Rect {
-        start: layout.location.x,
-        end: layout.location.x + layout.size.width,
-        top: layout.location.y,
-        bottom: layout.location.y + layout.size.height,
-    })?;
+/// Work out how the props and layout of a subtree the patch left unchanged change, as `update`
+/// does. `parent_changed` tells whether the layout of its parent was resolved again.
+fn plan_update(
+    renderer: &mut Renderer,
+    view_node: &ViewNode,
+    stretch_node: &stretch::style::Node,
+    parent_layout: &JsonValue,
+    parent_changed: bool,
+    refresh: bool,
+    path: &str,
+) -> Result<Patch> {
+    let props = if refresh {
+        Some(renderer.document.props(&view_node.json, renderer.render_context, path, &mut renderer.warnings)?)
+    } else {
+        None
+    };
+
+    let layout = resolve_layout(
+        &view_node.json,
+        props.as_ref().unwrap_or(&view_node.props),
+        parent_layout,
+        renderer.document,
+        renderer.render_context,
+    );
+    let toggled = view_node.json.has_key("if")
+        && (layout["display"] == "none") != (stretch_node.display == stretch::style::Display::None);
+    let changed = (refresh && view_node.json.has_key("@media"))
+        || toggled
+        || (parent_changed && layout::depends_on_parent(&view_node.json));
+
+    let patched_stretch_node = if changed {
+        let raw_shard_view = &*view_node.shard_view as *const dyn ShardView;
+        Some(parse_layout(&layout, raw_shard_view, vec![])?)
+    } else {
+        None
+    };
+
+    let mut children = vec![];
+    if refresh || changed {
+        for (i, (child, child_stretch_node)) in view_node.children.iter().zip(&stretch_node.children).enumerate() {
+            let path = format!("{}/children/{}", path, i);
+            children.push(plan_update(renderer, child, child_stretch_node, &layout, changed, refresh, &path)?);
+        }
+    }
+
+    Ok(Patch::Keep { json: None, props, layout: patched_stretch_node, removed: 0..0, inserted: 0..0, children })
+}
+
+/// Make the changes worked out by `plan_patch`. `index` is the position of the first view of
+/// `view_node` among those of `native_parent`. Returns whether anything changed, in which case
+/// cached layouts are cleared.
+fn commit_patch(
+    view_node: &mut ViewNode,
+    stretch_node: &mut stretch::style::Node,
+    native_parent: &mut dyn ShardView,
+    index: usize,
+    patch: Patch,
+) -> Result<bool> {
+    let (json, props, layout, removed, inserted, patches) = match patch {
+        Patch::Render(patched_view_node, patched_stretch_node) => {
+            remove_child(native_parent, view_node)?;
+            insert_child(native_parent, &patched_view_node, index)?;
+            *view_node = patched_view_node;
+            *stretch_node = patched_stretch_node;
+            return Ok(true);
+        }
+        Patch::Keep { json, props, layout, removed, inserted, children } => {
+            (json, props, layout, removed, inserted, children)
+        }
+    };
+
+    let mut dirty = !removed.is_empty() || !inserted.is_empty();
+    if let Some(json) = json {
+        view_node.json = json;
+    }
+    if let Some(props) = props {
+        dirty |= set_props(view_node, &props)?;
+    }
+    if let Some(layout) = layout {
+        let children = std::mem::take(&mut stretch_node.children);
+        *stretch_node = layout;
+        stretch_node.children = children;
+        dirty = true;
+    }
+
+    let ViewNode { shard_view, children, layout_only, .. } = view_node;
+    let (native_view, mut index): (&mut dyn ShardView, usize) =
+        if *layout_only { (native_parent, index) } else { (&mut **shard_view, 0) };
+
+    stretch_node.children.drain(removed.clone());
+    for removed in children.drain(removed) {
+        remove_child(native_view, &removed)?;
+    }
+
+    for (i, patch) in patches.into_iter().enumerate() {
+        match patch {
+            Patch::Render(child, child_stretch_node) if inserted.contains(&i) => {
+                insert_child(native_view, &child, index)?;
+                children.insert(i, child);
+                stretch_node.children.insert(i, child_stretch_node);
+            }
+            patch => dirty |= commit_patch(&mut children[i], &mut stretch_node.children[i], native_view, index, patch)?,
+        }
+        index += native_count(&children[i]);
+    }
+
+    if dirty {
+        stretch_node.layout_cache.replace(None);
+    }
+
+    Ok(dirty)
+}
+
+/// Whether `view_node` was rendered from the document node `json` itself, rather than from its
+/// expansion, fragment or a fallback. Props are not compared as `set_prop` may have changed them.
+fn is_rendering_of(view_node: &ViewNode, json: &JsonValue) -> bool {
+    view_node.children.len() == json["children"].len()
+        && json
+            .entries()
+            .filter(|(key, _)| !["children", "props"].contains(key))
+            .all(|(key, value)| view_node.json[key] == *value)
+        && view_node.json.entries().all(|(key, _)| key == "props" || json.has_key(key))
+}
+
+/// The layout of a node, hidden with `display: none` while its `if` is false.
+fn resolve_layout(
+    json: &JsonValue,
+    props: &JsonValue,
+    parent_layout: &JsonValue,
+    document: &Document,
+    render_context: &RenderContext,
+) -> JsonValue {
+    let mut layout = layout::resolve(json, props, parent_layout, render_context);
+    if !document.is_visible(json, render_context) {
+        layout["display"] = "none".into();
+    }
+    layout
+}
+
+/// Resolve the props of a node again and set those which changed on its view.
+fn apply_props(
+    view_node: &mut ViewNode,
+    document: &Document,
+    render_context: &RenderContext,
+    path: &str,
+    warnings: &mut Vec<Warning>,
+) -> Result<bool> {
+    let props = document.props(&view_node.json, render_context, path, warnings)?;
+    set_props(view_node, &props)
+}
+
+/// Set the props of a node's view which differ from `props`, unsetting those it no longer has.
+fn set_props(view_node: &mut ViewNode, props: &JsonValue) -> Result<bool> {
+    let mut changed = false;
+
+    let removed: Vec<String> =
+        view_node.props.entries().filter(|(key, _)| !props.has_key(key)).map(|(key, _)| key.to_string()).collect();
+    for key in removed {
+        view_node.shard_view.set_prop(&key, &JsonValue::Null)?;
+        view_node.props.remove(&key);
+        changed = true;
+    }
+
+    for (key, value) in props.entries() {
+        if view_node.props[key] != *value {
+            view_node.shard_view.set_prop(key, value)?;
+            view_node.props[key] = value.clone();
+            changed = true;
+        }
+    }
+
+    Ok(changed)
+}
+
+/// View frames are relative to the nearest ancestor with a native view, `offset` being the position
+/// of the node's parent within it. `origin` is the position of the node's parent within the root.
+fn set_frame(
+    view_node: &mut ViewNode,
+    layout: &stretch::layout::Node,
+    offset: (f32, f32),
+    origin: (f32, f32),
+) -> Result<()> {
+    let (x, y) = (offset.0 + layout.location.x, offset.1 + layout.location.y);
+    let origin = (origin.0 + layout.location.x, origin.1 + layout.location.y);
+
+    view_node.frame = Rect {
+        start: origin.0,
+        end: origin.0 + layout.size.width,
+        top: origin.1,
+        bottom: origin.1 + layout.size.height,
+    };
+
+    let offset = if view_node.layout_only {
+        (x, y)
+    } else {
+        view_node.shard_view.set_frame(Rect {
+            start: x,
+            end: x + layout.size.width,
+            top: y,
+            bottom: y + layout.size.height,
+        })?;
+        (0.0, 0.0)
+    };
 
     for i in 0..view_node.children.len() {
         let view_node = &mut view_node.children[i];
         let layout = &layout.children[i];
-        set_frame(view_node, layout)?;
+        set_frame(view_node, layout, offset, origin)?;
     }
 
     Ok(())
 }
 
-pub fn render_root(platform: &ShardViewManager, context: &Any, json: &str) -> Result<Root> {
+#[derive(Debug, Clone)]
+pub struct RenderContext {
+    pub locale: String,
+    pub theme: ThemeMode,
+    pub width: Option<f32>,
+    pub platform: Option<Platform>,
+    pub kinds: Rc<KindRegistry>,
+    pub fragments: Rc<Fragments>,
+    pub placeholder: Option<JsonValue>,
+    /// State overriding the document's initial `state`, such as state saved from an earlier root.
+    pub state: JsonValue,
+}
+
+impl Default for RenderContext {
+    fn default() -> RenderContext {
+        RenderContext {
+            locale: "en".to_string(),
+            theme: ThemeMode::Light,
+            width: None,
+            platform: None,
+            kinds: Rc::new(KindRegistry::new()),
+            fragments: Rc::new(Fragments::default()),
+            placeholder: None,
+            state: JsonValue::Null,
+        }
+    }
+}
+
+pub fn render_root(platform: &dyn ShardViewManager, context: &dyn Any, json: &str) -> Result<Root> {
+    render_root_with_context(platform, context, &RenderContext::default(), json)
+}
+
+pub fn render_root_with_context(
+    platform: &dyn ShardViewManager,
+    context: &dyn Any,
+    render_context: &RenderContext,
+    json: &str,
+) -> Result<Root> {
     match json::parse(json) {
-        Ok(json) => render(platform, context, &json["root"]),
+        Ok(json) => render_document(platform, context, render_context, json),
         Err(err) => Err(Box::new(err.to_string())),
     }
 }
 
-fn render(platform: &ShardViewManager, context: &Any, json: &JsonValue) -> Result<Root> {
-    let mut shard_view = match json["kind"].as_str() {
-        Some(kind) => platform.create_view(context, kind)?,
-        None => return Err(Box::new("expected kind")),
+/// Render a document encoded as JSON, CBOR or MessagePack. The encoding is taken from
+/// `content_type` when it names one and detected from the first byte otherwise.
+pub fn render_root_from_bytes(
+    platform: &dyn ShardViewManager,
+    context: &dyn Any,
+    render_context: &RenderContext,
+    bytes: &[u8],
+    content_type: Option<&str>,
+) -> Result<Root> {
+    let encoding = match content_type.and_then(Encoding::from_content_type).or_else(|| Encoding::detect(bytes)) {
+        Some(encoding) => encoding,
+        None => return Err(Box::new("unknown document encoding")),
+    };
+    render_document(platform, context, render_context, encoding.decode(bytes)?)
+}
+
+fn render_document(
+    platform: &dyn ShardViewManager,
+    context: &dyn Any,
+    render_context: &RenderContext,
+    json: JsonValue,
+) -> Result<Root> {
+    let document = Document::new(&json);
+    let render_context =
+        &RenderContext { state: state::initial(&json["state"], &render_context.state), ..render_context.clone() };
+    let mut renderer = Renderer {
+        platform,
+        context,
+        render_context,
+        document: &document,
+        warnings: vec![],
+        in_placeholder: false,
+        includes: vec![],
     };
+    let (view_node, stretch_node) = renderer.render(&json["root"], None, "/root")?;
+    let mut warnings = renderer.warnings;
 
-    for (key, value) in json["props"].entries() {
-        shard_view.set_prop(key, value)?
+    let mut ids = HashMap::new();
+    index_ids(&view_node, &mut vec![], "/root", &mut ids, &mut warnings);
+
+    Ok(Root { view_node, stretch_node, warnings, document, render_context: render_context.clone(), ids, source: json })
+}
+
+/// Record the child indices leading to every node with an id. The first node wins if ids repeat.
+fn index_ids(
+    view_node: &ViewNode,
+    indices: &mut Vec<usize>,
+    path: &str,
+    ids: &mut HashMap<String, Vec<usize>>,
+    warnings: &mut Vec<Warning>,
+) {
+    if let Some(id) = view_node.id() {
+        if ids.contains_key(id) {
+            warnings.push(Warning { path: lint::pointer(path, "id"), message: format!("duplicate id {}", id) });
+        } else {
+            ids.insert(id.to_string(), indices.clone());
+        }
     }
 
-    let mut children: Vec<ViewNode> = vec![];
-    let mut node_children: Vec<stretch::style::Node> = vec![];
+    for (i, child) in view_node.children.iter().enumerate() {
+        indices.push(i);
+        index_ids(child, indices, &format!("{}/children/{}", path, i), ids, warnings);
+        indices.pop();
+    }
+}
 
-    for child in json["children"].members() {
-        let root = render(platform, context, child)?;
-        children.push(root.view_node);
-        node_children.push(root.stretch_node);
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+struct Renderer<'a> {
+    platform: &'a dyn ShardViewManager,
+    context: &'a dyn Any,
+    render_context: &'a RenderContext,
+    document: &'a Document,
+    warnings: Vec<Warning>,
+    in_placeholder: bool,
+    /// The fragments being included, outermost first.
+    includes: Vec<String>,
+}
+
+impl<'a> Renderer<'a> {
+    /// Render a node, `parent_layout` being the resolved layout of its parent or `None` for the root.
+    /// Errors in the subtree of a node marked `"error-boundary": true` are contained by rendering
+    /// its `fallback`, or leaving it empty, instead.
+    fn render(
+        &mut self,
+        json: &JsonValue,
+        parent_layout: Option<&JsonValue>,
+        path: &str,
+    ) -> Result<(ViewNode, stretch::style::Node)> {
+        if json["error-boundary"] != true {
+            return self.render_node(json, parent_layout, path);
+        }
+
+        match self.render_node(json, parent_layout, path) {
+            Ok(result) => Ok(result),
+            Err(error) => {
+                let message = format!("error boundary caught: {}", warning::describe(&*error));
+
+                if json.has_key("fallback") {
+                    self.warn(path, format!("{}, rendering its fallback", message));
+                    self.render(&json["fallback"], parent_layout, &lint::pointer(path, "fallback"))
+                } else if parent_layout.is_some() {
+                    self.warn(path, format!("{}, leaving it empty", message));
+                    self.render_empty(json, parent_layout, path)
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    }
+
+    fn render_node(
+        &mut self,
+        json: &JsonValue,
+        parent_layout: Option<&JsonValue>,
+        path: &str,
+    ) -> Result<(ViewNode, stretch::style::Node)> {
+        if json.has_key("$include") {
+            return self.render_include(json, parent_layout, path);
+        }
+
+        let expanded = self.render_context.kinds.expand(json, self.render_context)?;
+        let json = expanded.as_ref().unwrap_or(json);
+        lint::check_node(json, path, &mut self.warnings);
+
+        let layout_only = parent_layout.is_some() && layout::is_layout_only(json);
+
+        let mut shard_view: Box<dyn ShardView> = match json["kind"].as_str() {
+            Some(_) if layout_only => Box::new(LayoutView),
+            // The root always needs a native view to be mounted in, as do virtual nodes with an
+            // id or which clip their children
+            Some(_) if layout::is_virtual(json) => self.platform.create_view(self.context, "flexbox")?,
+            Some(kind) => match self.platform.create_view(self.context, kind) {
+                Ok(shard_view) => shard_view,
+                Err(error) => return self.render_fallback(json, parent_layout, path, error),
+            },
+            None => return Err(Box::new("expected kind")),
+        };
+
+        let props = self.document.props(json, self.render_context, path, &mut self.warnings)?;
+        for (key, value) in props.entries() {
+            shard_view.set_prop(key, value)?;
+        }
+
+        let layout =
+            resolve_layout(json, &props, parent_layout.unwrap_or(&JsonValue::Null), self.document, self.render_context);
+
+        let mut children: Vec<ViewNode> = vec![];
+        let mut node_children: Vec<stretch::style::Node> = vec![];
+
+        for (i, child) in json["children"].members().enumerate() {
+            let (view_node, stretch_node) = self.render(child, Some(&layout), &format!("{}/children/{}", path, i))?;
+            children.push(view_node);
+            node_children.push(stretch_node);
+        }
+
+        let raw_shard_view = &*shard_view as *const dyn ShardView;
+
+        if !layout_only {
+            for child in &children {
+                add_child(&mut *shard_view, child)?;
+            }
+        }
+
+        let stretch_node = parse_layout(&layout, raw_shard_view, node_children)?;
+        let view_node = ViewNode {
+            shard_view,
+            children,
+            json: without_children(json),
+            props,
+            layout_only,
+            frame: Rect { start: 0.0, end: 0.0, top: 0.0, bottom: 0.0 },
+        };
+        Ok((view_node, stretch_node))
+    }
+
+    /// Render the `fallback` of a node whose view could not be created, or else the placeholder of
+    /// the render context in its place. Without either the node is left empty, or fails the
+    /// render if it is the root.
+    fn render_fallback(
+        &mut self,
+        json: &JsonValue,
+        parent_layout: Option<&JsonValue>,
+        path: &str,
+        error: Box<dyn Any>,
+    ) -> Result<(ViewNode, stretch::style::Node)> {
+        let message = format!("could not create view of kind {}: {}", json["kind"], warning::describe(&*error));
+
+        if json.has_key("fallback") {
+            self.warn(path, format!("{}, rendering its fallback", message));
+            return self.render(&json["fallback"], parent_layout, &lint::pointer(path, "fallback"));
+        }
+
+        match &self.render_context.placeholder {
+            Some(placeholder) if !self.in_placeholder => {
+                self.warn(path, format!("{}, rendering a placeholder", message));
+
+                let mut node = placeholder.clone();
+                for (key, value) in json["layout"].entries() {
+                    node["layout"][key] = value.clone();
+                }
+
+                self.in_placeholder = true;
+                let result = self.render(&node, parent_layout, path);
+                self.in_placeholder = false;
+                result
+            }
+            _ if parent_layout.is_some() => {
+                self.warn(path, format!("{}, leaving it empty", message));
+                self.render_empty(json, parent_layout, path)
+            }
+            _ => Err(error),
+        }
     }
 
-    let raw_shard_view = &*shard_view as *const ShardView;
+    /// Render the fragment referenced by a `{"$include": "fragment-id"}` node in its place. An
+    /// include with a `placeholder` is lazy, its placeholder is rendered until the host supplies
+    /// the fragment through `Root::load_fragment`.
+    fn render_include(
+        &mut self,
+        json: &JsonValue,
+        parent_layout: Option<&JsonValue>,
+        path: &str,
+    ) -> Result<(ViewNode, stretch::style::Node)> {
+        let id = match json["$include"].as_str() {
+            Some(id) => id,
+            None => return Err(Box::new(format!("expected a string fragment id, got {}", json["$include"].dump()))),
+        };
+
+        if self.includes.iter().any(|include| include == id) {
+            return Err(Box::new(format!("fragment {} includes itself via {}", id, self.includes.join(" > "))));
+        }
+
+        if self.includes.len() >= MAX_INCLUDE_DEPTH {
+            return Err(Box::new(format!("includes nested more than {} deep at {}", MAX_INCLUDE_DEPTH, id)));
+        }
 
-    for child in &children {
-        shard_view.add_child(&*child.shard_view)?;
+        // The root has no parent view to swap its placeholder out of, so it is never lazy
+        let lazy = json.has_key("placeholder") && parent_layout.is_some();
+        let fragment = match self.render_context.fragments.cached(id) {
+            Some(fragment) => fragment,
+            None if lazy => return self.render_placeholder(json, parent_layout, path),
+            None => self.render_context.fragments.get(id)?,
+        };
+
+        self.includes.push(id.to_string());
+        let result = self.render(&with_include(json, &fragment), parent_layout, path);
+        self.includes.pop();
+        result
     }
 
-    let layout = match json["layout"] {
+    /// Render the `placeholder` of a lazy include whose fragment is not loaded yet, marking it
+    /// with the include so `Root::load_fragment` can swap the fragment in later.
+    fn render_placeholder(
+        &mut self,
+        json: &JsonValue,
+        parent_layout: Option<&JsonValue>,
+        path: &str,
+    ) -> Result<(ViewNode, stretch::style::Node)> {
+        let (mut view_node, stretch_node) =
+            self.render(&with_include(json, &json["placeholder"]), parent_layout, &lint::pointer(path, "placeholder"))?;
+
+        let mut include = json.clone();
+        include.remove("placeholder");
+        view_node.json["$lazy"] = include;
+        Ok((view_node, stretch_node))
+    }
+
+    /// Render a node without a view or children which keeps the layout of `json`.
+    fn render_empty(
+        &mut self,
+        json: &JsonValue,
+        parent_layout: Option<&JsonValue>,
+        path: &str,
+    ) -> Result<(ViewNode, stretch::style::Node)> {
+        let layout = if json["layout"].is_object() {
+            json["layout"].clone()
+        } else {
+            object! {}
+        };
+        self.render_node(&object! { "kind" => "flexbox", "layout" => layout }, parent_layout, path)
+    }
+
+    fn warn(&mut self, path: &str, message: String) {
+        self.warnings.push(Warning { path: path.to_string(), message });
+    }
+}
+
+/// A node to render in place of an include. Like composite kinds, layout set on the include
+/// overrides that of `node` and its `id`, `class` and `if` carry over unless `node` sets its own.
+fn with_include(include: &JsonValue, node: &JsonValue) -> JsonValue {
+    let mut node = node.clone();
+    for (key, value) in include["layout"].entries() {
+        node["layout"][key] = value.clone();
+    }
+    for key in &["id", "class", "if"] {
+        if include.has_key(key) && !node.has_key(key) {
+            node[*key] = include[*key].clone();
+        }
+    }
+    node
+}
+
+/// Add the view of `child` to `parent`, or the views of its children when it is layout only.
+fn add_child(parent: &mut dyn ShardView, child: &ViewNode) -> Result<()> {
+    if child.layout_only {
+        for child in &child.children {
+            add_child(parent, child)?;
+        }
+        Ok(())
+    } else {
+        parent.add_child(&*child.shard_view)
+    }
+}
+
+/// Insert the view of `child` into `parent` at `index`, or the views of its children when it is layout only.
+fn insert_child(parent: &mut dyn ShardView, child: &ViewNode, index: usize) -> Result<()> {
+    if child.layout_only {
+        let mut index = index;
+        for child in &child.children {
+            insert_child(parent, child, index)?;
+            index += native_count(child);
+        }
+        Ok(())
+    } else {
+        parent.insert_child(&*child.shard_view, index)
+    }
+}
+
+/// The number of views `view_node` adds to the view of its parent.
+fn native_count(view_node: &ViewNode) -> usize {
+    if view_node.layout_only {
+        view_node.children.iter().map(native_count).sum()
+    } else {
+        1
+    }
+}
+
+/// Remove the view of `child` from `parent`, or the views of its children when it is layout only.
+fn remove_child(parent: &mut dyn ShardView, child: &ViewNode) -> Result<()> {
+    if child.layout_only {
+        for child in &child.children {
+            remove_child(parent, child)?;
+        }
+        Ok(())
+    } else {
+        parent.remove_child(&*child.shard_view)
+    }
+}
+
+fn without_children(json: &JsonValue) -> JsonValue {
+    let mut result = JsonValue::new_object();
+    for (key, value) in json.entries().filter(|(key, _)| *key != "children") {
+        result[key] = value.clone();
+    }
+    result
+}
+
+fn parse_layout(
+    layout: &JsonValue,
+    raw_shard_view: *const dyn ShardView,
+    node_children: Vec<stretch::style::Node>,
+) -> Result<stretch::style::Node> {
+    let layout = match layout {
         JsonValue::Object(ref value) => value,
         _ => return Err(Box::new("expected layout")),
     };
 
-    let stretch_node = stretch::style::Node {
+    Ok(stretch::style::Node {
         display: match layout["display"] {
             JsonValue::Short(ref value) if value == "flex" => stretch::style::Display::Flex,
             JsonValue::Short(ref value) if value == "none" => stretch::style::Display::None,
             _ => Default::default(),
         },
 
+        // Stretch lays out hidden absolute children twice which shifts the layouts of their
+        // siblings, hidden nodes take no part in layout either way
         position_type: match layout["position"] {
+            _ if layout["display"] == "none" => stretch::style::PositionType::Relative,
             JsonValue::Short(ref value) if value == "relative" => stretch::style::PositionType::Relative,
             JsonValue::Short(ref value) if value == "absolute" => stretch::style::PositionType::Absolute,
             _ => Default::default(),
@@ -248,9 +1419,7 @@ fn render(platform: &ShardViewManager, context: &Any, json: &JsonValue) -> Resul
         children: node_children,
 
         ..Default::default()
-    };
-
-    Ok(Root { view_node: ViewNode { shard_view, children }, stretch_node })
+    })
 }
 
 fn parse_dimension(json: &JsonValue, default: Dimension) -> Result<Dimension> {
@@ -0,0 +1,200 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::action;
+use crate::Warning;
+use json::JsonValue;
+
+const NODE_KEYS: &[&str] = &[
+    "id",
+    "class",
+    "if",
+    "kind",
+    "layout",
+    "props",
+    "children",
+    "fallback",
+    "error-boundary",
+    "@media",
+    "android",
+    "ios",
+];
+const VARIANT_KEYS: &[&str] = &["layout", "props", "min-width", "max-width"];
+
+const LAYOUT_ENUMS: &[(&str, &[&str])] = &[
+    ("display", &["flex", "none"]),
+    ("position", &["relative", "absolute"]),
+    ("direction", &["inherit", "ltr", "rtl"]),
+    ("flex-direction", &["row", "row-reverse", "column", "column-reverse"]),
+    ("flex-wrap", &["nowrap", "wrap", "wrap-reverse"]),
+    ("overflow", &["visible", "hidden", "scroll"]),
+    ("align-items", &["flex-start", "flex-end", "center", "baseline", "stretch"]),
+    ("align-self", &["auto", "flex-start", "flex-end", "center", "baseline", "stretch"]),
+    ("align-content", &["flex-start", "flex-end", "center", "stretch", "space-between", "space-around"]),
+    ("justify-content", &["flex-start", "flex-end", "center", "space-between", "space-around", "space-evenly"]),
+];
+
+const LAYOUT_NUMBERS: &[&str] = &["flex-grow", "flex-shrink", "aspect-ratio"];
+
+const LAYOUT_DIMENSIONS: &[&str] = &[
+    "start",
+    "end",
+    "top",
+    "bottom",
+    "margin",
+    "margin-start",
+    "margin-end",
+    "margin-top",
+    "margin-bottom",
+    "padding",
+    "padding-start",
+    "padding-end",
+    "padding-top",
+    "padding-bottom",
+    "border",
+    "border-start",
+    "border-end",
+    "border-top",
+    "border-bottom",
+    "flex-basis",
+    "width",
+    "height",
+    "min-width",
+    "min-height",
+    "max-width",
+    "max-height",
+];
+
+const VIEW_PROPS: &[&str] = &["background-color", "border-color", "border-radius", "border-width", "on-click"];
+
+/// Props understood by the built-in kinds, on top of `VIEW_PROPS` for those with a view. Kinds
+/// registered by the app are not known to the core and are not checked.
+const KIND_PROPS: &[(&str, &[&str])] = &[
+    ("flexbox", &[]),
+    ("solid-color", &[]),
+    ("text", &["span", "markdown", "text-align", "max-lines", "line-height"]),
+    ("image", &["src", "content-mode"]),
+    ("scroll", &["direction", "content-inset", "content"]),
+    ("spacer", &[]),
+    ("gap", &["size"]),
+    ("hstack", &[]),
+    ("vstack", &[]),
+];
+
+const VIRTUAL_KINDS: &[&str] = &["spacer", "gap", "hstack", "vstack"];
+
+const PROP_ENUMS: &[(&str, &str, &[&str])] = &[
+    ("text", "text-align", &["start", "end", "center"]),
+    ("scroll", "direction", &["horizontal", "vertical"]),
+    ("image", "content-mode", &["cover", "contain", "center"]),
+];
+
+const DEPRECATED_KINDS: &[(&str, &str)] = &[("solid-color", "use flexbox with a background-color")];
+
+/// Check a node, without descending into its children, for values which render but are most
+/// likely mistakes in the document.
+pub(crate) fn check_node(json: &JsonValue, path: &str, warnings: &mut Vec<Warning>) {
+    let mut warn = |path: String, message: String| warnings.push(Warning { path, message });
+    let kind = json["kind"].as_str().unwrap_or_default();
+
+    for (key, _) in json.entries().filter(|(key, _)| !NODE_KEYS.contains(key)) {
+        warn(pointer(path, key), format!("ignored key {}", key));
+    }
+
+    for key in &["id", "class"] {
+        if json.has_key(key) && !json[*key].is_string() {
+            warn(pointer(path, key), format!("expected a string {}, got {}", key, json[*key].dump()));
+        }
+    }
+
+    if let Some((_, message)) = DEPRECATED_KINDS.iter().find(|(deprecated, _)| *deprecated == kind) {
+        warn(pointer(path, "kind"), format!("kind {} is deprecated, {}", kind, message));
+    }
+
+    // The node itself and its platform and media variants
+    let mut sections = vec![(json, path.to_string())];
+    for platform in &["android", "ios"] {
+        if json.has_key(platform) {
+            sections.push((&json[*platform], pointer(path, platform)));
+        }
+    }
+    for (i, variant) in json["@media"].members().enumerate() {
+        let variant_path = format!("{}/@media/{}", path, i);
+        for (key, _) in variant.entries().filter(|(key, _)| !VARIANT_KEYS.contains(key)) {
+            warn(pointer(&variant_path, key), format!("ignored key {}", key));
+        }
+        sections.push((variant, variant_path));
+    }
+
+    for (section, section_path) in sections {
+        check_layout(&section["layout"], &pointer(&section_path, "layout"), &mut warn);
+        check_props(kind, &section["props"], &pointer(&section_path, "props"), &mut warn);
+        check_actions(&section["props"], &pointer(&section_path, "props"), &mut warn);
+    }
+}
+
+fn check_layout(layout: &JsonValue, path: &str, warn: &mut dyn FnMut(String, String)) {
+    for (key, value) in layout.entries() {
+        let path = pointer(path, key);
+
+        if let Some((_, values)) = LAYOUT_ENUMS.iter().find(|(name, _)| *name == key) {
+            if !value.as_str().is_some_and(|value| values.contains(&value)) {
+                warn(path, format!("unknown value {} for {}, using the default", value.dump(), key));
+            }
+        } else if LAYOUT_NUMBERS.contains(&key) {
+            if !value.is_number() {
+                warn(path, format!("expected a number for {}, using the default", key));
+            }
+        } else if LAYOUT_DIMENSIONS.contains(&key) {
+            match value["unit"].as_str() {
+                Some("auto") => (),
+                Some("points") | Some("percent") if value["value"].is_number() => (),
+                _ => warn(path, format!("invalid dimension {} for {}, using the default", value.dump(), key)),
+            }
+        } else {
+            warn(path, format!("ignored layout key {}", key));
+        }
+    }
+}
+
+fn check_props(kind: &str, props: &JsonValue, path: &str, warn: &mut dyn FnMut(String, String)) {
+    let known = match KIND_PROPS.iter().find(|(name, _)| *name == kind) {
+        Some((_, known)) => known,
+        None => return,
+    };
+
+    let is_virtual = VIRTUAL_KINDS.contains(&kind);
+
+    for (key, value) in props.entries() {
+        let path = pointer(path, key);
+
+        if !known.contains(&key) && (is_virtual || !VIEW_PROPS.contains(&key)) {
+            warn(path, format!("unknown prop {} for kind {}", key, kind));
+            continue;
+        }
+
+        let values =
+            PROP_ENUMS.iter().find(|(name, prop, _)| *name == kind && *prop == key).map(|(_, _, values)| values);
+        if let (Some(values), Some(value)) = (values, value.as_str()) {
+            if !values.contains(&value) {
+                warn(path, format!("unknown value {} for {}, using the default", value, key));
+            }
+        }
+    }
+}
+
+fn check_actions(props: &JsonValue, path: &str, warn: &mut dyn FnMut(String, String)) {
+    for (key, value) in props.entries().filter(|(key, _)| action::is_action_prop(key)) {
+        if let Err((path, reason)) = action::validate(value, &pointer(path, key)) {
+            warn(path, format!("{}, ignoring {}", reason, key));
+        }
+    }
+}
+
+/// Append `key` to a JSON pointer, escaping it as described in RFC 6901.
+pub(crate) fn pointer(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"))
+}
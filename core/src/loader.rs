@@ -0,0 +1,235 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::encoding::Encoding;
+use json::object;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use stretch::result::Result;
+
+pub struct Request {
+    pub url: String,
+    /// The ETag of the cached copy, to send as `If-None-Match`.
+    pub etag: Option<String>,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+    pub etag: Option<String>,
+    pub cache_control: Option<String>,
+}
+
+impl Response {
+    /// A response with a body in any encoding `Encoding` reads. Successful bodies are decoded and
+    /// kept as JSON, which is what the cache stores.
+    pub fn from_bytes(status: u16, body: &[u8], content_type: Option<&str>) -> Result<Response> {
+        let body = match status {
+            200..=299 => {
+                let encoding = content_type.and_then(Encoding::from_content_type).or_else(|| Encoding::detect(body));
+                match encoding {
+                    Some(encoding) => encoding.decode(body)?.dump(),
+                    None => return Err(Box::new("unknown document encoding")),
+                }
+            }
+            _ => String::from_utf8_lossy(body).into_owned(),
+        };
+        Ok(Response { status, body, etag: None, cache_control: None })
+    }
+}
+
+/// Performs HTTP requests for a `DocumentLoader`, blocking until the response arrives.
+pub trait Transport {
+    fn fetch(&mut self, request: &Request) -> Result<Response>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    /// A cached copy still within its max-age, no request was made.
+    Cache,
+    /// A copy which was fetched, or cached and confirmed unchanged by the server.
+    Network,
+    /// A cached copy past its max-age, shown while revalidating or when the server is unreachable.
+    Stale,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedDocument {
+    pub body: String,
+    pub source: Source,
+}
+
+/// Loads documents through a `Transport`, caching them on disk keyed by URL. Cached copies are
+/// revalidated with their ETag once their `max-age` has passed.
+pub struct DocumentLoader {
+    transport: Box<dyn Transport>,
+    cache_dir: PathBuf,
+}
+
+struct Entry {
+    body: String,
+    etag: Option<String>,
+    expires_at: u64,
+}
+
+impl DocumentLoader {
+    pub fn new(transport: Box<dyn Transport>, cache_dir: PathBuf) -> DocumentLoader {
+        DocumentLoader { transport, cache_dir }
+    }
+
+    /// The cached copy of a document, whether or not it is still fresh.
+    pub fn cached(&self, url: &str) -> Option<String> {
+        self.read(url).map(|entry| entry.body)
+    }
+
+    /// Load a document from the cache while it is fresh and from the network otherwise, falling
+    /// back to a stale cached copy when the request fails.
+    pub fn load(&mut self, url: &str) -> Result<LoadedDocument> {
+        let entry = self.read(url);
+
+        match entry {
+            Some(entry) if entry.expires_at > now() => Ok(LoadedDocument { body: entry.body, source: Source::Cache }),
+            Some(entry) => match self.revalidate(url, Some(&entry)) {
+                Ok(body) => Ok(LoadedDocument { body: body.unwrap_or(entry.body), source: Source::Network }),
+                Err(_) => Ok(LoadedDocument { body: entry.body, source: Source::Stale }),
+            },
+            None => {
+                let body = self.revalidate(url, None)?.unwrap_or_default();
+                Ok(LoadedDocument { body, source: Source::Network })
+            }
+        }
+    }
+
+    /// Like `load`, but a stale cached copy is passed to `on_document` before revalidating so it
+    /// can be shown right away. `on_document` is called a second time only if the document changed.
+    pub fn load_stale_while_revalidate(
+        &mut self,
+        url: &str,
+        on_document: &mut dyn FnMut(LoadedDocument),
+    ) -> Result<()> {
+        let entry = match self.read(url) {
+            Some(entry) => entry,
+            None => {
+                on_document(self.load(url)?);
+                return Ok(());
+            }
+        };
+
+        if entry.expires_at > now() {
+            on_document(LoadedDocument { body: entry.body, source: Source::Cache });
+            return Ok(());
+        }
+
+        on_document(LoadedDocument { body: entry.body.clone(), source: Source::Stale });
+
+        // The stale copy is already showing, so a failed revalidation is not an error
+        if let Ok(Some(body)) = self.revalidate(url, Some(&entry)) {
+            if body != entry.body {
+                on_document(LoadedDocument { body, source: Source::Network });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetch a document, conditionally if there is a cached copy. Returns the new body, or `None`
+    /// if the server confirmed the cached copy is unchanged.
+    fn revalidate(&mut self, url: &str, entry: Option<&Entry>) -> Result<Option<String>> {
+        let request = Request { url: url.to_string(), etag: entry.and_then(|entry| entry.etag.clone()) };
+        let response = self.transport.fetch(&request)?;
+        let cache_control = CacheControl::parse(response.cache_control.as_deref().unwrap_or_default());
+
+        match (response.status, entry) {
+            (304, Some(entry)) => {
+                let etag = response.etag.or_else(|| entry.etag.clone());
+                self.write(url, &entry.body, etag, cache_control);
+                Ok(None)
+            }
+            (200..=299, _) => {
+                self.write(url, &response.body, response.etag, cache_control);
+                Ok(Some(response.body))
+            }
+            (status, _) => Err(Box::new(format!("unexpected status {} loading {}", status, url))),
+        }
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.cache_dir.join(format!("{:016x}.json", fnv1a(url)))
+    }
+
+    fn read(&self, url: &str) -> Option<Entry> {
+        let json = json::parse(&fs::read_to_string(self.path(url)).ok()?).ok()?;
+
+        // Guard against hash collisions between URLs
+        if json["url"] != url {
+            return None;
+        }
+
+        Some(Entry {
+            body: json["body"].as_str()?.to_string(),
+            etag: json["etag"].as_str().map(str::to_string),
+            expires_at: json["expires-at"].as_u64().unwrap_or(0),
+        })
+    }
+
+    /// Caching is best effort, a document which cannot be written is simply fetched next time.
+    fn write(&self, url: &str, body: &str, etag: Option<String>, cache_control: CacheControl) {
+        let path = self.path(url);
+        if cache_control.no_store {
+            let _ = fs::remove_file(path);
+            return;
+        }
+
+        let entry = object! {
+            "url" => url,
+            "etag" => etag,
+            "expires-at" => now().saturating_add(cache_control.max_age),
+            "body" => body
+        };
+
+        let temp = path.with_extension("tmp");
+        if fs::create_dir_all(&self.cache_dir).is_ok() && fs::write(&temp, entry.dump()).is_ok() {
+            let _ = fs::rename(temp, path);
+        }
+    }
+}
+
+#[derive(Default)]
+struct CacheControl {
+    max_age: u64,
+    no_store: bool,
+}
+
+impl CacheControl {
+    /// Documents without a `max-age` are revalidated every time they are loaded.
+    fn parse(header: &str) -> CacheControl {
+        let mut cache_control = CacheControl::default();
+
+        for directive in header.split(',').map(|directive| directive.trim().to_ascii_lowercase()) {
+            if directive == "no-store" {
+                cache_control.no_store = true;
+            } else if directive == "no-cache" {
+                cache_control.max_age = 0;
+                break;
+            } else if let Some(max_age) = directive.strip_prefix("max-age=") {
+                cache_control.max_age = max_age.trim_matches('"').parse().unwrap_or(0);
+            }
+        }
+
+        cache_control
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+/// FNV-1a, which unlike the standard library hasher is stable across releases, so cache file
+/// names survive compiler upgrades.
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
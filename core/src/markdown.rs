@@ -0,0 +1,276 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::text::{FontSize, FontStyle, FontWeight, TextDecoration, TextRun};
+use json::object;
+
+const LINK_ACTION: &str = "open-url";
+const CODE_FONT_FAMILY: &str = "monospace";
+const BULLET: &str = "• ";
+const HEADING_SCALES: [f32; 6] = [2.0, 1.5, 1.25, 1.0, 0.875, 0.85];
+
+enum Block {
+    Paragraph(String),
+    Heading(usize, String),
+    ListItem(String, String),
+}
+
+/// Compile a CommonMark subset into runs styled on top of `base`. Supported are paragraphs,
+/// ATX headings, bullet and ordered lists, emphasis, strong emphasis, code spans, links and
+/// backslash escapes. Anything else is kept as literal text.
+pub(crate) fn parse(source: &str, base: &TextRun) -> Vec<TextRun> {
+    let blocks = blocks(source);
+    let mut runs = vec![];
+
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 {
+            let separator = match (&blocks[i - 1], block) {
+                // Items of the same list are on consecutive lines
+                (Block::ListItem(previous, _), Block::ListItem(marker, _))
+                    if (previous == BULLET) == (marker == BULLET) =>
+                {
+                    "\n"
+                }
+                _ => "\n\n",
+            };
+            push(&mut runs, base, separator);
+        }
+
+        match block {
+            Block::Paragraph(text) => inline(text, base, &mut runs),
+            Block::Heading(level, text) => {
+                let style = TextRun {
+                    font_weight: FontWeight::Bold,
+                    font_size: scaled(base.font_size, HEADING_SCALES[level - 1]),
+                    ..base.clone()
+                };
+                inline(text, &style, &mut runs);
+            }
+            Block::ListItem(marker, text) => {
+                push(&mut runs, base, marker);
+                inline(text, base, &mut runs);
+            }
+        }
+    }
+
+    runs
+}
+
+fn blocks(source: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut paragraph: Vec<&str> = vec![];
+    let mut after_blank = false;
+
+    for line in source.lines() {
+        let line = line.trim();
+        let block = if line.is_empty() { None } else { heading(line).or_else(|| list_item(line)) };
+
+        if (line.is_empty() || block.is_some()) && !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+
+        match block {
+            Some(block) => blocks.push(block),
+            None if line.is_empty() => (),
+            None => match blocks.last_mut() {
+                Some(Block::ListItem(_, text)) if paragraph.is_empty() && !after_blank => {
+                    text.push(' ');
+                    text.push_str(line);
+                }
+                _ => paragraph.push(line),
+            },
+        }
+
+        after_blank = line.is_empty();
+    }
+
+    if !paragraph.is_empty() {
+        blocks.push(Block::Paragraph(paragraph.join(" ")));
+    }
+
+    blocks
+}
+
+fn heading(line: &str) -> Option<Block> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+
+    if level == 0 || level > 6 || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+
+    Some(Block::Heading(level, rest.trim().trim_end_matches('#').trim_end().to_string()))
+}
+
+fn list_item(line: &str) -> Option<Block> {
+    for bullet in &["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return Some(Block::ListItem(BULLET.to_string(), rest.trim().to_string()));
+        }
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+
+    if (1..=9).contains(&digits) && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some(Block::ListItem(format!("{}. ", &line[..digits]), rest[2..].trim().to_string()));
+    }
+
+    None
+}
+
+fn inline(text: &str, style: &TextRun, runs: &mut Vec<TextRun>) {
+    let chars: Vec<char> = text.chars().collect();
+    inline_chars(&chars, style, runs);
+}
+
+fn inline_chars(chars: &[char], style: &TextRun, runs: &mut Vec<TextRun>) {
+    let mut literal = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '\\' && chars.get(i + 1).is_some_and(char::is_ascii_punctuation) {
+            literal.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+
+        if c == '`' {
+            if let Some(end) = position(chars, i + 1, '`') {
+                push(runs, style, &literal);
+                literal.clear();
+
+                let code = TextRun { font_family: Some(CODE_FONT_FAMILY.to_string()), ..style.clone() };
+                push(runs, &code, &chars[i + 1..end].iter().collect::<String>());
+                i = end + 1;
+                continue;
+            }
+        }
+
+        if c == '[' {
+            if let Some((label_end, end, url)) = link(chars, i) {
+                push(runs, style, &literal);
+                literal.clear();
+
+                let link = TextRun {
+                    decoration: TextDecoration::Underline,
+                    action: Some(object! { "action" => LINK_ACTION, "value" => url }),
+                    ..style.clone()
+                };
+                inline_chars(&chars[i + 1..label_end], &link, runs);
+                i = end + 1;
+                continue;
+            }
+        }
+
+        if (c == '*' || c == '_') && !(c == '_' && i > 0 && chars[i - 1].is_alphanumeric()) {
+            let strong = chars.get(i + 1) == Some(&c);
+            let width = if strong { 2 } else { 1 };
+            let delimiter = &[c, c][..width];
+
+            if let Some(end) = find_closing(chars, i + width, delimiter) {
+                let content = &chars[i + width..end];
+
+                if !content.is_empty() && !content[0].is_whitespace() {
+                    push(runs, style, &literal);
+                    literal.clear();
+
+                    let emphasis = if strong {
+                        TextRun { font_weight: FontWeight::Bold, ..style.clone() }
+                    } else {
+                        TextRun { font_style: FontStyle::Italic, ..style.clone() }
+                    };
+                    inline_chars(content, &emphasis, runs);
+                    i = end + width;
+                    continue;
+                }
+            }
+        }
+
+        literal.push(c);
+        i += 1;
+    }
+
+    push(runs, style, &literal);
+}
+
+/// The indices of the closing `]` and `)` and the destination of a `[label](destination)` link.
+fn link(chars: &[char], start: usize) -> Option<(usize, usize, String)> {
+    let label_end = find_closing(chars, start + 1, &[']'])?;
+
+    if chars.get(label_end + 1) != Some(&'(') {
+        return None;
+    }
+
+    let end = position(chars, label_end + 2, ')')?;
+    Some((label_end, end, chars[label_end + 2..end].iter().collect::<String>().trim().to_string()))
+}
+
+/// Find `delimiter` from `from` skipping escapes and code spans. A single character delimiter
+/// does not match half of a doubled one so `*a **b** c*` closes at the last `*`.
+fn find_closing(chars: &[char], from: usize, delimiter: &[char]) -> Option<usize> {
+    let mut i = from;
+
+    while i + delimiter.len() <= chars.len() {
+        match chars[i] {
+            '\\' => {
+                i += 2;
+                continue;
+            }
+            '`' => {
+                if let Some(end) = position(chars, i + 1, '`') {
+                    i = end + 1;
+                    continue;
+                }
+            }
+            _ => (),
+        }
+
+        if chars[i..].starts_with(delimiter) {
+            if delimiter.len() == 1 && chars.get(i + 1) == Some(&delimiter[0]) {
+                i += 2;
+                continue;
+            }
+            return Some(i);
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+fn position(chars: &[char], from: usize, c: char) -> Option<usize> {
+    chars.get(from..)?.iter().position(|other| *other == c).map(|offset| from + offset)
+}
+
+/// Append `text` in `style`, extending the previous run when the styles match.
+fn push(runs: &mut Vec<TextRun>, style: &TextRun, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    if let Some(last) = runs.last_mut() {
+        let same_style =
+            TextRun { text: String::new(), ..last.clone() } == TextRun { text: String::new(), ..style.clone() };
+        if same_style {
+            last.text.push_str(text);
+            return;
+        }
+    }
+
+    runs.push(TextRun { text: text.to_string(), ..style.clone() });
+}
+
+fn scaled(size: FontSize, scale: f32) -> FontSize {
+    match size {
+        FontSize::Points(size) => FontSize::Points(size * scale),
+        FontSize::Pixels(size) => FontSize::Pixels(size * scale),
+    }
+}
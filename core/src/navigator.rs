@@ -0,0 +1,145 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::ActionHandler;
+use crate::Root;
+use json::JsonValue;
+use stretch::geometry::Point;
+use stretch::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    Push,
+    Pop,
+    Replace,
+}
+
+/// What a navigator needs from the app: rendering the targets of `push` and `replace` actions,
+/// animating between roots and handling all other actions.
+pub trait NavigatorHost: ActionHandler {
+    /// Render the target of a `push` or `replace`, a URL or an inline document.
+    fn render(&mut self, target: &JsonValue) -> Result<Box<Root>>;
+
+    /// Show `to` in place of `from`, which is `None` for the first root. When popping, `to`
+    /// should be scrolled back to its `scroll_position`.
+    fn transition(
+        &mut self,
+        transition: Transition,
+        from: Option<&NavigationEntry>,
+        to: &NavigationEntry,
+    ) -> Result<()>;
+}
+
+pub struct NavigationEntry {
+    /// Boxed so that hosts can keep pointers to the root while it is on the stack.
+    pub root: Box<Root>,
+    pub target: JsonValue,
+    pub scroll_position: Point<f32>,
+}
+
+/// A stack of roots driven by `push`, `pop` and `replace` actions. Covered roots are kept alive
+/// so their state is where it was left when they are revealed again.
+#[derive(Default)]
+pub struct ShardNavigator {
+    entries: Vec<NavigationEntry>,
+}
+
+impl ShardNavigator {
+    pub fn new() -> ShardNavigator {
+        ShardNavigator::default()
+    }
+
+    pub fn entries(&self) -> &[NavigationEntry] {
+        &self.entries
+    }
+
+    pub fn top(&self) -> Option<&NavigationEntry> {
+        self.entries.last()
+    }
+
+    pub fn top_mut(&mut self) -> Option<&mut NavigationEntry> {
+        self.entries.last_mut()
+    }
+
+    /// Record where the top root is scrolled to, to restore it when the root is revealed again.
+    pub fn set_scroll_position(&mut self, x: f32, y: f32) {
+        if let Some(entry) = self.entries.last_mut() {
+            entry.scroll_position = Point { x, y };
+        }
+    }
+
+    pub fn push(&mut self, host: &mut dyn NavigatorHost, target: &JsonValue) -> Result<()> {
+        let entry = NavigationEntry { root: host.render(target)?, target: target.clone(), scroll_position: origin() };
+        host.transition(Transition::Push, self.entries.last(), &entry)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn replace(&mut self, host: &mut dyn NavigatorHost, target: &JsonValue) -> Result<()> {
+        let entry = NavigationEntry { root: host.render(target)?, target: target.clone(), scroll_position: origin() };
+        host.transition(Transition::Replace, self.entries.last(), &entry)?;
+        self.entries.pop();
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Pop up to `count` roots, always keeping the first one. Returns false if there was nothing
+    /// to pop, in which case the host may want to close the navigator itself.
+    pub fn pop(&mut self, host: &mut dyn NavigatorHost, count: usize) -> Result<bool> {
+        let count = count.min(self.entries.len().saturating_sub(1));
+        if count == 0 {
+            return Ok(false);
+        }
+
+        let from = self.entries.split_off(self.entries.len() - count);
+        host.transition(Transition::Pop, from.last(), &self.entries[self.entries.len() - 1])?;
+        Ok(true)
+    }
+
+    /// Dispatch an action on the top root. Navigation actions in a chain run after the rest of
+    /// the chain, in order, so they never replace the root which is dispatching.
+    pub fn dispatch(&mut self, host: &mut dyn NavigatorHost, action: &JsonValue) -> Result<()> {
+        let mut navigation = vec![];
+
+        if let Some(entry) = self.entries.last_mut() {
+            let mut handler = DeferNavigation { host: &mut *host, navigation: &mut navigation };
+            entry.root.dispatch(action, &mut handler)?;
+        }
+
+        for (name, value) in navigation {
+            match name.as_str() {
+                "push" => self.push(host, &value)?,
+                "replace" => self.replace(host, &value)?,
+                _ => {
+                    self.pop(host, value.as_usize().unwrap_or(1))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct DeferNavigation<'a> {
+    host: &'a mut dyn NavigatorHost,
+    navigation: &'a mut Vec<(String, JsonValue)>,
+}
+
+impl<'a> ActionHandler for DeferNavigation<'a> {
+    fn on_action(&mut self, action: &str, value: &JsonValue) -> Result<()> {
+        match action {
+            "push" | "pop" | "replace" => {
+                self.navigation.push((action.to_string(), value.clone()));
+                Ok(())
+            }
+            _ => self.host.on_action(action, value),
+        }
+    }
+}
+
+fn origin() -> Point<f32> {
+    Point { x: 0.0, y: 0.0 }
+}
@@ -0,0 +1,183 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use json::JsonValue;
+use stretch::result::Result;
+
+/// Apply a JSON Patch (RFC 6902), an array of `add`, `remove`, `replace`, `move`, `copy` and
+/// `test` operations, to a document. An operation with an `id` has its `path` and `from`
+/// resolved relative to the node with that id. Either every operation applies or, if one
+/// fails, the document is left as it was.
+pub(crate) fn apply(document: &mut JsonValue, patch: &JsonValue) -> Result<()> {
+    if !patch.is_array() {
+        return Err(Box::new(format!("expected an array of patch operations, got {}", patch.dump())));
+    }
+
+    let mut patched = document.clone();
+    for (i, operation) in patch.members().enumerate() {
+        if let Err(reason) = apply_operation(&mut patched, operation) {
+            return Err(Box::new(format!("patch operation {} failed: {}", i, reason)));
+        }
+    }
+
+    *document = patched;
+    Ok(())
+}
+
+fn apply_operation(document: &mut JsonValue, operation: &JsonValue) -> std::result::Result<(), String> {
+    let prefix = match operation["id"].as_str() {
+        Some(id) => match find_node(&document["root"], "/root", id) {
+            Some(pointer) => pointer,
+            None => return Err(format!("no node with id {}", id)),
+        },
+        None if operation.has_key("id") => return Err(format!("expected a string id, got {}", operation["id"].dump())),
+        None => String::new(),
+    };
+
+    let path = pointer(&prefix, operation, "path")?;
+    let value = || {
+        if operation.has_key("value") {
+            Ok(operation["value"].clone())
+        } else {
+            Err("missing value".to_string())
+        }
+    };
+
+    match operation["op"].as_str().unwrap_or_default() {
+        "add" => add(document, &path, value()?),
+        "remove" => remove(document, &path).map(|_| ()),
+        "replace" => match get_mut(document, &path) {
+            Some(target) => {
+                *target = value()?;
+                Ok(())
+            }
+            None => Err(format!("path {} does not exist", join(&path))),
+        },
+        "move" => {
+            let from = pointer(&prefix, operation, "from")?;
+            if path.len() > from.len() && path.starts_with(&from) {
+                return Err(format!("cannot move {} into itself", join(&from)));
+            }
+            let moved = remove(document, &from)?;
+            add(document, &path, moved)
+        }
+        "copy" => {
+            let from = pointer(&prefix, operation, "from")?;
+            match get(document, &from) {
+                Some(copied) => add(document, &path, copied.clone()),
+                None => Err(format!("path {} does not exist", join(&from))),
+            }
+        }
+        "test" => match get(document, &path) {
+            Some(current) if *current == value()? => Ok(()),
+            Some(current) => Err(format!("test failed, {} is {}", join(&path), current.dump())),
+            None => Err(format!("test failed, path {} does not exist", join(&path))),
+        },
+        op => Err(format!("unknown op {}", op)),
+    }
+}
+
+/// The JSON pointer to the first node with `id` in document order, searching through children.
+fn find_node(node: &JsonValue, pointer: &str, id: &str) -> Option<String> {
+    if node["id"] == id {
+        return Some(pointer.to_string());
+    }
+
+    node["children"]
+        .members()
+        .enumerate()
+        .find_map(|(i, child)| find_node(child, &format!("{}/children/{}", pointer, i), id))
+}
+
+/// Parse the pointer in `operation[key]`, relative to `prefix`, into unescaped reference tokens.
+fn pointer(prefix: &str, operation: &JsonValue, key: &str) -> std::result::Result<Vec<String>, String> {
+    let pointer = match operation[key].as_str() {
+        Some(pointer) => format!("{}{}", prefix, pointer),
+        None => return Err(format!("expected a string {}, got {}", key, operation[key].dump())),
+    };
+
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+
+    match pointer.strip_prefix('/') {
+        Some(tokens) => Ok(tokens.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect()),
+        None => Err(format!("invalid pointer {}", pointer)),
+    }
+}
+
+fn join(tokens: &[String]) -> String {
+    tokens.iter().map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+/// An array index without leading zeros, as required by RFC 6901.
+fn index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn get<'a>(value: &'a JsonValue, tokens: &[String]) -> Option<&'a JsonValue> {
+    tokens.iter().try_fold(value, |value, token| match value {
+        JsonValue::Object(_) if value.has_key(token) => Some(&value[token.as_str()]),
+        JsonValue::Array(array) => array.get(index(token)?),
+        _ => None,
+    })
+}
+
+fn get_mut<'a>(value: &'a mut JsonValue, tokens: &[String]) -> Option<&'a mut JsonValue> {
+    tokens.iter().try_fold(value, |value, token| match value {
+        JsonValue::Object(_) if value.has_key(token) => Some(&mut value[token.as_str()]),
+        JsonValue::Array(array) => array.get_mut(index(token)?),
+        _ => None,
+    })
+}
+
+fn add(document: &mut JsonValue, tokens: &[String], value: JsonValue) -> std::result::Result<(), String> {
+    let (last, parent) = match tokens.split_last() {
+        Some(split) => split,
+        None => {
+            *document = value;
+            return Ok(());
+        }
+    };
+
+    match get_mut(document, parent) {
+        Some(object @ JsonValue::Object(_)) => {
+            object[last.as_str()] = value;
+            Ok(())
+        }
+        Some(JsonValue::Array(array)) => {
+            let i = if last == "-" { Some(array.len()) } else { index(last) };
+            match i {
+                Some(i) if i <= array.len() => {
+                    array.insert(i, value);
+                    Ok(())
+                }
+                _ => Err(format!("index {} is out of bounds at {}", last, join(parent))),
+            }
+        }
+        Some(_) => Err(format!("{} is not an object or array", join(parent))),
+        None => Err(format!("path {} does not exist", join(parent))),
+    }
+}
+
+fn remove(document: &mut JsonValue, tokens: &[String]) -> std::result::Result<JsonValue, String> {
+    let (last, parent) = match tokens.split_last() {
+        Some(split) => split,
+        None => return Err("cannot remove the whole document".to_string()),
+    };
+
+    match get_mut(document, parent) {
+        Some(object @ JsonValue::Object(_)) if object.has_key(last) => Ok(object.remove(last)),
+        Some(JsonValue::Array(array)) => match index(last) {
+            Some(i) if i < array.len() => Ok(array.remove(i)),
+            _ => Err(format!("path {} does not exist", join(tokens))),
+        },
+        _ => Err(format!("path {} does not exist", join(tokens))),
+    }
+}
@@ -0,0 +1,235 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::ViewNode;
+use json::JsonValue;
+use std::iter::Peekable;
+use std::str::Chars;
+use stretch::result::Result;
+
+/// A subset of CSS selectors matched against rendered nodes: `kind`, `*`, `#id`, `.class`,
+/// `[prop]` and `[prop=value]`, combined with descendant (whitespace) and child (`>`) combinators.
+/// Prop values are JSON literals, or strings when they do not parse as one.
+#[derive(Debug)]
+pub(crate) struct Selector {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug)]
+enum Condition {
+    Kind(String),
+    Id(String),
+    Class(String),
+    Prop(String, Option<JsonValue>),
+}
+
+/// A compound selector and how it relates to the step before it.
+#[derive(Debug)]
+struct Step {
+    combinator: Combinator,
+    conditions: Vec<Condition>,
+}
+
+impl Selector {
+    pub(crate) fn parse(selector: &str) -> Result<Selector> {
+        match parse_steps(&mut selector.chars().peekable()) {
+            Ok(steps) => Ok(Selector { steps }),
+            Err(reason) => Err(Box::new(format!("invalid selector {}: {}", selector, reason))),
+        }
+    }
+
+    /// All nodes below and including `root` matching the selector, in document order.
+    pub(crate) fn query<'a>(&self, root: &'a ViewNode) -> Vec<&'a ViewNode> {
+        let mut matches = vec![];
+        self.collect(root, &mut vec![], &mut matches);
+        matches
+    }
+
+    fn collect<'a>(&self, node: &'a ViewNode, ancestors: &mut Vec<&'a ViewNode>, matches: &mut Vec<&'a ViewNode>) {
+        if self.matches(self.steps.len() - 1, node, ancestors) {
+            matches.push(node);
+        }
+
+        ancestors.push(node);
+        for child in &node.children {
+            self.collect(child, ancestors, matches);
+        }
+        ancestors.pop();
+    }
+
+    fn matches(&self, index: usize, node: &ViewNode, ancestors: &[&ViewNode]) -> bool {
+        let step = &self.steps[index];
+        if !step.conditions.iter().all(|condition| condition.matches(node)) {
+            return false;
+        }
+
+        if index == 0 {
+            return true;
+        }
+
+        match step.combinator {
+            Combinator::Child => match ancestors.split_last() {
+                Some((parent, rest)) => self.matches(index - 1, parent, rest),
+                None => false,
+            },
+            Combinator::Descendant => {
+                (0..ancestors.len()).rev().any(|i| self.matches(index - 1, ancestors[i], &ancestors[..i]))
+            }
+        }
+    }
+}
+
+impl Condition {
+    fn matches(&self, node: &ViewNode) -> bool {
+        match self {
+            Condition::Kind(kind) => node.kind() == kind,
+            Condition::Id(id) => node.id() == Some(id.as_str()),
+            Condition::Class(class) => node.classes().any(|c| c == class),
+            Condition::Prop(key, None) => node.props.has_key(key),
+            Condition::Prop(key, Some(value)) => node.props[key.as_str()] == *value,
+        }
+    }
+}
+
+fn parse_steps(chars: &mut Peekable<Chars>) -> std::result::Result<Vec<Step>, String> {
+    let mut steps = vec![];
+    let mut combinator = Combinator::Descendant;
+
+    loop {
+        skip_whitespace(chars);
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let conditions = parse_compound(chars)?;
+        steps.push(Step { combinator, conditions });
+
+        let whitespace = skip_whitespace(chars);
+        combinator = match chars.peek() {
+            Some('>') => {
+                chars.next();
+                Combinator::Child
+            }
+            Some(_) if whitespace => Combinator::Descendant,
+            Some(c) => return Err(format!("unexpected {}", c)),
+            None => break,
+        };
+
+        skip_whitespace(chars);
+        if combinator == Combinator::Child && chars.peek().is_none() {
+            return Err("expected a selector after >".to_string());
+        }
+    }
+
+    if steps.is_empty() {
+        return Err("empty selector".to_string());
+    }
+
+    Ok(steps)
+}
+
+fn parse_compound(chars: &mut Peekable<Chars>) -> std::result::Result<Vec<Condition>, String> {
+    let mut conditions = vec![];
+    let universal = chars.peek() == Some(&'*');
+
+    if universal {
+        chars.next();
+    } else {
+        let kind = parse_ident(chars);
+        if !kind.is_empty() {
+            conditions.push(Condition::Kind(kind));
+        }
+    }
+
+    while let Some(c) = chars.peek().copied() {
+        match c {
+            '#' | '.' => {
+                chars.next();
+                let name = parse_ident(chars);
+                if name.is_empty() {
+                    return Err(format!("expected a name after {}", c));
+                }
+                conditions.push(if c == '#' { Condition::Id(name) } else { Condition::Class(name) });
+            }
+            '[' => {
+                chars.next();
+                conditions.push(parse_prop(chars)?);
+            }
+            _ => break,
+        }
+    }
+
+    if conditions.is_empty() && !universal {
+        return Err(match chars.peek() {
+            Some(c) => format!("unexpected {}", c),
+            None => "expected a selector".to_string(),
+        });
+    }
+
+    Ok(conditions)
+}
+
+fn parse_prop(chars: &mut Peekable<Chars>) -> std::result::Result<Condition, String> {
+    skip_whitespace(chars);
+    let key = parse_ident(chars);
+    if key.is_empty() {
+        return Err("expected a prop name after [".to_string());
+    }
+    skip_whitespace(chars);
+
+    match chars.next() {
+        Some(']') => return Ok(Condition::Prop(key, None)),
+        Some('=') => (),
+        _ => return Err(format!("expected = or ] after {}", key)),
+    }
+
+    skip_whitespace(chars);
+    let mut raw = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+
+    loop {
+        match chars.next() {
+            Some(']') if !quoted => break,
+            Some(c) => {
+                if c == '"' && !escaped {
+                    quoted = !quoted;
+                }
+                escaped = c == '\\' && !escaped;
+                raw.push(c);
+            }
+            None => return Err(format!("unclosed [{}", key)),
+        }
+    }
+
+    let raw = raw.trim();
+    let value = json::parse(raw).unwrap_or_else(|_| JsonValue::from(raw));
+    Ok(Condition::Prop(key, Some(value)))
+}
+
+fn parse_ident(chars: &mut Peekable<Chars>) -> String {
+    let mut ident = String::new();
+    while let Some(c) = chars.peek().copied().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_') {
+        ident.push(c);
+        chars.next();
+    }
+    ident
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) -> bool {
+    let mut skipped = false;
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+        skipped = true;
+    }
+    skipped
+}
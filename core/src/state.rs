@@ -0,0 +1,89 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use json::JsonValue;
+
+/// The state a root starts with, the document's `state` with the keys of `initial` on top.
+pub(crate) fn initial(document: &JsonValue, initial: &JsonValue) -> JsonValue {
+    let mut state = if document.is_object() { document.clone() } else { JsonValue::new_object() };
+    for (key, value) in initial.entries() {
+        state[key] = value.clone();
+    }
+    state
+}
+
+/// The value at a dot separated path such as `cart.items.0.quantity`.
+pub(crate) fn get<'a>(state: &'a JsonValue, path: &str) -> &'a JsonValue {
+    path.split('.').fold(state, |value, segment| match segment.parse::<usize>() {
+        Ok(index) if value.is_array() => &value[index],
+        _ => &value[segment],
+    })
+}
+
+/// Set the value at a dot separated path, creating objects along the way.
+pub(crate) fn set(state: &mut JsonValue, path: &str, value: JsonValue) {
+    let target = path.split('.').fold(state, |value, segment| {
+        if !value.is_object() && !value.is_array() {
+            *value = JsonValue::new_object();
+        }
+        match segment.parse::<usize>() {
+            Ok(index) if value.is_array() => &mut value[index],
+            _ => &mut value[segment],
+        }
+    });
+    *target = value;
+}
+
+/// Resolve a `{"$state": path}` reference. With `equals` it resolves to whether the value
+/// equals the given one and with `"not": true` to the negation of that.
+pub(crate) fn resolve(state: &JsonValue, reference: &JsonValue) -> JsonValue {
+    let mut value = get(state, reference["$state"].as_str().unwrap_or_default()).clone();
+
+    if reference.has_key("equals") {
+        value = (value == reference["equals"]).into();
+    }
+
+    if reference["not"] == true {
+        value = (!is_truthy(&value)).into();
+    }
+
+    value
+}
+
+/// `null`, `false`, `0`, `""` and empty arrays and objects are false, everything else is true.
+pub(crate) fn is_truthy(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Null => false,
+        JsonValue::Boolean(value) => *value,
+        JsonValue::Number(_) => value.as_f64().is_some_and(|value| value != 0.0),
+        JsonValue::Short(_) | JsonValue::String(_) => !value.as_str().unwrap_or_default().is_empty(),
+        JsonValue::Array(array) => !array.is_empty(),
+        JsonValue::Object(_) => !value.is_empty(),
+    }
+}
+
+/// Apply a `set-state`, `toggle-state` or `increment-state` action with a validated value.
+pub(crate) fn apply(state: &mut JsonValue, action: &str, value: &JsonValue) {
+    match action {
+        "set-state" => {
+            for (path, value) in value.entries() {
+                set(state, path, value.clone());
+            }
+        }
+        "toggle-state" => {
+            let path = value.as_str().unwrap_or_default();
+            let toggled = !is_truthy(get(state, path));
+            set(state, path, toggled.into());
+        }
+        "increment-state" => {
+            for (path, by) in value.entries() {
+                let current = get(state, path).as_f64().unwrap_or(0.0);
+                set(state, path, (current + by.as_f64().unwrap_or(0.0)).into());
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,176 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use json::JsonValue;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Select the CLDR plural category for `n` in the given language. Only the integer
+/// and visible fraction operands are taken into account which covers the counts
+/// documents pass in practice.
+pub fn plural_category(language: &str, n: f64) -> PluralCategory {
+    let n = n.abs();
+    let i = n.trunc() as u64;
+    let integer = n.fract() == 0.0;
+
+    match language {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "tr" => PluralCategory::Other,
+
+        "fr" | "pt" if i == 0 || i == 1 => PluralCategory::One,
+        "fr" | "pt" => PluralCategory::Other,
+
+        "ru" | "uk" | "be" if integer => match (i % 10, i % 100) {
+            (1, m) if m != 11 => PluralCategory::One,
+            (2..=4, m) if !(12..=14).contains(&m) => PluralCategory::Few,
+            _ => PluralCategory::Many,
+        },
+        "ru" | "uk" | "be" => PluralCategory::Other,
+
+        "hr" | "sr" | "bs" if integer => match (i % 10, i % 100) {
+            (1, m) if m != 11 => PluralCategory::One,
+            (2..=4, m) if !(12..=14).contains(&m) => PluralCategory::Few,
+            _ => PluralCategory::Other,
+        },
+        "hr" | "sr" | "bs" => PluralCategory::Other,
+
+        "pl" if integer => match (i % 10, i % 100) {
+            _ if i == 1 => PluralCategory::One,
+            (2..=4, m) if !(12..=14).contains(&m) => PluralCategory::Few,
+            _ => PluralCategory::Many,
+        },
+        "pl" => PluralCategory::Other,
+
+        "cs" | "sk" if integer => match i {
+            1 => PluralCategory::One,
+            2..=4 => PluralCategory::Few,
+            _ => PluralCategory::Other,
+        },
+        "cs" | "sk" => PluralCategory::Many,
+
+        "ar" if integer => match (i, i % 100) {
+            (0, _) => PluralCategory::Zero,
+            (1, _) => PluralCategory::One,
+            (2, _) => PluralCategory::Two,
+            (_, 3..=10) => PluralCategory::Few,
+            (_, 11..=99) => PluralCategory::Many,
+            _ => PluralCategory::Other,
+        },
+        "ar" => PluralCategory::Other,
+
+        _ if i == 1 && integer => PluralCategory::One,
+        _ => PluralCategory::Other,
+    }
+}
+
+/// Locale keyed string tables of a document, e.g.
+/// `{"en": {"cart.items": {"one": "{count} item", "other": "{count} items"}}}`.
+pub struct Strings {
+    tables: JsonValue,
+}
+
+impl Strings {
+    pub fn new(tables: JsonValue) -> Strings {
+        Strings { tables }
+    }
+
+    /// Resolve a `{"$t": key, ...}` reference for `locale`. Every other key of the reference
+    /// is an argument which can be interpolated as `{name}`, `count` also selects the plural form.
+    pub fn translate(&self, locale: &str, reference: &JsonValue) -> Option<String> {
+        let key = reference["$t"].as_str()?;
+
+        for locale in fallback_locales(locale) {
+            let entry = &self.tables[locale.as_str()][key];
+
+            let template = match entry {
+                JsonValue::Object(_) => {
+                    let category = match reference["count"].as_f64() {
+                        Some(count) => plural_category(language(&locale), count),
+                        None => PluralCategory::Other,
+                    };
+
+                    entry[category.as_str()].as_str().or_else(|| entry["other"].as_str())
+                }
+                _ => entry.as_str(),
+            };
+
+            if let Some(template) = template {
+                return Some(interpolate(template, reference));
+            }
+        }
+
+        None
+    }
+}
+
+fn language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+fn fallback_locales(locale: &str) -> Vec<String> {
+    let locale = locale.replace('_', "-");
+    let language = language(&locale).to_string();
+
+    if language == locale {
+        vec![locale]
+    } else {
+        vec![locale, language]
+    }
+}
+
+fn interpolate(template: &str, args: &JsonValue) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+
+        match &args[&rest[1..end]] {
+            JsonValue::Null => result.push_str(&rest[..=end]),
+            value @ JsonValue::Number(_) => result.push_str(&format_number(value.as_f64().unwrap_or(0.0))),
+            value => result.push_str(&value.as_str().map(str::to_string).unwrap_or_else(|| value.dump())),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
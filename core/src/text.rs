@@ -0,0 +1,247 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::action;
+use crate::color::Color;
+use crate::markdown;
+use json::object;
+use json::JsonValue;
+use stretch::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontWeight {
+    Regular,
+    Bold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextDecoration {
+    None,
+    Underline,
+    LineThrough,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontSize {
+    Points(f32),
+    Pixels(f32),
+}
+
+/// A run of text sharing the same attributes. Runs are produced by flattening a `span`
+/// tree where every span inherits the attributes of its parent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub font_family: Option<String>,
+    pub font_weight: FontWeight,
+    pub font_style: FontStyle,
+    pub font_size: FontSize,
+    pub color: Color,
+    pub decoration: TextDecoration,
+    /// A multiple of the natural line height, applied to the lines the run is on. The extra
+    /// space goes above the text on both platforms.
+    pub line_height: Option<f32>,
+    pub action: Option<JsonValue>,
+}
+
+impl Default for TextRun {
+    fn default() -> TextRun {
+        TextRun {
+            text: String::new(),
+            font_family: None,
+            font_weight: FontWeight::Regular,
+            font_style: FontStyle::Normal,
+            font_size: FontSize::Points(12.0),
+            color: Color { r: 0, g: 0, b: 0, a: 255 },
+            decoration: TextDecoration::None,
+            line_height: None,
+            action: None,
+        }
+    }
+}
+
+impl TextRun {
+    pub fn to_json(&self) -> JsonValue {
+        let (unit, size) = match self.font_size {
+            FontSize::Points(size) => ("points", size),
+            FontSize::Pixels(size) => ("pixels", size),
+        };
+
+        object! {
+            "text" => self.text.as_str(),
+            "font-family" => self.font_family.clone(),
+            "font-weight" => match self.font_weight {
+                FontWeight::Regular => "regular",
+                FontWeight::Bold => "bold",
+            },
+            "font-style" => match self.font_style {
+                FontStyle::Normal => "normal",
+                FontStyle::Italic => "italic",
+            },
+            "font-size" => object! { "unit" => unit, "value" => size },
+            "font-color" => self.color.to_argb_hex(),
+            "text-decoration" => match self.decoration {
+                TextDecoration::None => "none",
+                TextDecoration::Underline => "underline",
+                TextDecoration::LineThrough => "line-through",
+            },
+            "line-height" => self.line_height,
+            "action" => self.action.clone()
+        }
+    }
+}
+
+pub fn parse_markdown(source: &str) -> Vec<TextRun> {
+    markdown::parse(source, &TextRun::default())
+}
+
+pub fn parse_span(span: &JsonValue) -> Result<Vec<TextRun>> {
+    let mut runs = vec![];
+    parse_span_into(span, &TextRun::default(), &mut runs)?;
+    Ok(runs)
+}
+
+fn parse_span_into(span: &JsonValue, parent: &TextRun, runs: &mut Vec<TextRun>) -> Result<()> {
+    let mut run = TextRun { text: String::new(), ..parent.clone() };
+    apply_attributes(span, &mut run)?;
+
+    match &span["text"] {
+        JsonValue::Array(children) => {
+            for child in children {
+                parse_span_into(child, &run, runs)?;
+            }
+        }
+        value => {
+            run.text = expect_str(value, "text")?.to_string();
+            runs.push(run);
+        }
+    }
+
+    Ok(())
+}
+
+/// Override the attributes of `run` with the ones set on `span`.
+fn apply_attributes(span: &JsonValue, run: &mut TextRun) -> Result<()> {
+    match &span["font-family"] {
+        JsonValue::Null => (),
+        value => run.font_family = Some(expect_str(value, "font-family")?.to_string()),
+    }
+
+    match &span["font-weight"] {
+        JsonValue::Null => (),
+        value => {
+            run.font_weight = match expect_str(value, "font-weight")? {
+                "regular" => FontWeight::Regular,
+                "bold" => FontWeight::Bold,
+                _ => return Err(unexpected("font-weight", value)),
+            }
+        }
+    }
+
+    match &span["font-style"] {
+        JsonValue::Null => (),
+        value => {
+            run.font_style = match expect_str(value, "font-style")? {
+                "normal" => FontStyle::Normal,
+                "italic" => FontStyle::Italic,
+                _ => return Err(unexpected("font-style", value)),
+            }
+        }
+    }
+
+    match &span["font-size"] {
+        JsonValue::Null => (),
+        value => {
+            run.font_size = match (value["unit"].as_str(), value["value"].as_f32()) {
+                (Some("points"), Some(size)) => FontSize::Points(size),
+                (Some("pixels"), Some(size)) => FontSize::Pixels(size),
+                _ => return Err(unexpected("font-size", value)),
+            }
+        }
+    }
+
+    match &span["font-color"] {
+        JsonValue::Null => (),
+        value if value.is_object() => run.color = Color::parse(expect_str(&value["default"], "font-color")?)?,
+        value => run.color = Color::parse(expect_str(value, "font-color")?)?,
+    }
+
+    match &span["text-decoration"] {
+        JsonValue::Null => (),
+        value => {
+            run.decoration = match expect_str(value, "text-decoration")? {
+                "none" => TextDecoration::None,
+                "underline" => TextDecoration::Underline,
+                "line-through" => TextDecoration::LineThrough,
+                _ => return Err(unexpected("text-decoration", value)),
+            }
+        }
+    }
+
+    match &span["line-height"] {
+        JsonValue::Null => (),
+        value => match value.as_f32().or_else(|| value["value"].as_f32()) {
+            Some(line_height) => run.line_height = Some(line_height),
+            None => return Err(unexpected("line-height", value)),
+        },
+    }
+
+    match &span["on-click"] {
+        JsonValue::Null => (),
+        value if action::validate(value, "").is_ok() => run.action = Some(value.clone()),
+        value => return Err(unexpected("on-click", value)),
+    }
+
+    Ok(())
+}
+
+/// Parse a `markdown` prop, either the source itself or a span whose `text` is the source and
+/// whose attributes are the base style of the document.
+fn parse_markdown_prop(value: &JsonValue) -> Result<Vec<TextRun>> {
+    let mut base = TextRun::default();
+
+    let source = match value {
+        JsonValue::Object(_) => {
+            apply_attributes(value, &mut base)?;
+            expect_str(&value["text"], "markdown")?
+        }
+        value => expect_str(value, "markdown")?,
+    };
+
+    Ok(markdown::parse(source, &base))
+}
+
+/// Replace the `span` or `markdown` prop of a text view with the runs it compiles to.
+pub(crate) fn compile_props(kind: &str, props: &mut JsonValue) -> Result<()> {
+    if kind != "text" {
+        return Ok(());
+    }
+
+    let runs = if props.has_key("span") {
+        parse_span(&props.remove("span"))?
+    } else if props.has_key("markdown") {
+        parse_markdown_prop(&props.remove("markdown"))?
+    } else {
+        return Ok(());
+    };
+
+    props["runs"] = JsonValue::Array(runs.iter().map(TextRun::to_json).collect());
+    Ok(())
+}
+
+fn expect_str<'a>(value: &'a JsonValue, key: &str) -> Result<&'a str> {
+    value.as_str().ok_or_else(|| unexpected(key, value))
+}
+
+fn unexpected(key: &str, value: &JsonValue) -> Box<dyn std::any::Any> {
+    Box::new(format!("unexpected value for {}: {}", key, value.dump()))
+}
@@ -0,0 +1,49 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use json::JsonValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ThemeMode {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThemeMode::Light => "light",
+            ThemeMode::Dark => "dark",
+        }
+    }
+}
+
+/// Named tokens of a document, e.g. `{"primary": {"light": "#000", "dark": "#fff"}}`.
+/// A token without per mode values resolves to the same value in every mode.
+pub struct Theme {
+    tokens: JsonValue,
+}
+
+impl Theme {
+    pub fn new(tokens: JsonValue) -> Theme {
+        Theme { tokens }
+    }
+
+    /// Resolve a `{"$theme": token}` reference for `mode`.
+    pub fn resolve(&self, mode: ThemeMode, reference: &JsonValue) -> Option<JsonValue> {
+        let token = &self.tokens[reference["$theme"].as_str()?];
+
+        match token {
+            JsonValue::Null => None,
+            JsonValue::Object(_) if token.has_key("light") || token.has_key("dark") => match &token[mode.as_str()] {
+                JsonValue::Null => Some(token["light"].clone()),
+                value => Some(value.clone()),
+            },
+            value => Some(value.clone()),
+        }
+    }
+}
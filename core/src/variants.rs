@@ -0,0 +1,67 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::RenderContext;
+use json::JsonValue;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Platform {
+    Android,
+    IOS,
+}
+
+impl Platform {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Platform::Android => "android",
+            Platform::IOS => "ios",
+        }
+    }
+}
+
+/// Merge the `layout` or `props` section of a node with those of its platform override
+/// and matching media variants. Later variants take precedence over earlier ones.
+pub(crate) fn merged(json: &JsonValue, section: &str, render_context: &RenderContext) -> JsonValue {
+    let mut result = json[section].clone();
+
+    if let Some(platform) = render_context.platform {
+        for (key, value) in json[platform.as_str()][section].entries() {
+            result[key] = value.clone();
+        }
+    }
+
+    for variant in json["@media"].members().filter(|variant| matches_media(variant, render_context.width)) {
+        for (key, value) in variant[section].entries() {
+            result[key] = value.clone();
+        }
+    }
+
+    result
+}
+
+fn matches_media(variant: &JsonValue, width: Option<f32>) -> bool {
+    let min_width = variant["min-width"].as_f32();
+    let max_width = variant["max-width"].as_f32();
+
+    match width {
+        Some(width) => min_width.map_or(true, |min| width >= min) && max_width.map_or(true, |max| width <= max),
+        None => min_width.is_none() && max_width.is_none(),
+    }
+}
+
+/// Whether any variant of a node sets something in the `layout` or `props` section.
+pub(crate) fn has_section(json: &JsonValue, section: &str) -> bool {
+    sections(json, section).any(|value| !value.is_null() && !value.is_empty())
+}
+
+/// The `layout` or `props` section of a node and of every one of its variants, whether or
+/// not they currently apply.
+pub(crate) fn sections<'a>(json: &'a JsonValue, section: &'a str) -> impl Iterator<Item = &'a JsonValue> {
+    let platforms = [Platform::Android, Platform::IOS].iter().map(move |platform| &json[platform.as_str()][section]);
+    std::iter::once(&json[section])
+        .chain(platforms)
+        .chain(json["@media"].members().map(move |variant| &variant[section]))
+}
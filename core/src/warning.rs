@@ -0,0 +1,33 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use std::any::Any;
+use std::fmt;
+
+/// A problem in a document which did not stop it from rendering. `path` is a JSON pointer
+/// to the offending value, e.g. `/root/children/2/props`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Describe an error returned by a view or the layout engine.
+pub(crate) fn describe(error: &dyn Any) -> String {
+    if let Some(message) = error.downcast_ref::<String>() {
+        message.clone()
+    } else if let Some(message) = error.downcast_ref::<&str>() {
+        message.to_string()
+    } else {
+        "unknown error".to_string()
+    }
+}
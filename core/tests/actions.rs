@@ -0,0 +1,112 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use json::JsonValue;
+use std::any::Any;
+use stretch::result::Result;
+
+#[derive(Default)]
+struct Handler {
+    actions: Vec<(String, JsonValue)>,
+}
+
+impl core::ActionHandler for Handler {
+    fn on_action(&mut self, action: &str, value: &JsonValue) -> Result<()> {
+        if action == "fail" {
+            return Err(Box::new("failed"));
+        }
+
+        self.actions.push((action.to_string(), value.clone()));
+        Ok(())
+    }
+}
+
+fn render(json: &str) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    core::render_root(&test::ViewManager {}, &context, json).unwrap()
+}
+
+fn view(view_node: &core::ViewNode) -> &test::View {
+    view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap()
+}
+
+#[test]
+fn dispatch_chained_actions() {
+    let mut root = render(
+        r##"{
+        "theme": {"background": {"light": "#fff", "dark": "#000"}},
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "props": {"background-color": {"$theme": "background"}},
+            "children": [{"kind": "test", "id": "count", "layout": {}, "props": {"label": "0"}}]
+        }
+    }"##,
+    );
+
+    let action = json::parse(
+        r#"[
+        {"action": "set-prop", "value": {"id": "count", "key": "label", "value": "1"}},
+        {"action": "set-theme", "value": "dark"},
+        [{"action": "open-url", "value": "https://example.com"}, {"action": "track", "value": {"event": "tap"}}]
+    ]"#,
+    )
+    .unwrap();
+
+    let mut handler = Handler::default();
+    root.dispatch(&action, &mut handler).unwrap();
+
+    assert_eq!(view(root.view_by_id("count").unwrap()).props["label"], "\"1\"");
+    assert_eq!(view(&root.view_node).props["background-color"], "\"#ff000000\"");
+    assert_eq!(handler.actions.len(), 2);
+    assert_eq!(handler.actions[0].0, "open-url");
+    assert_eq!(handler.actions[0].1, "https://example.com");
+    assert_eq!(handler.actions[1].0, "track");
+}
+
+#[test]
+fn stop_chain_on_failure() {
+    let mut root = render(r#"{"root": {"kind": "test", "layout": {}}}"#);
+    let mut handler = Handler::default();
+
+    let action = json::parse(r#"[{"action": "fail"}, {"action": "track"}]"#).unwrap();
+    assert!(root.dispatch(&action, &mut handler).is_err());
+    assert!(handler.actions.is_empty());
+
+    let action = json::parse(r#"[{"action": "track"}, {"action": "open-url", "value": 1}]"#).unwrap();
+    assert!(root.dispatch(&action, &mut handler).is_err());
+    assert!(handler.actions.is_empty());
+}
+
+#[test]
+fn validate_actions_during_render() {
+    let root = render(
+        r#"{
+        "root": {
+            "kind": "flexbox",
+            "layout": {},
+            "props": {"on-click": {"action": "open-url", "value": "https://example.com"}},
+            "children": [
+                {"kind": "flexbox", "layout": {}, "props": {"on-click": [{"action": "track"}, {"value": 1}]}},
+                {"kind": "flexbox", "layout": {}, "props": {"on-click": {"action": "set-theme", "value": "sepia"}}}
+            ]
+        }
+    }"#,
+    );
+
+    assert!(view(&root.view_node).props.contains_key("on-click"));
+    assert!(root.view_node.children.iter().all(|child| !view(child).props.contains_key("on-click")));
+
+    let warnings: Vec<String> = root.warnings.iter().map(|warning| warning.to_string()).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "/root/children/0/props/on-click/1: expected an action or an array of actions, ignoring on-click",
+            "/root/children/1/props/on-click/value: invalid value \"sepia\" for action set-theme, ignoring on-click",
+        ]
+    );
+}
@@ -0,0 +1,85 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+
+fn render(json: &str) -> stretch::result::Result<core::Root> {
+    let context: Option<&dyn Any> = None;
+    core::render_root(&test::ViewManager {}, &context, json)
+}
+
+#[test]
+fn contain_failure_in_error_boundary() {
+    let root = render(
+        r#"{
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "children": [
+                {
+                    "kind": "test",
+                    "layout": {},
+                    "error-boundary": true,
+                    "fallback": {"kind": "test", "layout": {}, "props": {"title": "fallback"}},
+                    "children": [
+                        {"kind": "test", "layout": {}},
+                        {"kind": "test", "layout": {}, "props": {"invalid": "image"}}
+                    ]
+                },
+                {"kind": "test", "layout": {}, "props": {"title": "sibling"}}
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+
+    let children = &root.view_node.children;
+    assert_eq!(children.len(), 2);
+    assert!(children[0].children.is_empty());
+
+    let fallback = children[0].shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(fallback.props["title"], "\"fallback\"");
+    let sibling = children[1].shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(sibling.props["title"], "\"sibling\"");
+
+    assert_eq!(root.warnings.len(), 1);
+    assert_eq!(root.warnings[0].path, "/root/children/0");
+    assert!(root.warnings[0].message.contains("invalid value \"image\""));
+}
+
+#[test]
+fn leave_error_boundary_without_fallback_empty() {
+    let root = render(
+        r#"{
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "children": [
+                {"kind": "test", "layout": {}, "error-boundary": true, "props": {"invalid": 1}}
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+
+    assert!(root.view_node.children[0].is_layout_only());
+    assert_eq!(root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap().child_count, 0);
+}
+
+#[test]
+fn fail_without_error_boundary() {
+    let result = render(
+        r#"{
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "children": [{"kind": "test", "layout": {}, "props": {"invalid": 1}}]
+        }
+    }"#,
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,98 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use core::Color;
+use std::any::Any;
+
+fn rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
+    Color { r, g, b, a }
+}
+
+#[test]
+fn parse_hex_colors() {
+    assert_eq!(Color::parse("#f00").unwrap(), rgba(255, 0, 0, 255));
+    assert_eq!(Color::parse("#f008").unwrap(), rgba(255, 0, 0, 136));
+    assert_eq!(Color::parse("#FF8000").unwrap(), rgba(255, 128, 0, 255));
+    assert_eq!(Color::parse("#ff800080").unwrap(), rgba(255, 128, 0, 128));
+    assert!(Color::parse("#ff80").is_ok());
+    assert!(Color::parse("#ff800").is_err());
+    assert!(Color::parse("#gg0000").is_err());
+}
+
+#[test]
+fn parse_functional_colors() {
+    assert_eq!(Color::parse("rgb(255, 0, 0)").unwrap(), rgba(255, 0, 0, 255));
+    assert_eq!(Color::parse("rgba(0, 0, 255, 0.5)").unwrap(), rgba(0, 0, 255, 128));
+    assert_eq!(Color::parse("rgb(100% 0% 0% / 50%)").unwrap(), rgba(255, 0, 0, 128));
+    assert_eq!(Color::parse("hsl(120, 100%, 50%)").unwrap(), rgba(0, 255, 0, 255));
+    assert_eq!(Color::parse("hsla(240deg, 100%, 50%, 0)").unwrap(), rgba(0, 0, 255, 0));
+    assert!(Color::parse("rgb(1, 2)").is_err());
+}
+
+#[test]
+fn parse_named_colors() {
+    assert_eq!(Color::parse("rebeccapurple").unwrap(), rgba(102, 51, 153, 255));
+    assert_eq!(Color::parse("Transparent").unwrap(), rgba(0, 0, 0, 0));
+    assert!(Color::parse("not-a-color").is_err());
+}
+
+#[test]
+fn render_normalized_color_props() {
+    let context: Option<&dyn Any> = None;
+
+    let root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r##"{
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "props": {
+                "background-color": {"default": "red", "pressed": "rgba(255, 0, 0, 0.5)"},
+                "border-color": "#00f8"
+            }
+        }
+    }"##,
+    )
+    .unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["background-color"], r##"{"default":"#ffff0000","pressed":"#80ff0000"}"##);
+    assert_eq!(view.props["border-color"], r##""#880000ff""##);
+}
+
+#[test]
+fn drop_invalid_colors_with_a_warning() {
+    let context: Option<&dyn Any> = None;
+
+    let root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r##"{
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "props": {"background-color": "not-a-color", "border-color": "#ffff0000", "label": "hi"}
+        }
+    }"##,
+    )
+    .unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert!(!view.props.contains_key("background-color"));
+    assert_eq!(view.props["border-color"], r##""#00ffff00""##);
+    assert_eq!(view.props["label"], "\"hi\"");
+
+    let warnings: Vec<String> = root.warnings.iter().map(|warning| warning.to_string()).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "/root/props/background-color: invalid color: not-a-color, dropping background-color",
+            "/root/props/border-color: #ffff0000 is read as #rrggbbaa, not #aarrggbb, write it as rgba() to be explicit",
+        ]
+    );
+}
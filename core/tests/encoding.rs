@@ -0,0 +1,85 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use core::Encoding;
+use json::{array, object};
+use std::any::Any;
+
+const DOCUMENT: &str = r#"{
+    "strings": {"en": {"title": "Spår"}},
+    "root": {
+        "kind": "test",
+        "layout": {"flex-direction": "column", "padding": {"unit": "points", "value": 12.5}},
+        "children": [
+            {"kind": "test", "id": "title", "layout": {}, "props": {"label": {"$t": "title"}, "count": -300, "big": 18446744073709551615}},
+            {"kind": "test", "layout": {"flex-grow": 1}, "props": {"enabled": true, "ratio": 0.25, "empty": null, "tags": ["a", "b"]}}
+        ]
+    }
+}"#;
+
+fn view(root: &core::Root, i: usize) -> &test::View {
+    root.view_node.children[i].shard_view.as_any().downcast_ref::<test::View>().unwrap()
+}
+
+#[test]
+fn render_binary_documents() {
+    let json = json::parse(DOCUMENT).unwrap();
+    let context: Option<&dyn Any> = None;
+    let render = |bytes: &[u8], content_type| {
+        core::render_root_from_bytes(&test::ViewManager {}, &context, &Default::default(), bytes, content_type).unwrap()
+    };
+
+    let expected = render(DOCUMENT.as_bytes(), None);
+    assert_eq!(view(&expected, 0).props["label"], "\"Spår\"");
+    for (encoding, content_type) in
+        &[(Encoding::Cbor, "application/cbor"), (Encoding::MessagePack, "application/msgpack")]
+    {
+        let bytes = encoding.encode(&json);
+        assert_eq!(Encoding::detect(&bytes), Some(*encoding));
+        assert_eq!(Encoding::from_content_type(content_type), Some(*encoding));
+        assert_eq!(encoding.decode(&bytes).unwrap(), json);
+
+        for content_type in &[None, Some(*content_type)] {
+            let root = render(&bytes, *content_type);
+            assert_eq!(view(&root, 0).props, view(&expected, 0).props);
+            assert_eq!(view(&root, 1).props, view(&expected, 1).props);
+        }
+    }
+}
+
+#[test]
+fn decode_reference_encodings() {
+    // A self-described indefinite length array holding 1.0 as a half float, [2, 3] and a
+    // chunked string, then a large negative integer
+    let cbor = b"\xd9\xd9\xf7\x9f\xf9\x3c\x00\x82\x02\x03\x7f\x62ab\x61c\xff\x3b\xff\xff\xff\xff\xff\xff\xff\xff\xff";
+    assert_eq!(Encoding::Cbor.decode(cbor).unwrap(), array![1.0, array![2, 3], "abc", -18446744073709551616.0]);
+
+    let message_pack = b"\x83\xa1a\x01\xa1b\xd0\x80\xa1c\x92\xcd\x01\x00\xcb\x3f\xd0\x00\x00\x00\x00\x00\x00";
+    assert_eq!(
+        Encoding::MessagePack.decode(message_pack).unwrap(),
+        object! { "a" => 1, "b" => -128, "c" => array![256, 0.25] }
+    );
+}
+
+#[test]
+fn reject_malformed_documents() {
+    let error = Encoding::Cbor.decode(b"\xa1\x61a\x82\x01").err().unwrap();
+    assert_eq!(
+        error.downcast_ref::<String>().unwrap(),
+        "invalid Cbor document: length 2 exceeds the input at offset 4"
+    );
+
+    assert!(Encoding::Cbor.decode(b"\xa1\x61a\x41\x00").is_err());
+    assert!(Encoding::Cbor.decode(b"\xa0\x00").is_err());
+    assert!(Encoding::Cbor.decode(b"\x9b\xff\xff\xff\xff\xff\xff\xff\xff").is_err());
+    assert!(Encoding::MessagePack.decode(b"\x81\x01\x02").is_err());
+    assert!(Encoding::MessagePack.decode(b"\x81\xa1a\xc4\x01\x00").is_err());
+
+    let context: Option<&dyn Any> = None;
+    let result = core::render_root_from_bytes(&test::ViewManager {}, &context, &Default::default(), b"\x00", None);
+    assert!(result.is_err());
+}
@@ -0,0 +1,69 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use json::object;
+use std::any::Any;
+use stretch::geometry::Size;
+
+const DOCUMENT: &str = r#"{
+    "root": {
+        "kind": "test",
+        "layout": {},
+        "children": [
+            {
+                "kind": "unknown-carousel",
+                "layout": {"height": {"unit": "points", "value": 40}},
+                "fallback": {"kind": "test", "layout": {}, "props": {"title": "fallback"}}
+            },
+            {"kind": "unknown-map", "layout": {"height": {"unit": "points", "value": 20}}}
+        ]
+    }
+}"#;
+
+#[test]
+fn render_fallback_when_view_creation_fails() {
+    let context: Option<&dyn Any> = None;
+    let mut root = core::render_root(&test::ViewManager {}, &context, DOCUMENT).unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    let fallback = root.view_node.children[0].shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(fallback.props["title"], "\"fallback\"");
+
+    assert!(root.view_node.children[1].is_layout_only());
+    assert_eq!(root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap().child_count, 1);
+
+    assert_eq!(root.warnings.len(), 2);
+    assert_eq!(root.warnings[0].path, "/root/children/0");
+    assert!(root.warnings[0].message.contains("unknown-carousel"));
+    assert_eq!(root.warnings[1].path, "/root/children/1");
+}
+
+#[test]
+fn render_placeholder_with_layout_of_failed_node() {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext {
+        placeholder: Some(
+            object! { "kind" => "test", "layout" => object! {}, "props" => object! { "placeholder" => true } },
+        ),
+        ..Default::default()
+    };
+
+    let mut root = core::render_root_with_context(&test::ViewManager {}, &context, &render_context, DOCUMENT).unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    let placeholder = root.view_node.children[1].shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(placeholder.props["placeholder"], "true");
+    assert_eq!(placeholder.frame.bottom - placeholder.frame.top, 20.0);
+}
+
+#[test]
+fn fail_when_root_view_cannot_be_created() {
+    let context: Option<&dyn Any> = None;
+    assert!(
+        core::render_root(&test::ViewManager {}, &context, r#"{"root": {"kind": "unknown", "layout": {}}}"#).is_err()
+    );
+}
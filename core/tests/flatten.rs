@@ -0,0 +1,111 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Rect;
+use stretch::geometry::Size;
+
+#[test]
+fn flatten_layout_only_nodes() {
+    let context: Option<&dyn Any> = None;
+
+    let mut root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r#"{
+        "root": {
+            "kind": "flexbox",
+            "layout": {},
+            "children": [
+                {
+                    "kind": "flexbox",
+                    "layout": {
+                        "flex-direction": "column",
+                        "padding-start": {"unit": "points", "value": 10},
+                        "padding-top": {"unit": "points", "value": 20}
+                    },
+                    "children": [
+                        {"kind": "test", "layout": {"height": {"unit": "points", "value": 30}}},
+                        {"kind": "test", "layout": {"height": {"unit": "points", "value": 30}}}
+                    ]
+                }
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    assert!(!root.view_node.is_layout_only());
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.child_count, 2);
+
+    let container = &root.view_node.children[0];
+    assert!(container.is_layout_only());
+    assert!(container.shard_view.as_any().downcast_ref::<core::LayoutView>().is_some());
+
+    let first = container.children[0].shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    let second = container.children[1].shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(first.frame, Rect { start: 10.0, end: 110.0, top: 20.0, bottom: 50.0 });
+    assert_eq!(second.frame, Rect { start: 10.0, end: 110.0, top: 50.0, bottom: 80.0 });
+}
+
+#[test]
+fn keep_nodes_with_props() {
+    let context: Option<&dyn Any> = None;
+
+    let root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r##"{
+        "root": {
+            "kind": "flexbox",
+            "layout": {},
+            "children": [
+                {"kind": "flexbox", "layout": {}, "props": {"background-color": "#fff"}},
+                {"kind": "flexbox", "layout": {}, "@media": [{"min-width": 600, "props": {"background-color": "#fff"}}]},
+                {"kind": "test", "layout": {}}
+            ]
+        }
+    }"##,
+    )
+    .unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.child_count, 3);
+    assert!(root.view_node.children.iter().all(|child| !child.is_layout_only()));
+}
+
+#[test]
+fn keep_nodes_with_ids_or_clipping() {
+    let context: Option<&dyn Any> = None;
+
+    let mut root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r#"{
+        "root": {
+            "kind": "flexbox",
+            "layout": {},
+            "children": [
+                {"kind": "flexbox", "id": "container", "layout": {}},
+                {"kind": "flexbox", "layout": {"overflow": "hidden"}},
+                {"kind": "vstack", "layout": {}, "@media": [{"min-width": 600, "layout": {"overflow": "scroll"}}]},
+                {"kind": "flexbox", "layout": {"overflow": "visible"}}
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.child_count, 3);
+    assert!(root.view_node.children[..3].iter().all(|child| !child.is_layout_only()));
+    assert!(root.view_node.children[3].is_layout_only());
+
+    root.set_prop("container", "background-color", "#fff".into()).unwrap();
+}
@@ -0,0 +1,105 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use std::cell::Cell;
+use std::rc::Rc;
+use stretch::geometry::Size;
+use stretch::result::Result;
+
+struct Resolver {
+    resolved: Rc<Cell<usize>>,
+}
+
+impl core::FragmentResolver for Resolver {
+    fn resolve(&self, id: &str) -> Result<String> {
+        self.resolved.set(self.resolved.get() + 1);
+
+        if let Some(level) = id.strip_prefix("level-") {
+            return Ok(format!(r#"{{"$include": "level-{}"}}"#, level.parse::<usize>().unwrap() + 1));
+        }
+
+        match id {
+            "header" => Ok(r#"{"kind": "test", "id": "header", "layout": {"height": {"unit": "points", "value": 20}}, "children": [{"$include": "title"}]}"#.to_string()),
+            "title" => Ok(r#"{"kind": "test", "layout": {}, "props": {"label": "Shard"}}"#.to_string()),
+            "a" => Ok(r#"{"kind": "test", "layout": {}, "children": [{"$include": "b"}]}"#.to_string()),
+            "b" => Ok(r#"{"$include": "a"}"#.to_string()),
+            _ => Err(Box::new(format!("no fragment {}", id))),
+        }
+    }
+}
+
+fn render(fragments: &Rc<core::Fragments>, json: &str) -> Result<core::Root> {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext { fragments: fragments.clone(), ..Default::default() };
+    core::render_root_with_context(&test::ViewManager {}, &context, &render_context, json)
+}
+
+fn include(id: &str) -> String {
+    format!(r#"{{"root": {{"kind": "test", "layout": {{}}, "children": [{{"$include": "{}"}}]}}}}"#, id)
+}
+
+#[test]
+fn include_cached_fragments() {
+    let resolved = Rc::new(Cell::new(0));
+    let fragments = Rc::new(core::Fragments::new(Resolver { resolved: resolved.clone() }));
+    let document = r#"{
+        "root": {
+            "kind": "test",
+            "layout": {"flex-direction": "column"},
+            "children": [
+                {"$include": "header", "id": "top", "layout": {"height": {"unit": "points", "value": 40}}},
+                {"$include": "title", "id": "subtitle"}
+            ]
+        }
+    }"#;
+
+    let mut root = render(&fragments, document).unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    let header = root.view_by_id("header").unwrap();
+    assert_eq!(header.frame().bottom - header.frame().top, 40.0);
+    assert_eq!(header.children[0].prop("label"), "Shard");
+    assert!(root.view_by_id("top").is_none());
+    assert_eq!(root.view_by_id("subtitle").unwrap().prop("label"), "Shard");
+    assert_eq!(resolved.get(), 2);
+
+    render(&fragments, document).unwrap();
+    assert_eq!(resolved.get(), 2);
+
+    fragments.invalidate("title");
+    render(&fragments, document).unwrap();
+    assert_eq!(resolved.get(), 3);
+}
+
+#[test]
+fn fail_on_include_cycles_and_deep_nesting() {
+    let fragments = Rc::new(core::Fragments::new(Resolver { resolved: Rc::new(Cell::new(0)) }));
+
+    let error = render(&fragments, &include("a")).err().unwrap();
+    assert_eq!(error.downcast_ref::<String>().unwrap(), "fragment a includes itself via a > b");
+
+    let error = render(&fragments, &include("level-0")).err().unwrap();
+    assert_eq!(error.downcast_ref::<String>().unwrap(), "includes nested more than 16 deep at level-16");
+
+    let unresolved = Rc::new(core::Fragments::default());
+    assert!(render(&unresolved, &include("title")).is_err());
+}
+
+#[test]
+fn contain_missing_fragments_in_error_boundary() {
+    let fragments = Rc::new(core::Fragments::new(Resolver { resolved: Rc::new(Cell::new(0)) }));
+    let root = render(
+        &fragments,
+        r#"{"root": {"kind": "test", "layout": {}, "children": [{"$include": "footer", "error-boundary": true}]}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(root.view_node.children.len(), 1);
+    assert_eq!(root.warnings[0].path, "/root/children/0");
+    assert_eq!(root.warnings[0].message, "error boundary caught: no fragment footer, leaving it empty");
+}
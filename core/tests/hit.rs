@@ -0,0 +1,166 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Size;
+
+fn render(json: &str) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let mut root = core::render_root(&test::ViewManager {}, &context, json).unwrap();
+    root.measure(Size::undefined()).unwrap();
+    root
+}
+
+fn ids(path: Vec<&core::ViewNode>) -> Vec<&str> {
+    path.iter().map(|view_node| view_node.id().unwrap_or("-")).collect()
+}
+
+#[test]
+fn hit_test_deepest_first() {
+    let root = render(
+        r#"{
+        "root": {
+            "kind": "test",
+            "id": "root",
+            "layout": {"width": {"unit": "points", "value": 200}, "height": {"unit": "points", "value": 200}},
+            "children": [
+                {
+                    "kind": "flexbox",
+                    "id": "row",
+                    "layout": {"width": {"unit": "points", "value": 100}, "height": {"unit": "points", "value": 50}},
+                    "children": [{"kind": "test", "id": "button", "layout": {"width": {"unit": "points", "value": 40}}}]
+                },
+                {
+                    "kind": "test",
+                    "id": "overlay",
+                    "layout": {
+                        "position": "absolute",
+                        "start": {"unit": "points", "value": 20},
+                        "top": {"unit": "points", "value": 20},
+                        "width": {"unit": "points", "value": 10},
+                        "height": {"unit": "points", "value": 10}
+                    }
+                }
+            ]
+        }
+    }"#,
+    );
+
+    assert_eq!(ids(root.hit_test(10.0, 10.0)), vec!["button", "row", "root"]);
+    assert_eq!(ids(root.hit_test(60.0, 10.0)), vec!["row", "root"]);
+    assert_eq!(ids(root.hit_test(25.0, 25.0)), vec!["overlay", "root"]);
+    assert_eq!(ids(root.hit_test(150.0, 150.0)), vec!["root"]);
+    assert!(root.hit_test(250.0, 10.0).is_empty());
+}
+
+#[test]
+fn hit_test_respects_overflow_and_display() {
+    let document = |overflow: &str| {
+        format!(
+            r#"{{
+            "root": {{
+                "kind": "test",
+                "id": "root",
+                "layout": {{"width": {{"unit": "points", "value": 200}}, "height": {{"unit": "points", "value": 200}}}},
+                "children": [
+                    {{
+                        "kind": "test",
+                        "id": "clip",
+                        "layout": {{"overflow": "{}", "width": {{"unit": "points", "value": 50}}, "height": {{"unit": "points", "value": 50}}}},
+                        "children": [
+                            {{
+                                "kind": "test",
+                                "id": "badge",
+                                "layout": {{
+                                    "position": "absolute",
+                                    "start": {{"unit": "points", "value": 40}},
+                                    "width": {{"unit": "points", "value": 20}},
+                                    "height": {{"unit": "points", "value": 20}}
+                                }}
+                            }},
+                            {{
+                                "kind": "test",
+                                "id": "hidden",
+                                "layout": {{
+                                    "display": "none",
+                                    "position": "absolute",
+                                    "width": {{"unit": "points", "value": 50}},
+                                    "height": {{"unit": "points", "value": 50}}
+                                }}
+                            }}
+                        ]
+                    }}
+                ]
+            }}
+        }}"#,
+            overflow
+        )
+    };
+
+    let root = render(&document("visible"));
+    assert_eq!(ids(root.hit_test(55.0, 10.0)), vec!["badge", "clip", "root"]);
+    assert_eq!(ids(root.hit_test(45.0, 10.0)), vec!["badge", "clip", "root"]);
+    assert_eq!(ids(root.hit_test(10.0, 10.0)), vec!["clip", "root"]);
+
+    let root = render(&document("hidden"));
+    assert_eq!(ids(root.hit_test(55.0, 10.0)), vec!["root"]);
+    assert_eq!(ids(root.hit_test(45.0, 10.0)), vec!["badge", "clip", "root"]);
+}
+
+#[test]
+fn hit_test_uses_resolved_layout() {
+    let document = r#"{
+        "state": {"expanded": false},
+        "root": {
+            "kind": "test",
+            "id": "root",
+            "layout": {"width": {"unit": "points", "value": 200}, "height": {"unit": "points", "value": 200}},
+            "children": [
+                {
+                    "kind": "test",
+                    "id": "body",
+                    "if": {"$state": "expanded"},
+                    "layout": {"position": "absolute", "width": {"unit": "points", "value": 50}, "height": {"unit": "points", "value": 50}}
+                },
+                {
+                    "kind": "test",
+                    "id": "clip",
+                    "layout": {"width": {"unit": "points", "value": 100}, "height": {"unit": "points", "value": 100}},
+                    "ios": {"layout": {"overflow": "scroll"}},
+                    "children": [
+                        {
+                            "kind": "test",
+                            "id": "badge",
+                            "layout": {
+                                "position": "absolute",
+                                "start": {"unit": "points", "value": 90},
+                                "width": {"unit": "points", "value": 20},
+                                "height": {"unit": "points", "value": 20}
+                            }
+                        }
+                    ]
+                }
+            ]
+        }
+    }"#;
+
+    let render = |platform| {
+        let context: Option<&dyn Any> = None;
+        let render_context = core::RenderContext { platform: Some(platform), ..Default::default() };
+        let mut root =
+            core::render_root_with_context(&test::ViewManager {}, &context, &render_context, document).unwrap();
+        root.measure(Size::undefined()).unwrap();
+        root
+    };
+
+    let root = render(core::Platform::Android);
+    assert_eq!(ids(root.hit_test(10.0, 10.0)), vec!["clip", "root"]);
+    assert_eq!(ids(root.hit_test(105.0, 10.0)), vec!["badge", "clip", "root"]);
+
+    let root = render(core::Platform::IOS);
+    assert_eq!(ids(root.hit_test(105.0, 10.0)), vec!["root"]);
+}
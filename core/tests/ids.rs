@@ -0,0 +1,87 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Rect;
+use stretch::geometry::Size;
+
+fn render(json: &str) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    core::render_root(&test::ViewManager {}, &context, json).unwrap()
+}
+
+#[test]
+fn find_nodes_by_id() {
+    let mut root = render(
+        r#"{
+        "root": {
+            "kind": "test",
+            "layout": {"flex-direction": "column"},
+            "children": [
+                {"kind": "test", "id": "header", "layout": {"height": {"unit": "points", "value": 20}}},
+                {
+                    "kind": "flexbox",
+                    "id": "body",
+                    "layout": {"padding-start": {"unit": "points", "value": 10}},
+                    "children": [{"kind": "test", "id": "title", "layout": {"height": {"unit": "points", "value": 30}}}]
+                }
+            ]
+        }
+    }"#,
+    );
+    root.measure(Size::undefined()).unwrap();
+
+    assert_eq!(root.view_by_id("header").unwrap().kind(), "test");
+    assert!(!root.view_by_id("body").unwrap().is_layout_only());
+    assert!(root.view_by_id("missing").is_none());
+
+    assert_eq!(root.frame_of("title"), Some(Rect { start: 10.0, end: 110.0, top: 20.0, bottom: 50.0 }));
+    let title = root.view_by_id("title").unwrap().shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    // The body keeps its view, so the native frame of the title is relative to it
+    assert_eq!(title.frame, Rect { start: 10.0, end: 110.0, top: 0.0, bottom: 30.0 });
+}
+
+#[test]
+fn set_prop_by_id() {
+    let mut root = render(
+        r##"{
+        "theme": {"accent": "#f00"},
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "children": [{"kind": "test", "id": "badge", "layout": {}, "props": {"label": "old"}}]
+        }
+    }"##,
+    );
+
+    root.set_prop("badge", "label", json::parse(r#""new""#).unwrap()).unwrap();
+    root.set_prop("badge", "background-color", json::parse(r#"{"$theme": "accent"}"#).unwrap()).unwrap();
+
+    let view = root.view_by_id("badge").unwrap().shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["label"], "\"new\"");
+    assert_eq!(view.props["background-color"], "\"#ffff0000\"");
+
+    assert!(root.set_prop("missing", "label", json::JsonValue::Null).is_err());
+}
+
+#[test]
+fn warn_about_duplicate_ids() {
+    let root = render(
+        r#"{
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "children": [{"kind": "test", "id": "item", "layout": {}}, {"kind": "test", "id": "item", "layout": {}}]
+        }
+    }"#,
+    );
+
+    assert!(std::ptr::eq(root.view_by_id("item").unwrap(), &root.view_node.children[0]));
+    assert_eq!(root.warnings.len(), 1);
+    assert_eq!(root.warnings[0].path, "/root/children/1/id");
+    assert_eq!(root.warnings[0].message, "duplicate id item");
+}
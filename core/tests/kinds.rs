@@ -0,0 +1,92 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use json::object;
+use std::any::Any;
+use std::rc::Rc;
+use stretch::geometry::Size;
+
+fn registry() -> core::KindRegistry {
+    let mut kinds = core::KindRegistry::new();
+
+    kinds.register("badge", |node| {
+        Ok(object! {
+            "kind" => "flexbox",
+            "layout" => object! { "padding" => object! { "unit" => "points", "value" => 4 } },
+            "props" => object! { "background-color" => node["props"]["color"].clone() },
+            "children" => vec![object! {
+                "kind" => "text",
+                "layout" => object! {},
+                "props" => object! { "span" => object! { "text" => node["props"]["label"].clone() } }
+            }]
+        })
+    });
+
+    kinds.register("rating", |node| {
+        let stars = node["props"]["value"].as_usize().unwrap_or(0);
+        Ok(object! {
+            "kind" => "hstack",
+            "children" => (0..stars).map(|_| object! { "kind" => "badge", "props" => object! { "label" => "*" } }).collect::<Vec<_>>()
+        })
+    });
+
+    kinds.register("loop", |_| Ok(object! { "kind" => "loop" }));
+    kinds
+}
+
+fn render(json: &str) -> stretch::result::Result<core::Root> {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext { kinds: Rc::new(registry()), ..Default::default() };
+    core::render_root_with_context(&test::ViewManager {}, &context, &render_context, json)
+}
+
+#[test]
+fn expand_composite_kind() {
+    let mut root = render(
+        r##"{
+        "root": {
+            "kind": "test",
+            "layout": {},
+            "children": [
+                {"kind": "badge", "id": "new", "layout": {"width": {"unit": "points", "value": 50}}, "props": {"label": "New", "color": "#f00"}}
+            ]
+        }
+    }"##,
+    )
+    .unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    let badge = &root.view_node.children[0];
+    assert!(std::ptr::eq(root.view_by_id("new").unwrap(), badge));
+    let view = badge.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.kind, "flexbox");
+    assert_eq!(view.props["background-color"], "\"#ffff0000\"");
+    assert_eq!(view.frame.end - view.frame.start, 50.0);
+
+    let text = badge.children[0].shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(text.kind, "text");
+    assert_eq!(json::parse(&text.props["runs"]).unwrap()[0]["text"], "New");
+}
+
+#[test]
+fn expand_nested_composite_kinds() {
+    let root =
+        render(r#"{"root": {"kind": "test", "layout": {}, "children": [{"kind": "rating", "props": {"value": 3}}]}}"#)
+            .unwrap();
+
+    let stack = &root.view_node.children[0];
+    assert!(stack.is_layout_only());
+    assert_eq!(stack.children.len(), 3);
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.child_count, 3);
+}
+
+#[test]
+fn fail_on_endless_expansion() {
+    assert!(render(r#"{"root": {"kind": "loop", "layout": {}}}"#).is_err());
+}
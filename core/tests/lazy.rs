@@ -0,0 +1,103 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use std::rc::Rc;
+use stretch::geometry::Size;
+
+const DOCUMENT: &str = r#"{
+    "root": {
+        "kind": "test",
+        "layout": {"flex-direction": "column"},
+        "children": [
+            {"kind": "test", "id": "header", "layout": {"height": {"unit": "points", "value": 20}}},
+            {
+                "$include": "recommendations",
+                "id": "recommendations",
+                "layout": {"height": {"unit": "points", "value": 60}},
+                "placeholder": {
+                    "kind": "vstack",
+                    "children": [
+                        {"kind": "test", "layout": {"height": {"unit": "points", "value": 10}}, "props": {"label": "loading"}},
+                        {"kind": "test", "layout": {"height": {"unit": "points", "value": 10}}, "props": {"label": "loading"}}
+                    ]
+                }
+            },
+            {"kind": "test", "id": "footer", "layout": {"height": {"unit": "points", "value": 20}}}
+        ]
+    }
+}"#;
+
+const RECOMMENDATIONS: &str = r#"{
+    "kind": "test",
+    "layout": {"flex-direction": "row"},
+    "props": {"label": "loaded"},
+    "children": [{"kind": "test", "id": "first", "layout": {"width": {"unit": "points", "value": 40}}}]
+}"#;
+
+fn render(fragments: &Rc<core::Fragments>) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext { fragments: fragments.clone(), ..Default::default() };
+    let mut root = core::render_root_with_context(&test::ViewManager {}, &context, &render_context, DOCUMENT).unwrap();
+    root.measure(Size::undefined()).unwrap();
+    root
+}
+
+fn view(view_node: &core::ViewNode) -> &test::View {
+    view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap()
+}
+
+#[test]
+fn render_placeholder_until_fragment_is_loaded() {
+    let fragments = Rc::new(core::Fragments::default());
+    let mut root = render(&fragments);
+
+    assert_eq!(root.pending_fragments(), vec!["recommendations"]);
+    let placeholder = root.view_by_id("recommendations").unwrap();
+    assert!(!placeholder.is_layout_only());
+    assert_eq!(placeholder.frame().bottom - placeholder.frame().top, 60.0);
+    assert_eq!(view(&root.view_node).child_count, 3);
+
+    let context: Option<&dyn Any> = None;
+    root.load_fragment(&test::ViewManager {}, &context, "recommendations", RECOMMENDATIONS).unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    assert!(root.pending_fragments().is_empty());
+    assert_eq!(view(&root.view_node).child_count, 3);
+
+    let recommendations = root.view_by_id("recommendations").unwrap();
+    assert_eq!(recommendations.prop("label"), "loaded");
+    assert_eq!(recommendations.frame().top, 20.0);
+    assert_eq!(recommendations.frame().bottom, 80.0);
+    assert_eq!(root.frame_of("first").unwrap().end, 40.0);
+    assert_eq!(root.frame_of("footer").unwrap().top, 80.0);
+}
+
+#[test]
+fn render_loaded_fragments_directly() {
+    let fragments = Rc::new(core::Fragments::default());
+    let mut root = render(&fragments);
+    let context: Option<&dyn Any> = None;
+    root.load_fragment(&test::ViewManager {}, &context, "recommendations", RECOMMENDATIONS).unwrap();
+
+    let root = render(&fragments);
+    assert!(root.pending_fragments().is_empty());
+    assert_eq!(root.view_by_id("recommendations").unwrap().prop("label"), "loaded");
+}
+
+#[test]
+fn keep_placeholder_when_fragment_is_invalid() {
+    let fragments = Rc::new(core::Fragments::default());
+    let mut root = render(&fragments);
+    let context: Option<&dyn Any> = None;
+
+    assert!(root.load_fragment(&test::ViewManager {}, &context, "recommendations", "[]").is_err());
+    assert!(root.load_fragment(&test::ViewManager {}, &context, "recommendations", r#"{"layout": {}}"#).is_err());
+
+    assert_eq!(root.pending_fragments(), vec!["recommendations"]);
+    assert_eq!(view(&root.view_node).child_count, 3);
+}
@@ -0,0 +1,138 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use core::{DocumentLoader, Encoding, LoadedDocument, Request, Response, Source, Transport};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::rc::Rc;
+use stretch::result::Result;
+
+#[derive(Default)]
+struct Server {
+    responses: VecDeque<Option<Response>>,
+    requests: Vec<(String, Option<String>)>,
+}
+
+/// Serves queued responses, where `None` fails the request as if the device were offline.
+struct FakeTransport(Rc<RefCell<Server>>);
+
+impl Transport for FakeTransport {
+    fn fetch(&mut self, request: &Request) -> Result<Response> {
+        let mut server = self.0.borrow_mut();
+        server.requests.push((request.url.clone(), request.etag.clone()));
+        match server.responses.pop_front().expect("unexpected request") {
+            Some(response) => Ok(response),
+            None => Err(Box::new("offline".to_string())),
+        }
+    }
+}
+
+fn ok(body: &str, etag: Option<&str>, cache_control: &str) -> Option<Response> {
+    Some(Response {
+        status: 200,
+        body: body.to_string(),
+        etag: etag.map(str::to_string),
+        cache_control: Some(cache_control.to_string()),
+    })
+}
+
+fn not_modified() -> Option<Response> {
+    Some(Response { status: 304, body: String::new(), etag: None, cache_control: None })
+}
+
+fn loader(name: &str) -> (DocumentLoader, Rc<RefCell<Server>>, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("shard-loader-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let server = Rc::new(RefCell::new(Server::default()));
+    (DocumentLoader::new(Box::new(FakeTransport(server.clone())), dir.clone()), server, dir)
+}
+
+const URL: &str = "https://example.com/home.json";
+
+#[test]
+fn serve_fresh_documents_from_disk() {
+    let (mut loader, server, dir) = loader("fresh");
+    server.borrow_mut().responses.push_back(ok("v1", Some("\"a\""), "public, max-age=60"));
+
+    assert_eq!(loader.load(URL).unwrap(), LoadedDocument { body: "v1".to_string(), source: Source::Network });
+    assert_eq!(loader.load(URL).unwrap(), LoadedDocument { body: "v1".to_string(), source: Source::Cache });
+
+    let server = Rc::new(RefCell::new(Server::default()));
+    let mut reopened = DocumentLoader::new(Box::new(FakeTransport(server.clone())), dir);
+    assert_eq!(reopened.load(URL).unwrap().source, Source::Cache);
+    assert!(server.borrow().requests.is_empty());
+    assert_eq!(reopened.cached("https://example.com/other.json"), None);
+}
+
+#[test]
+fn revalidate_with_etag_while_showing_stale_copy() {
+    let (mut loader, server, _) = loader("revalidate");
+    server.borrow_mut().responses.push_back(ok("v1", Some("\"a\""), "no-cache"));
+    server.borrow_mut().responses.push_back(not_modified());
+    server.borrow_mut().responses.push_back(ok("v2", Some("\"b\""), "max-age=0"));
+
+    loader.load(URL).unwrap();
+    assert_eq!(loader.load(URL).unwrap(), LoadedDocument { body: "v1".to_string(), source: Source::Network });
+
+    let mut documents = vec![];
+    loader.load_stale_while_revalidate(URL, &mut |document| documents.push(document)).unwrap();
+
+    assert_eq!(
+        documents,
+        vec![
+            LoadedDocument { body: "v1".to_string(), source: Source::Stale },
+            LoadedDocument { body: "v2".to_string(), source: Source::Network },
+        ]
+    );
+    assert_eq!(
+        server.borrow().requests,
+        vec![
+            (URL.to_string(), None),
+            (URL.to_string(), Some("\"a\"".to_string())),
+            (URL.to_string(), Some("\"a\"".to_string())),
+        ]
+    );
+    assert_eq!(loader.cached(URL), Some("v2".to_string()));
+}
+
+#[test]
+fn fall_back_to_cache_when_offline() {
+    let (mut loader, server, _) = loader("offline");
+    server.borrow_mut().responses.push_back(ok("v1", None, "max-age=0"));
+    server.borrow_mut().responses.push_back(None);
+    server.borrow_mut().responses.push_back(None);
+    server.borrow_mut().responses.push_back(ok("private", None, "no-store"));
+
+    loader.load(URL).unwrap();
+    assert_eq!(loader.load(URL).unwrap(), LoadedDocument { body: "v1".to_string(), source: Source::Stale });
+    assert!(loader.load("https://example.com/other.json").is_err());
+
+    assert_eq!(loader.load("https://example.com/private.json").unwrap().body, "private");
+    assert_eq!(loader.cached("https://example.com/private.json"), None);
+}
+
+#[test]
+fn decode_binary_responses_to_json() {
+    let document = json::parse(r#"{"root": {"kind": "text", "props": {"text": "hi"}}}"#).unwrap();
+    let cbor = Response::from_bytes(200, &Encoding::Cbor.encode(&document), Some("application/cbor")).unwrap();
+    assert_eq!(json::parse(&cbor.body).unwrap(), document);
+
+    let detected = Response::from_bytes(200, &Encoding::MessagePack.encode(&document), None).unwrap();
+    assert_eq!(json::parse(&detected.body).unwrap(), document);
+
+    assert_eq!(Response::from_bytes(404, b"not found", Some("text/plain")).unwrap().body, "not found");
+    assert!(Response::from_bytes(200, b"<html>", Some("text/html")).is_err());
+}
+
+#[test]
+fn cache_documents_with_huge_max_age() {
+    let (mut loader, server, _) = loader("max-age");
+    server.borrow_mut().responses.push_back(ok("v1", None, "max-age=18446744073709551615"));
+
+    assert_eq!(loader.load(URL).unwrap().source, Source::Network);
+    assert_eq!(loader.load(URL).unwrap(), LoadedDocument { body: "v1".to_string(), source: Source::Cache });
+}
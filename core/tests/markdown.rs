@@ -0,0 +1,70 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use core::{FontSize, FontStyle, FontWeight, TextDecoration};
+use std::any::Any;
+
+#[test]
+fn parse_inline_markdown() {
+    let runs = core::parse_markdown("Hello *small* **big `code`** [docs](https://visly.app) a\\*b snake_case_name");
+    let texts: Vec<&str> = runs.iter().map(|run| run.text.as_str()).collect();
+    assert_eq!(texts, vec!["Hello ", "small", " ", "big ", "code", " ", "docs", " a*b snake_case_name"]);
+
+    assert_eq!(runs[1].font_style, FontStyle::Italic);
+    assert_eq!(runs[3].font_weight, FontWeight::Bold);
+    assert_eq!(runs[4].font_weight, FontWeight::Bold);
+    assert_eq!(runs[4].font_family.as_ref().unwrap(), "monospace");
+
+    assert_eq!(runs[6].decoration, TextDecoration::Underline);
+    let action = runs[6].action.as_ref().unwrap();
+    assert_eq!(action["action"], "open-url");
+    assert_eq!(action["value"], "https://visly.app");
+    assert_eq!(runs[7].action, None);
+}
+
+#[test]
+fn parse_markdown_blocks() {
+    let runs = core::parse_markdown("# Title\n\nFirst line\nsecond line\n\n- one\n- *two*\n\n1. first");
+    let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+    assert_eq!(text, "Title\n\nFirst line second line\n\n• one\n• two\n\n1. first");
+
+    assert_eq!(runs[0].text, "Title");
+    assert_eq!(runs[0].font_weight, FontWeight::Bold);
+    assert_eq!(runs[0].font_size, FontSize::Points(24.0));
+    assert_eq!(runs[1].font_weight, FontWeight::Regular);
+}
+
+#[test]
+fn render_markdown_prop() {
+    let context: Option<&dyn Any> = None;
+
+    let root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r##"{
+        "strings": {"en": {"about": "Read the [guide](https://visly.app/guide)"}},
+        "root": {
+            "kind": "text",
+            "layout": {},
+            "props": {
+                "markdown": {"text": {"$t": "about"}, "font-color": "#333"}
+            }
+        }
+    }"##,
+    )
+    .unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert!(!view.props.contains_key("markdown"));
+
+    let runs = json::parse(&view.props["runs"]).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0]["text"], "Read the ");
+    assert_eq!(runs[0]["font-color"], "#ff333333");
+    assert_eq!(runs[1]["text"], "guide");
+    assert_eq!(runs[1]["action"]["value"], "https://visly.app/guide");
+}
@@ -0,0 +1,62 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Size;
+use stretch::number::Number;
+
+const DOCUMENT: &str = r#"{
+    "root": {
+        "kind": "test",
+        "layout": {"flex-direction": "column"},
+        "props": {"columns": 1},
+        "@media": [
+            {"min-width": 600, "layout": {"flex-direction": "row"}, "props": {"columns": 2}},
+            {"min-width": 900, "props": {"columns": 3, "sidebar": true}}
+        ],
+        "children": [
+            {"kind": "test", "layout": {}}
+        ]
+    }
+}"#;
+
+fn size(width: f32) -> Size<Number> {
+    Size { width: Number::Defined(width), height: Number::Undefined }
+}
+
+#[test]
+fn render_media_variant_for_root_width() {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext { width: Some(700.0), ..Default::default() };
+    let root = core::render_root_with_context(&test::ViewManager {}, &context, &render_context, DOCUMENT).unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["columns"], "2");
+    assert_eq!(root.stretch_node.flex_direction, stretch::style::FlexDirection::Row);
+}
+
+#[test]
+fn reapply_media_variants_on_resize() {
+    let context: Option<&dyn Any> = None;
+    let mut root = core::render_root(&test::ViewManager {}, &context, DOCUMENT).unwrap();
+
+    root.measure(size(400.0)).unwrap();
+    assert_eq!(root.stretch_node.flex_direction, stretch::style::FlexDirection::Column);
+
+    root.measure(size(1000.0)).unwrap();
+    assert_eq!(root.stretch_node.flex_direction, stretch::style::FlexDirection::Row);
+    assert_eq!(root.stretch_node.children.len(), 1);
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["columns"], "3");
+    assert_eq!(view.props["sidebar"], "true");
+
+    root.measure(size(400.0)).unwrap();
+    assert_eq!(root.stretch_node.flex_direction, stretch::style::FlexDirection::Column);
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["columns"], "1");
+    assert_eq!(view.props["sidebar"], "null");
+}
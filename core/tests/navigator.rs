@@ -0,0 +1,129 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use core::{NavigationEntry, ShardNavigator, Transition};
+use json::JsonValue;
+use std::any::Any;
+use stretch::result::Result;
+
+#[derive(Default)]
+struct Host {
+    transitions: Vec<(Transition, Option<String>, String, f32)>,
+    actions: Vec<String>,
+}
+
+fn title(entry: &NavigationEntry) -> String {
+    entry.root.state()["title"].to_string()
+}
+
+impl core::ActionHandler for Host {
+    fn on_action(&mut self, action: &str, _: &JsonValue) -> Result<()> {
+        self.actions.push(action.to_string());
+        Ok(())
+    }
+}
+
+impl core::NavigatorHost for Host {
+    fn render(&mut self, target: &JsonValue) -> Result<Box<core::Root>> {
+        let document = match target {
+            JsonValue::Object(_) => target.dump(),
+            target => {
+                format!(r#"{{"state": {{"title": {}}}, "root": {{"kind": "test", "layout": {{}}}}}}"#, target.dump())
+            }
+        };
+        let context: Option<&dyn Any> = None;
+        core::render_root(&test::ViewManager {}, &context, &document).map(Box::new)
+    }
+
+    fn transition(
+        &mut self,
+        transition: Transition,
+        from: Option<&NavigationEntry>,
+        to: &NavigationEntry,
+    ) -> Result<()> {
+        self.transitions.push((transition, from.map(title), title(to), to.scroll_position.y));
+        Ok(())
+    }
+}
+
+fn action(json: &str) -> JsonValue {
+    json::parse(json).unwrap()
+}
+
+#[test]
+fn push_and_pop_restores_state_and_scroll_position() {
+    let mut host = Host::default();
+    let mut navigator = ShardNavigator::new();
+
+    navigator.push(&mut host, &"home".into()).unwrap();
+    navigator.set_scroll_position(0.0, 120.0);
+
+    let open = action(
+        r#"[
+        {"action": "set-state", "value": {"opened": true}},
+        {"action": "push", "value": "details"},
+        {"action": "track"}
+    ]"#,
+    );
+    navigator.dispatch(&mut host, &open).unwrap();
+
+    assert_eq!(navigator.entries().len(), 2);
+    assert_eq!(host.actions, vec!["track"]);
+
+    navigator.dispatch(&mut host, &action(r#"{"action": "pop"}"#)).unwrap();
+
+    assert_eq!(navigator.entries().len(), 1);
+    assert_eq!(navigator.top().unwrap().root.state()["opened"], true);
+    assert_eq!(
+        host.transitions,
+        vec![
+            (Transition::Push, None, "home".to_string(), 0.0),
+            (Transition::Push, Some("home".to_string()), "details".to_string(), 0.0),
+            (Transition::Pop, Some("details".to_string()), "home".to_string(), 120.0),
+        ]
+    );
+}
+
+#[test]
+fn replace_and_pop_several() {
+    let mut host = Host::default();
+    let mut navigator = ShardNavigator::new();
+
+    navigator.push(&mut host, &"home".into()).unwrap();
+    navigator.push(&mut host, &"list".into()).unwrap();
+    navigator.dispatch(&mut host, &action(r#"{"action": "replace", "value": "search"}"#)).unwrap();
+    navigator.push(&mut host, &"details".into()).unwrap();
+
+    let titles: Vec<String> = navigator.entries().iter().map(title).collect();
+    assert_eq!(titles, vec!["home", "search", "details"]);
+
+    navigator.dispatch(&mut host, &action(r#"{"action": "pop", "value": 5}"#)).unwrap();
+    assert_eq!(navigator.entries().len(), 1);
+    assert_eq!(
+        host.transitions.last().unwrap(),
+        &(Transition::Pop, Some("details".to_string()), "home".to_string(), 0.0)
+    );
+
+    assert!(!navigator.pop(&mut host, 1).unwrap());
+}
+
+#[test]
+fn push_inline_document() {
+    let mut host = Host::default();
+    let mut navigator = ShardNavigator::new();
+
+    navigator.push(&mut host, &"home".into()).unwrap();
+    navigator
+        .dispatch(
+            &mut host,
+            &action(r#"{"action": "push", "value": {"state": {"title": "inline"}, "root": {"kind": "test", "layout": {}}}}"#),
+        )
+        .unwrap();
+
+    assert_eq!(title(navigator.top().unwrap()), "inline");
+    assert!(navigator.dispatch(&mut host, &action(r#"{"action": "push", "value": 1}"#)).is_err());
+}
@@ -0,0 +1,172 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Size;
+use stretch::result::Result;
+
+const DOCUMENT: &str = r#"{
+    "state": {"status": "preparing"},
+    "root": {
+        "kind": "test",
+        "layout": {"flex-direction": "column"},
+        "children": [
+            {"kind": "test", "id": "status", "layout": {"height": {"unit": "points", "value": 20}}, "props": {"label": {"$state": "status"}}},
+            {"kind": "test", "id": "eta", "layout": {"height": {"unit": "points", "value": 20}}, "props": {"label": "10 min"}},
+            {"kind": "test", "id": "courier", "layout": {"height": {"unit": "points", "value": 20}}, "props": {"label": "Sam"}}
+        ]
+    }
+}"#;
+
+fn render() -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let mut root = core::render_root(&test::ViewManager {}, &context, DOCUMENT).unwrap();
+    root.measure(Size::undefined()).unwrap();
+    root
+}
+
+fn apply(root: &mut core::Root, patch: &str) -> Result<()> {
+    let context: Option<&dyn Any> = None;
+    root.apply_patch(&test::ViewManager {}, &context, &json::parse(patch).unwrap())?;
+    root.measure(Size::undefined())
+}
+
+fn view<'a>(root: &'a core::Root, id: &str) -> &'a test::View {
+    root.view_by_id(id).unwrap().shard_view.as_any().downcast_ref::<test::View>().unwrap()
+}
+
+fn root_view(root: &core::Root) -> &test::View {
+    root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap()
+}
+
+#[test]
+fn update_props_in_place() {
+    let mut root = render();
+    let eta = view(&root, "eta") as *const test::View;
+    let updates = (view(&root, "eta").prop_updates, view(&root, "courier").prop_updates);
+
+    apply(
+        &mut root,
+        r#"[
+        {"op": "replace", "path": "/root/children/1/props/label", "value": "5 min"},
+        {"op": "replace", "id": "courier", "path": "/layout/height/value", "value": 40},
+        {"op": "replace", "path": "/state/status", "value": "on the way"}
+    ]"#,
+    )
+    .unwrap();
+
+    assert!(std::ptr::eq(view(&root, "eta"), eta));
+    assert_eq!(view(&root, "eta").props["label"], "\"5 min\"");
+    assert_eq!(view(&root, "eta").prop_updates, updates.0 + 1);
+    assert_eq!(view(&root, "courier").prop_updates, updates.1);
+    assert_eq!(root.frame_of("courier").unwrap().bottom, 80.0);
+    assert_eq!(view(&root, "status").props["label"], "\"on the way\"");
+    assert_eq!(root.state()["status"], "on the way");
+}
+
+#[test]
+fn add_remove_and_replace_nodes() {
+    let mut root = render();
+
+    apply(
+        &mut root,
+        r#"[
+        {"op": "add", "path": "/root/children/-", "value": {"kind": "test", "id": "rating", "layout": {"height": {"unit": "points", "value": 10}}}},
+        {"op": "remove", "path": "/root/children/0"},
+        {"op": "replace", "id": "courier", "path": "/kind", "value": "map"}
+    ]"#,
+    )
+    .unwrap();
+
+    let ids: Vec<_> = root.view_node.children.iter().map(|child| child.id().unwrap()).collect();
+    assert_eq!(ids, vec!["eta", "courier", "rating"]);
+    assert!(root.view_by_id("status").is_none());
+    assert_eq!(view(&root, "courier").kind, "map");
+    assert_eq!(root_view(&root).child_count, 3);
+    assert_eq!(root.frame_of("courier").unwrap().top, 20.0);
+    assert_eq!(root.frame_of("rating").unwrap().top, 40.0);
+}
+
+#[test]
+fn reject_conflicting_patches() {
+    let mut root = render();
+
+    let error = apply(
+        &mut root,
+        r#"[
+        {"op": "replace", "id": "eta", "path": "/props/label", "value": "5 min"},
+        {"op": "test", "id": "courier", "path": "/props/label", "value": "Alex"}
+    ]"#,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        error.downcast_ref::<String>().unwrap(),
+        r#"patch operation 1 failed: test failed, /root/children/2/props/label is "Sam""#
+    );
+    assert_eq!(view(&root, "eta").props["label"], "\"10 min\"");
+
+    let error = apply(&mut root, r#"[{"op": "remove", "id": "missing", "path": ""}]"#).err().unwrap();
+    assert_eq!(error.downcast_ref::<String>().unwrap(), "patch operation 0 failed: no node with id missing");
+
+    assert!(apply(&mut root, r#"[{"op": "add", "path": "/root/children/7", "value": {}}]"#).is_err());
+
+    let views = root_view(&root) as *const test::View;
+    let error = apply(&mut root, r#"[{"op": "replace", "path": "/root/kind", "value": "map"}]"#).err().unwrap();
+    assert_eq!(
+        error.downcast_ref::<&str>().unwrap(),
+        &"cannot patch the root node into a different kind, render the document again"
+    );
+    assert!(std::ptr::eq(root_view(&root), views));
+}
+
+#[test]
+fn insert_added_nodes_in_order() {
+    let mut root = render();
+
+    apply(
+        &mut root,
+        r#"[
+        {"op": "add", "path": "/root/children/1", "value": {"kind": "test", "id": "rating", "layout": {"height": {"unit": "points", "value": 10}}}},
+        {"op": "add", "path": "/root/children/0", "value": {"kind": "flexbox", "layout": {}, "children": [{"kind": "test", "id": "banner", "layout": {"height": {"unit": "points", "value": 30}}}]}}
+    ]"#,
+    )
+    .unwrap();
+
+    let ids = ["banner", "status", "rating", "eta", "courier"];
+    let views: Vec<*const test::View> = ids.iter().map(|id| view(&root, id) as *const test::View).collect();
+    assert_eq!(root_view(&root).children, views);
+    assert_eq!(root.frame_of("rating").unwrap().top, 50.0);
+}
+
+#[test]
+fn leave_root_unchanged_when_patch_fails() {
+    let mut root = render();
+
+    let eta = view(&root, "eta") as *const test::View;
+    let updates = view(&root, "eta").prop_updates;
+
+    // The added include fails to render before any view is touched
+    let patch = r#"[
+        {"op": "replace", "id": "eta", "path": "/props/label", "value": "5 min"},
+        {"op": "replace", "path": "/state/status", "value": "on the way"},
+        {"op": "add", "path": "/root/children/-", "value": {"$include": "missing"}}
+    ]"#;
+    let error = apply(&mut root, patch).err().unwrap();
+    assert_eq!(error.downcast_ref::<String>().unwrap(), "no fragment resolver to include missing");
+
+    assert!(std::ptr::eq(view(&root, "eta"), eta));
+    assert_eq!(view(&root, "eta").prop_updates, updates);
+    assert_eq!(view(&root, "eta").props["label"], "\"10 min\"");
+    assert_eq!(view(&root, "status").props["label"], "\"preparing\"");
+    assert_eq!(root.state()["status"], "preparing");
+    assert_eq!(root_view(&root).child_count, 3);
+
+    // Later patches apply to the document as it was before the failed one
+    apply(&mut root, r#"[{"op": "test", "path": "/root/children/1/props/label", "value": "10 min"}]"#).unwrap();
+    assert_eq!(root.frame_of("courier").unwrap().top, 40.0);
+}
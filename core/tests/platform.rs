@@ -0,0 +1,55 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Size;
+
+const DOCUMENT: &str = r#"{
+    "root": {
+        "kind": "test",
+        "layout": {"width": {"unit": "points", "value": 100}},
+        "props": {"font-family": "sans-serif", "elevation": 2},
+        "ios": {
+            "props": {"font-family": "Helvetica", "shadow": 2},
+            "layout": {"width": {"unit": "points", "value": 200}}
+        },
+        "android": {
+            "props": {"font-family": "Roboto"}
+        }
+    }
+}"#;
+
+fn render(platform: Option<core::Platform>) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext { platform, ..Default::default() };
+    let mut root = core::render_root_with_context(&test::ViewManager {}, &context, &render_context, DOCUMENT).unwrap();
+    root.measure(Size::undefined()).unwrap();
+    root
+}
+
+#[test]
+fn render_platform_overrides() {
+    let root = render(Some(core::Platform::IOS));
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["font-family"], "\"Helvetica\"");
+    assert_eq!(view.props["shadow"], "2");
+    assert_eq!(view.frame.end, 200.0);
+
+    let root = render(Some(core::Platform::Android));
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["font-family"], "\"Roboto\"");
+    assert_eq!(view.props["elevation"], "2");
+    assert_eq!(view.frame.end, 100.0);
+}
+
+#[test]
+fn render_without_platform_ignores_overrides() {
+    let root = render(None);
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["font-family"], "\"sans-serif\"");
+    assert!(!view.props.contains_key("shadow"));
+}
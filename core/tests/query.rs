@@ -0,0 +1,81 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Rect;
+use stretch::geometry::Size;
+
+const DOCUMENT: &str = r##"{
+    "root": {
+        "kind": "test",
+        "id": "list",
+        "layout": {"flex-direction": "column"},
+        "children": [
+            {
+                "kind": "flexbox",
+                "class": "row header",
+                "layout": {},
+                "children": [{"kind": "text", "layout": {"height": {"unit": "points", "value": 20}}, "props": {"span": {"text": "Title"}}}]
+            },
+            {
+                "kind": "test",
+                "class": "row",
+                "layout": {"height": {"unit": "points", "value": 40}},
+                "props": {"selected": true, "label": "First"},
+                "children": [{"kind": "text", "layout": {}, "props": {"span": {"text": "First"}}}]
+            },
+            {"kind": "test", "class": "row", "layout": {}, "props": {"selected": false, "label": "Second"}}
+        ]
+    }
+}"##;
+
+fn render() -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let mut root = core::render_root(&test::ViewManager {}, &context, DOCUMENT).unwrap();
+    root.measure(Size::undefined()).unwrap();
+    root
+}
+
+#[test]
+fn query_by_kind_id_class_and_prop() {
+    let root = render();
+
+    assert_eq!(root.query("text").unwrap().len(), 2);
+    assert_eq!(root.query("#list").unwrap().len(), 1);
+    assert_eq!(root.query(".row").unwrap().len(), 3);
+    assert_eq!(root.query("flexbox.header.row").unwrap().len(), 1);
+    assert_eq!(root.query("[selected]").unwrap().len(), 2);
+    assert_eq!(root.query("*").unwrap().len(), 6);
+
+    let selected = root.query(".row[selected=true]").unwrap();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].prop("label"), "First");
+    assert_eq!(selected[0].frame(), Rect { start: 0.0, end: 100.0, top: 20.0, bottom: 60.0 });
+
+    assert_eq!(root.query(r#"[label="Second"]"#).unwrap()[0].prop("selected"), false);
+    assert_eq!(root.query("[label=Second]").unwrap().len(), 1);
+}
+
+#[test]
+fn query_descendants_and_children() {
+    let root = render();
+
+    assert_eq!(root.query("#list text").unwrap().len(), 2);
+    assert_eq!(root.query("#list > text").unwrap().len(), 0);
+    assert_eq!(root.query("#list > .row > text").unwrap().len(), 2);
+    assert_eq!(root.query("test[selected=true] > text").unwrap().len(), 1);
+    assert_eq!(root.query(".header text").unwrap()[0].frame(), Rect { start: 0.0, end: 100.0, top: 0.0, bottom: 20.0 });
+}
+
+#[test]
+fn reject_invalid_selectors() {
+    let root = render();
+
+    for selector in &["", "#", "text >", "[label", "text,image", ".row[=1]"] {
+        assert!(root.query(selector).is_err(), "{} should not parse", selector);
+    }
+}
@@ -11,7 +11,7 @@ use stretch::geometry::Size;
 
 #[test]
 fn render_view_of_kind() {
-    let context: Option<&Any> = None;
+    let context: Option<&dyn Any> = None;
 
     let mut root = core::render_root(
         &test::ViewManager {},
@@ -32,7 +32,7 @@ fn render_view_of_kind() {
 
 #[test]
 fn render_view_with_flex_direction() {
-    let context: Option<&Any> = None;
+    let context: Option<&dyn Any> = None;
 
     let mut root = core::render_root(
         &test::ViewManager {},
@@ -54,7 +54,7 @@ fn render_view_with_flex_direction() {
 
 #[test]
 fn render_view_with_size() {
-    let context: Option<&Any> = None;
+    let context: Option<&dyn Any> = None;
 
     let mut root = core::render_root(
         &test::ViewManager {},
@@ -78,7 +78,7 @@ fn render_view_with_size() {
 
 #[test]
 fn render_view_with_children() {
-    let context: Option<&Any> = None;
+    let context: Option<&dyn Any> = None;
 
     let mut root = core::render_root(
         &test::ViewManager {},
@@ -106,7 +106,7 @@ fn render_view_with_children() {
 
 #[test]
 fn render_view_with_props() {
-    let context: Option<&Any> = None;
+    let context: Option<&dyn Any> = None;
 
     let mut root = core::render_root(
         &test::ViewManager {},
@@ -132,7 +132,7 @@ fn render_view_with_props() {
 
 #[test]
 fn render_view_intrinsic_size() {
-    let context: Option<&Any> = None;
+    let context: Option<&dyn Any> = None;
 
     let mut root = core::render_root(
         &test::ViewManager {},
@@ -0,0 +1,82 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+use stretch::geometry::Rect;
+use stretch::geometry::Size;
+
+fn frame(view_node: &core::ViewNode) -> Rect<f32> {
+    view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap().frame
+}
+
+#[test]
+fn render_vstack_with_gap_and_spacer() {
+    let context: Option<&dyn Any> = None;
+
+    let mut root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r#"{
+        "root": {
+            "kind": "vstack",
+            "layout": {"height": {"unit": "points", "value": 100}},
+            "children": [
+                {"kind": "test", "layout": {"height": {"unit": "points", "value": 10}}},
+                {"kind": "gap", "props": {"size": 8}},
+                {"kind": "test", "layout": {"height": {"unit": "points", "value": 10}}},
+                {"kind": "spacer"},
+                {"kind": "test", "layout": {"height": {"unit": "points", "value": 10}}}
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.kind, "flexbox");
+    assert_eq!(view.child_count, 3);
+
+    let children = &root.view_node.children;
+    assert!(children[1].is_layout_only() && children[3].is_layout_only());
+    assert_eq!(frame(&children[0]).top, 0.0);
+    assert_eq!(frame(&children[2]).top, 18.0);
+    assert_eq!(frame(&children[4]).top, 90.0);
+}
+
+#[test]
+fn render_gap_along_hstack_axis() {
+    let context: Option<&dyn Any> = None;
+
+    let mut root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r#"{
+        "root": {
+            "kind": "test",
+            "layout": {"flex-direction": "column"},
+            "children": [
+                {
+                    "kind": "hstack",
+                    "children": [
+                        {"kind": "test", "layout": {"width": {"unit": "points", "value": 10}}},
+                        {"kind": "gap", "props": {"size": 4}},
+                        {"kind": "test", "layout": {"width": {"unit": "points", "value": 10}}}
+                    ]
+                }
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    let stack = &root.view_node.children[0];
+    assert!(stack.is_layout_only());
+    assert_eq!(frame(&stack.children[0]), Rect { start: 0.0, end: 10.0, top: 0.0, bottom: 100.0 });
+    assert_eq!(frame(&stack.children[2]), Rect { start: 14.0, end: 24.0, top: 0.0, bottom: 100.0 });
+}
@@ -0,0 +1,98 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use json::JsonValue;
+use std::any::Any;
+use stretch::geometry::Size;
+use stretch::result::Result;
+
+const DOCUMENT: &str = r#"{
+    "state": {"expanded": false, "cart": {"quantity": 1}, "tab": "home"},
+    "root": {
+        "kind": "test",
+        "layout": {"flex-direction": "column"},
+        "children": [
+            {"kind": "test", "id": "header", "layout": {}, "props": {"on-click": {"action": "toggle-state", "value": "expanded"}}},
+            {"kind": "test", "id": "body", "if": {"$state": "expanded"}, "layout": {"height": {"unit": "points", "value": 50}}},
+            {"kind": "test", "id": "quantity", "layout": {}, "props": {"label": {"$state": "cart.quantity"}}},
+            {"kind": "test", "id": "home", "layout": {}, "props": {"selected": {"$state": "tab", "equals": "home"}}},
+            {"kind": "test", "id": "collapsed", "layout": {}, "props": {"visible": {"$state": "expanded", "not": true}}}
+        ]
+    }
+}"#;
+
+struct Handler;
+
+impl core::ActionHandler for Handler {
+    fn on_action(&mut self, _: &str, _: &JsonValue) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn render(render_context: &core::RenderContext) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let mut root = core::render_root_with_context(&test::ViewManager {}, &context, render_context, DOCUMENT).unwrap();
+    root.measure(Size::undefined()).unwrap();
+    root
+}
+
+fn view<'a>(root: &'a core::Root, id: &str) -> &'a test::View {
+    root.view_by_id(id).unwrap().shard_view.as_any().downcast_ref::<test::View>().unwrap()
+}
+
+#[test]
+fn bind_props_to_state() {
+    let mut root = render(&Default::default());
+    assert_eq!(view(&root, "quantity").props["label"], "1");
+    assert_eq!(view(&root, "home").props["selected"], "true");
+    assert_eq!(view(&root, "collapsed").props["visible"], "true");
+
+    let updates: Vec<u32> =
+        ["header", "quantity", "home", "collapsed"].iter().map(|id| view(&root, id).prop_updates).collect();
+
+    let action = json::parse(r#"{"action": "increment-state", "value": {"cart.quantity": 2}}"#).unwrap();
+    root.dispatch(&action, &mut Handler).unwrap();
+
+    assert_eq!(root.state()["cart"]["quantity"], 3);
+    assert_eq!(view(&root, "quantity").props["label"], "3");
+    assert_eq!(view(&root, "quantity").prop_updates, updates[1] + 1);
+    assert_eq!(view(&root, "header").prop_updates, updates[0]);
+    assert_eq!(view(&root, "home").prop_updates, updates[2]);
+    assert_eq!(view(&root, "collapsed").prop_updates, updates[3]);
+
+    root.set_state("tab", "search".into()).unwrap();
+    assert_eq!(view(&root, "home").props["selected"], "false");
+}
+
+#[test]
+fn toggle_conditional_nodes() {
+    let mut root = render(&Default::default());
+    assert_eq!(root.frame_of("body").unwrap().bottom - root.frame_of("body").unwrap().top, 0.0);
+
+    let on_click = json::parse(&view(&root, "header").props["on-click"]).unwrap();
+    root.dispatch(&on_click, &mut Handler).unwrap();
+    root.measure(Size::undefined()).unwrap();
+
+    assert_eq!(root.state()["expanded"], true);
+    assert_eq!(root.frame_of("body").unwrap().bottom - root.frame_of("body").unwrap().top, 50.0);
+    assert_eq!(view(&root, "collapsed").props["visible"], "false");
+
+    root.dispatch(&on_click, &mut Handler).unwrap();
+    root.measure(Size::undefined()).unwrap();
+    assert_eq!(root.frame_of("body").unwrap().bottom - root.frame_of("body").unwrap().top, 0.0);
+}
+
+#[test]
+fn override_initial_state() {
+    let render_context =
+        core::RenderContext { state: json::parse(r#"{"expanded": true}"#).unwrap(), ..Default::default() };
+    let root = render(&render_context);
+
+    assert_eq!(root.state()["expanded"], true);
+    assert_eq!(root.state()["tab"], "home");
+    assert_eq!(root.frame_of("body").unwrap().bottom - root.frame_of("body").unwrap().top, 50.0);
+}
@@ -0,0 +1,65 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use core::PluralCategory;
+use std::any::Any;
+
+const DOCUMENT: &str = r#"{
+    "strings": {
+        "en": {
+            "title": "Cart",
+            "cart.items": {"one": "{count} item", "other": "{count} items"}
+        },
+        "ru": {
+            "cart.items": {"one": "{count} товар", "few": "{count} товара", "many": "{count} товаров"}
+        }
+    },
+    "root": {
+        "kind": "test",
+        "layout": {},
+        "props": {
+            "title": {"$t": "title"},
+            "count": {"$t": "cart.items", "count": 3},
+            "missing": {"$t": "missing.key"}
+        }
+    }
+}"#;
+
+fn render(locale: &str) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext { locale: locale.to_string(), ..Default::default() };
+    core::render_root_with_context(&test::ViewManager {}, &context, &render_context, DOCUMENT).unwrap()
+}
+
+#[test]
+fn render_translated_props() {
+    let root = render("en-US");
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["title"], "\"Cart\"");
+    assert_eq!(view.props["count"], "\"3 items\"");
+    assert_eq!(view.props["missing"], "\"missing.key\"");
+}
+
+#[test]
+fn render_translated_props_with_plural_rules() {
+    let root = render("ru_RU");
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["count"], "\"3 товара\"");
+    assert_eq!(view.props["title"], "\"title\"");
+}
+
+#[test]
+fn plural_categories() {
+    assert_eq!(core::plural_category("en", 1.0), PluralCategory::One);
+    assert_eq!(core::plural_category("en", 1.5), PluralCategory::Other);
+    assert_eq!(core::plural_category("fr", 0.0), PluralCategory::One);
+    assert_eq!(core::plural_category("ru", 21.0), PluralCategory::One);
+    assert_eq!(core::plural_category("ru", 12.0), PluralCategory::Many);
+    assert_eq!(core::plural_category("pl", 22.0), PluralCategory::Few);
+    assert_eq!(core::plural_category("ar", 2.0), PluralCategory::Two);
+    assert_eq!(core::plural_category("ja", 1.0), PluralCategory::Other);
+}
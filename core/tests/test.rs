@@ -12,21 +12,47 @@ use stretch::geometry::Size;
 use stretch::number::*;
 use stretch::result::Result;
 
+#[allow(dead_code)]
 pub struct View {
     pub kind: String,
     pub props: HashMap<String, String>,
     pub frame: Rect<f32>,
     pub child_count: u32,
+    /// The views of the children, in order.
+    pub children: Vec<*const View>,
+    pub prop_updates: u32,
+}
+
+fn as_view(view: &dyn core::ShardView) -> *const View {
+    view.as_any().downcast_ref::<View>().unwrap()
 }
 
 impl core::ShardView for View {
-    fn add_child(&mut self, _: &core::ShardView) -> Result<()> {
+    fn add_child(&mut self, child: &dyn core::ShardView) -> Result<()> {
         self.child_count += 1;
+        self.children.push(as_view(child));
+        Ok(())
+    }
+
+    fn remove_child(&mut self, child: &dyn core::ShardView) -> Result<()> {
+        self.child_count -= 1;
+        self.children.retain(|view| *view != as_view(child));
+        Ok(())
+    }
+
+    fn insert_child(&mut self, child: &dyn core::ShardView, index: usize) -> Result<()> {
+        self.child_count += 1;
+        self.children.insert(index, as_view(child));
         Ok(())
     }
 
     fn set_prop(&mut self, key: &str, value: &JsonValue) -> Result<()> {
+        if key == "invalid" {
+            return Err(Box::new(format!("invalid value {}", value.dump())));
+        }
+
         self.props.insert(key.to_string(), value.dump());
+        self.prop_updates += 1;
         Ok(())
     }
 
@@ -39,7 +65,7 @@ impl core::ShardView for View {
         Ok(Size { width: constraints.width.or_else(100.0), height: constraints.height.or_else(100.0) })
     }
 
-    fn as_any(&self) -> &Any {
+    fn as_any(&self) -> &dyn Any {
         self
     }
 }
@@ -47,12 +73,18 @@ impl core::ShardView for View {
 pub struct ViewManager {}
 
 impl core::ShardViewManager for ViewManager {
-    fn create_view(&self, _: &Any, kind: &str) -> Result<Box<core::ShardView>> {
+    fn create_view(&self, _: &dyn Any, kind: &str) -> Result<Box<dyn core::ShardView>> {
+        if kind.starts_with("unknown") {
+            return Err(Box::new(format!("unknown kind {}", kind)));
+        }
+
         Ok(Box::new(View {
             kind: kind.to_string(),
             props: HashMap::new(),
             frame: Rect { start: 0.0, end: 0.0, top: 0.0, bottom: 0.0 },
             child_count: 0,
+            children: vec![],
+            prop_updates: 0,
         }))
     }
 }
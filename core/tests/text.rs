@@ -0,0 +1,84 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use core::{Color, FontSize, FontStyle, FontWeight, TextDecoration};
+use std::any::Any;
+
+#[test]
+fn parse_span_inherits_attributes() {
+    let span = json::parse(
+        r##"{
+        "font-size": {"unit": "pixels", "value": 16},
+        "font-color": "#f00",
+        "text": [
+            {"text": "Hello "},
+            {"text": "world", "font-weight": "bold", "text-decoration": "underline", "on-click": {"action": "open"}},
+            {"text": "!", "font-style": "italic", "font-color": {"default": "blue", "pressed": "red"}}
+        ]
+    }"##,
+    )
+    .unwrap();
+
+    let runs = core::parse_span(&span).unwrap();
+    assert_eq!(runs.len(), 3);
+
+    assert_eq!(runs[0].text, "Hello ");
+    assert_eq!(runs[0].font_size, FontSize::Pixels(16.0));
+    assert_eq!(runs[0].font_weight, FontWeight::Regular);
+    assert_eq!(runs[0].color, Color { r: 255, g: 0, b: 0, a: 255 });
+    assert_eq!(runs[0].action, None);
+
+    assert_eq!(runs[1].font_weight, FontWeight::Bold);
+    assert_eq!(runs[1].decoration, TextDecoration::Underline);
+    assert_eq!(runs[1].font_size, FontSize::Pixels(16.0));
+    assert_eq!(runs[1].action.as_ref().unwrap()["action"], "open");
+
+    assert_eq!(runs[2].font_style, FontStyle::Italic);
+    assert_eq!(runs[2].font_weight, FontWeight::Regular);
+    assert_eq!(runs[2].color, Color { r: 0, g: 0, b: 255, a: 255 });
+}
+
+#[test]
+fn parse_span_rejects_invalid_attributes() {
+    assert!(core::parse_span(&json::parse(r#"{"text": "a", "font-weight": "heavy"}"#).unwrap()).is_err());
+    assert!(
+        core::parse_span(&json::parse(r#"{"text": "a", "font-size": {"unit": "em", "value": 1}}"#).unwrap()).is_err()
+    );
+    assert!(core::parse_span(&json::parse(r#"{"text": "a", "font-color": "nope"}"#).unwrap()).is_err());
+    assert!(core::parse_span(&json::parse(r#"{"text": 1}"#).unwrap()).is_err());
+}
+
+#[test]
+fn render_text_sends_runs() {
+    let context: Option<&dyn Any> = None;
+
+    let root = core::render_root(
+        &test::ViewManager {},
+        &context,
+        r#"{
+        "root": {
+            "kind": "text",
+            "layout": {},
+            "props": {
+                "span": {"text": [{"text": "a"}, {"text": "b", "line-height": 1.5}]}
+            }
+        }
+    }"#,
+    )
+    .unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert!(!view.props.contains_key("span"));
+
+    let runs = json::parse(&view.props["runs"]).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0]["text"], "a");
+    assert_eq!(runs[0]["font-color"], "#ff000000");
+    assert_eq!(runs[0]["font-size"]["unit"], "points");
+    assert_eq!(runs[0]["line-height"], json::JsonValue::Null);
+    assert_eq!(runs[1]["line-height"], 1.5);
+}
@@ -0,0 +1,54 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+
+const DOCUMENT: &str = r##"{
+    "theme": {
+        "background": {"light": "#fff", "dark": "#000"},
+        "accent": "#f00"
+    },
+    "root": {
+        "kind": "test",
+        "layout": {},
+        "props": {
+            "background-color": {"$theme": "background"},
+            "border-color": {"$theme": "accent"},
+            "span": {"text": "hello", "font-color": {"$theme": "background"}}
+        }
+    }
+}"##;
+
+fn render(theme: core::ThemeMode) -> core::Root {
+    let context: Option<&dyn Any> = None;
+    let render_context = core::RenderContext { theme, ..Default::default() };
+    core::render_root_with_context(&test::ViewManager {}, &context, &render_context, DOCUMENT).unwrap()
+}
+
+#[test]
+fn render_theme_tokens() {
+    let root = render(core::ThemeMode::Light);
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["background-color"], "\"#ffffffff\"");
+    assert_eq!(view.props["border-color"], "\"#ffff0000\"");
+    assert_eq!(view.props["span"], r##"{"text":"hello","font-color":"#fff"}"##);
+
+    let root = render(core::ThemeMode::Dark);
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["background-color"], "\"#ff000000\"");
+}
+
+#[test]
+fn switch_theme_mode_updates_affected_props() {
+    let mut root = render(core::ThemeMode::Light);
+    root.set_theme_mode(core::ThemeMode::Dark).unwrap();
+
+    let view = root.view_node.shard_view.as_any().downcast_ref::<test::View>().unwrap();
+    assert_eq!(view.props["background-color"], "\"#ff000000\"");
+    assert_eq!(view.props["span"], r##"{"text":"hello","font-color":"#000"}"##);
+    assert_eq!(view.prop_updates, 5);
+}
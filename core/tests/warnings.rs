@@ -0,0 +1,69 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+mod test;
+use std::any::Any;
+
+fn warnings(json: &str) -> Vec<String> {
+    let context: Option<&dyn Any> = None;
+    let root = core::render_root(&test::ViewManager {}, &context, json).unwrap();
+    root.warnings.iter().map(|warning| warning.to_string()).collect()
+}
+
+#[test]
+fn warn_about_ignored_and_defaulted_values() {
+    let warnings = warnings(
+        r#"{
+        "root": {
+            "kind": "flexbox",
+            "layout": {"flex-direction": "diagonal", "width": {"unit": "px", "value": 10}, "colour": "red"},
+            "style": {},
+            "children": [
+                {
+                    "kind": "text",
+                    "layout": {},
+                    "props": {"span": {"text": "hi"}, "text-align": "justify", "font-size": 12},
+                    "@media": [{"min-width": 600, "props": {"max-lines": 2, "text-color": "red"}}]
+                }
+            ]
+        }
+    }"#,
+    );
+
+    assert_eq!(
+        warnings,
+        vec![
+            "/root/style: ignored key style",
+            "/root/layout/flex-direction: unknown value \"diagonal\" for flex-direction, using the default",
+            "/root/layout/width: invalid dimension {\"unit\":\"px\",\"value\":10} for width, using the default",
+            "/root/layout/colour: ignored layout key colour",
+            "/root/children/0/props/text-align: unknown value justify for text-align, using the default",
+            "/root/children/0/props/font-size: unknown prop font-size for kind text",
+            "/root/children/0/@media/0/props/text-color: unknown prop text-color for kind text",
+        ]
+    );
+}
+
+#[test]
+fn warn_about_deprecated_kinds_and_props_of_virtual_kinds() {
+    let warnings = warnings(
+        r##"{
+        "root": {
+            "kind": "vstack",
+            "props": {"background-color": "#fff"},
+            "children": [{"kind": "solid-color", "layout": {}, "props": {"background-color": "#fff"}}]
+        }
+    }"##,
+    );
+
+    assert_eq!(
+        warnings,
+        vec![
+            "/root/props/background-color: unknown prop background-color for kind vstack",
+            "/root/children/0/kind: kind solid-color is deprecated, use flexbox with a background-color",
+        ]
+    );
+}
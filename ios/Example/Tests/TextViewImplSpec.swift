@@ -41,46 +41,41 @@ class TextViewImplSpec: QuickSpec {
             expect(viewimpl.lineHeightMultiple).to(equal(10))
         }
         
-        it("should set simple span") {
-            try! viewimpl.setProp(key: "span", value: JsonValue.Object([
-                "text": JsonValue.String("Hello")
-                ]))
+        func run(_ text: String, action: String? = nil) -> JsonValue {
+            var run: [String: JsonValue] = [
+                "text": JsonValue.String(text),
+                "font-weight": JsonValue.String("regular"),
+                "font-style": JsonValue.String("normal"),
+                "font-size": JsonValue.Object(["unit": JsonValue.String("points"), "value": JsonValue.Number(12)]),
+                "font-color": JsonValue.String("#ff000000"),
+                "text-decoration": JsonValue.String("none"),
+                ]
+            if let action = action {
+                run["action"] = JsonValue.Object(["action": JsonValue.String(action)])
+            }
+            return JsonValue.Object(run)
+        }
+        
+        it("should set simple runs") {
+            try! viewimpl.setProp(key: "runs", value: JsonValue.Array([run("Hello")]))
             expect(viewimpl.text.string).to(equal("Hello"))
         }
         
-        it("should set complex span") {
-            try! viewimpl.setProp(key: "span", value: JsonValue.Object([
-                "text": JsonValue.Array([
-                    JsonValue.Object(["text": JsonValue.String("Hello")]),
-                    JsonValue.Object(["text": JsonValue.String(" ")]),
-                    JsonValue.Object(["text": JsonValue.String("world!")])
-                    ])
-                ]))
+        it("should set multiple runs") {
+            try! viewimpl.setProp(key: "runs", value: JsonValue.Array([run("Hello"), run(" "), run("world!")]))
             expect(viewimpl.text.string).to(equal("Hello world!"))
         }
         
         it("should set simple tap handler") {
-            try! viewimpl.setProp(key: "span", value: JsonValue.Object([
-                "text": JsonValue.String("Hello world!"),
-                "on-click": JsonValue.Object(["action": JsonValue.String("example-action")])
-                ]))
-            
+            try! viewimpl.setProp(key: "runs", value: JsonValue.Array([run("Hello world!", action: "example-action")]))
             expect(viewimpl.tapEvents.count).to(equal(1))
         }
         
-        it("should set complex tap handler") {
-            try! viewimpl.setProp(key: "span", value: JsonValue.Object([
-                "text": JsonValue.Array([
-                    JsonValue.Object([
-                        "text": JsonValue.String("Hello"),
-                        "on-click": JsonValue.Object(["action": JsonValue.String("example-action")])
-                        ]),
-                    JsonValue.Object(["text": JsonValue.String(" ")]),
-                    JsonValue.Object([
-                        "text": JsonValue.String("world!"),
-                        "on-click": JsonValue.Object(["action": JsonValue.String("example-action")])
-                        ]),
-                    ])
+        it("should set tap handlers on runs") {
+            try! viewimpl.setProp(key: "runs", value: JsonValue.Array([
+                run("Hello", action: "example-action"),
+                run(" "),
+                run("world!", action: "example-action"),
                 ]))
             
             let result = viewimpl.tapEvents
@@ -93,28 +88,5 @@ class TextViewImplSpec: QuickSpec {
             expect(result[1].range.location).to(equal("Hello ".count))
             expect(result[1].range.length).to(equal("world!".count))
         }
-        
-        it("should set tap handler on nested text") {
-            try! viewimpl.setProp(key: "span", value: JsonValue.Object([
-                "text": JsonValue.Array([
-                    JsonValue.Object(["text": JsonValue.String("Hello")]),
-                    JsonValue.Object(["text": JsonValue.String(" ")]),
-                    JsonValue.Object([
-                        "text": JsonValue.Array([
-                            JsonValue.Object(["text": JsonValue.String("world")]),
-                            JsonValue.Object(["text": JsonValue.String("!")]),
-                            ]),
-                        "on-click": JsonValue.Object(["action": JsonValue.String("example-action")])
-                        ])
-                    ])
-                ]))
-            
-            let result = viewimpl.tapEvents
-            
-            expect(result.count).to(equal(1))
-            
-            expect(result[0].range.location).to(equal("Hello ".count))
-            expect(result[0].range.length).to(equal("world!".count))
-        }
     }
 }
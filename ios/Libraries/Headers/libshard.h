@@ -2,12 +2,74 @@
 #include <stdlib.h>
 #include <stdbool.h>
 
+typedef enum {
+  IOSTransitionPush,
+  IOSTransitionPop,
+  IOSTransitionReplace,
+} IOSTransition;
+
+typedef enum {
+  IOSValueNull,
+  IOSValueBoolean,
+  IOSValueNumber,
+  IOSValueString,
+  IOSValueObject,
+  IOSValueArray,
+} IOSValueType;
+
+typedef struct IOSNavigator IOSNavigator;
+
+/**
+ * The response to a request of an `IOSTransport`, filled in by `shard_response_set`.
+ */
+typedef struct IOSResponse IOSResponse;
+
 typedef struct IOSViewManager IOSViewManager;
 
+typedef struct {
+  float red;
+  float green;
+  float blue;
+  float alpha;
+} IOSColor;
+
+typedef struct {
+  const uint8_t *ptr;
+  uintptr_t len;
+} IOSString;
+
+/**
+ * Handle to a JSON value. Handles passed to Swift are borrowed and only valid for the duration
+ * of the call they are passed to. Handles built with the `shard_value_new_*` functions are owned
+ * by Swift until they are handed to a function which takes them over.
+ */
+typedef struct {
+  const void *value_ptr;
+} IOSValue;
+
 typedef struct {
   void *root_ptr;
 } IOSRoot;
 
+typedef struct {
+  const char *locale;
+  bool dark_mode;
+} IOSRenderContext;
+
+typedef struct {
+  float start;
+  float end;
+  float top;
+  float bottom;
+} CRect;
+
+typedef struct {
+  IOSString kind;
+  IOSString id;
+  CRect frame;
+  const void *view;
+} IOSNode;
+
 typedef struct {
   float width;
   float height;
@@ -16,31 +78,286 @@ typedef struct {
 typedef struct {
   const void *swift_ptr;
   void (*set_frame)(const void*, float, float, float, float, const char**);
-  void (*set_prop)(const void*, const char*, const char*, const char**);
+  void (*set_prop)(const void*, const char*, IOSValue, const char**);
   void (*add_child)(const void*, const void*, const char**);
+  void (*remove_child)(const void*, const void*, const char**);
+  void (*insert_child)(const void*, const void*, uintptr_t, const char**);
   CSize (*measure)(const void*, const CSize*, const char**);
 } IOSView;
 
+IOSColor shard_color_parse(const char *color, const char **error);
+
+/**
+ * Load a document through a `DocumentLoader` caching in `cache_dir`, with `fetch` making the
+ * requests. Returns the document as JSON, to be freed with `shard_string_free`.
+ */
+char *shard_load_document(const char *url,
+                          const char *cache_dir,
+                          const void *swift_ptr,
+                          void (*fetch)(const void*,
+                                        const char*,
+                                        const char*,
+                                        IOSResponse*,
+                                        const char**),
+                          const char **error);
+
+/**
+ * Like `shard_load_document`, but a stale cached copy is passed to `on_document` before
+ * revalidating so it can be shown right away, and the document again only if it changed. The
+ * document passed to `on_document` is only valid during the call.
+ */
+void shard_load_document_stale_while_revalidate(const char *url,
+                                                const char *cache_dir,
+                                                const void *swift_ptr,
+                                                void (*fetch)(const void*,
+                                                              const char*,
+                                                              const char*,
+                                                              IOSResponse*,
+                                                              const char**),
+                                                void (*on_document)(const void*, IOSString),
+                                                const char **error);
+
+/**
+ * Run an action on the top root, taking over `action`. Navigation actions in a chain run after the rest of it.
+ */
+void shard_navigator_dispatch(IOSNavigator *navigator,
+                              IOSValue action,
+                              const char **error);
+
+void shard_navigator_free(IOSNavigator *navigator);
+
+IOSNavigator *shard_navigator_new(const void *swift_ptr,
+                                  IOSRoot (*render)(const void*, IOSValue, const char**),
+                                  void (*transition)(const void*,
+                                                     IOSTransition,
+                                                     IOSRoot,
+                                                     IOSRoot,
+                                                     float,
+                                                     float,
+                                                     const char**),
+                                  void (*on_action)(const void*, const char*, IOSValue, const char**));
+
+/**
+ * Pop up to `count` roots, always keeping the first one. Returns false if there was nothing to pop.
+ */
+bool shard_navigator_pop(IOSNavigator *navigator, uintptr_t count, const char **error);
+
+/**
+ * Render `target`, a URL or an inline document, and show it on top. Takes over `target`.
+ */
+void shard_navigator_push(IOSNavigator *navigator, IOSValue target, const char **error);
+
+/**
+ * Render `target`, a URL or an inline document, and show it in place of the top. Takes over `target`.
+ */
+void shard_navigator_replace(IOSNavigator *navigator,
+                             IOSValue target,
+                             const char **error);
+
+void shard_navigator_set_scroll_position(IOSNavigator *navigator, float x, float y);
+
 IOSRoot shard_render(IOSViewManager *view_manager,
                      const void *context,
+                     IOSRenderContext render_context,
                      const char *json,
                      const char **error);
 
+IOSRoot shard_render_bytes(IOSViewManager *view_manager,
+                           const void *context,
+                           IOSRenderContext render_context,
+                           const uint8_t *bytes,
+                           uintptr_t len,
+                           const char *content_type,
+                           const char **error);
+
+/**
+ * Fill in the response to a request of a transport. The body is decoded right away, so none of
+ * the arguments need to outlive the call.
+ */
+void shard_response_set(IOSResponse *response,
+                        uint16_t status,
+                        const uint8_t *body,
+                        uintptr_t len,
+                        const char *content_type,
+                        const char *etag,
+                        const char *cache_control);
+
+/**
+ * Apply a JSON Patch to the document of the root, taking over `patch`. A patch which fails to apply
+ * or render leaves the views untouched.
+ */
+void shard_root_apply_patch(IOSRoot root,
+                            IOSViewManager *view_manager,
+                            const void *context,
+                            IOSValue patch,
+                            const char **error);
+
+/**
+ * Run the action encoded as JSON in `action`, calling `on_action` for those not built into the core.
+ */
+void shard_root_dispatch(IOSRoot root,
+                         const char *action,
+                         const void *swift_ptr,
+                         void (*on_action)(const void*, const char*, IOSValue, const char**),
+                         const char **error);
+
+/**
+ * Write the frame of the node with `id` relative to the root into `frame`. Returns false if
+ * there is no such node.
+ */
+bool shard_root_frame_of(IOSRoot root, const char *id, CRect *frame);
+
 void shard_root_free(IOSRoot root);
 
 const void *shard_root_get_view(IOSRoot root);
 
+/**
+ * Call `on_node` with every node under a point, deepest first.
+ */
+void shard_root_hit_test(IOSRoot root,
+                         float x,
+                         float y,
+                         const void *swift_ptr,
+                         void (*on_node)(const void*, IOSNode));
+
+/**
+ * Supply the JSON of a fragment, replacing the placeholders of lazy includes of `id` with it.
+ */
+void shard_root_load_fragment(IOSRoot root,
+                              IOSViewManager *view_manager,
+                              const void *context,
+                              const char *id,
+                              const char *json,
+                              const char **error);
+
 void shard_root_measure(IOSRoot root, CSize size, const char **error);
 
+/**
+ * The id of a fragment of a lazy include still showing its placeholder, valid until the root
+ * changes.
+ */
+IOSString shard_root_pending_fragment(IOSRoot root, uintptr_t index);
+
+uintptr_t shard_root_pending_fragments_len(IOSRoot root);
+
+/**
+ * Call `on_node` with every node matching `selector` in document order. The strings of a node
+ * are only valid during the call.
+ */
+void shard_root_query(IOSRoot root,
+                      const char *selector,
+                      const void *swift_ptr,
+                      void (*on_node)(const void*, IOSNode),
+                      const char **error);
+
+void shard_root_set_dark_mode(IOSRoot root, bool dark_mode, const char **error);
+
+/**
+ * Override a prop of the node with the given id, taking over `value`.
+ */
+void shard_root_set_prop(IOSRoot root,
+                         const char *id,
+                         const char *key,
+                         IOSValue value,
+                         const char **error);
+
+/**
+ * Set the value at a dot separated path of the state, taking over `value`.
+ */
+void shard_root_set_state(IOSRoot root, const char *path, IOSValue value, const char **error);
+
+/**
+ * The current state of the root, valid until the root changes.
+ */
+IOSValue shard_root_state(IOSRoot root);
+
+/**
+ * The view of the node with `id`, or null if there is none or the node was flattened.
+ */
+const void *shard_root_view_by_id(IOSRoot root, const char *id);
+
+/**
+ * The message of a warning, valid until the root is freed.
+ */
+IOSString shard_root_warning_message(IOSRoot root, uintptr_t index);
+
+/**
+ * The JSON pointer of a warning, valid until the root is freed.
+ */
+IOSString shard_root_warning_path(IOSRoot root, uintptr_t index);
+
+uintptr_t shard_root_warnings_len(IOSRoot root);
+
+void shard_string_free(char *string);
+
+bool shard_value_as_bool(IOSValue value);
+
+double shard_value_as_number(IOSValue value);
+
+IOSString shard_value_as_string(IOSValue value);
+
+IOSValue shard_value_at(IOSValue value, uintptr_t index);
+
+/**
+ * Free a value built with the `shard_value_new_*` functions which was not taken over.
+ */
+void shard_value_free(IOSValue value);
+
+/**
+ * Set `key` of an object built with `shard_value_new_object`, taking over `item`.
+ */
+void shard_value_insert(IOSValue object, const char *key, IOSValue item);
+
+IOSString shard_value_key_at(IOSValue value, uintptr_t index);
+
+uintptr_t shard_value_len(IOSValue value);
+
+IOSValue shard_value_new_array(void);
+
+IOSValue shard_value_new_bool(bool value);
+
+IOSValue shard_value_new_null(void);
+
+IOSValue shard_value_new_number(double value);
+
+IOSValue shard_value_new_object(void);
+
+IOSValue shard_value_new_string(const char *value);
+
+/**
+ * Append `item` to an array built with `shard_value_new_array`, taking over `item`.
+ * Returns false, dropping `item`, when `array` is not an array.
+ */
+bool shard_value_push(IOSValue array, IOSValue item);
+
+IOSValueType shard_value_type(IOSValue value);
+
+IOSValue shard_value_value_at(IOSValue value, uintptr_t index);
+
 void shard_view_free(IOSView *view);
 
 void shard_view_manager_free(IOSViewManager *view_manager);
 
 const IOSViewManager *shard_view_manager_new(const void *swift_ptr,
-                                             IOSView *(*create_view)(const void*, const void*, const char*, const char**));
+                                             IOSView *(*create_view)(const void*,
+                                                                     const void*,
+                                                                     const char*,
+                                                                     const char**));
+
+/**
+ * Resolve the fragments included by roots rendered from now on with `resolve`, dropping the
+ * fragments resolved so far.
+ */
+void shard_view_manager_set_fragment_resolver(IOSViewManager *view_manager,
+                                              const void *swift_ptr,
+                                              const char *(*resolve)(const void*,
+                                                                     const char*,
+                                                                     const char**));
 
 IOSView *shard_view_new(const void *swift_ptr,
                         void (*set_frame)(const void*, float, float, float, float, const char**),
-                        void (*set_prop)(const void*, const char*, const char*, const char**),
+                        void (*set_prop)(const void*, const char*, IOSValue, const char**),
                         void (*add_child)(const void*, const void*, const char**),
+                        void (*remove_child)(const void*, const void*, const char**),
+                        void (*insert_child)(const void*, const void*, uintptr_t, const char**),
                         CSize (*measure)(const void*, const CSize*, const char**));
@@ -50,18 +50,19 @@ class BaseViewImpl: ShardViewImpl {
             default: self.borderRadius = 0
             }
         case "on-click":
-            let value = try value.asObject()
-            let action = try value["action"]!.asString()
-            
-            self.tapHandler = { sender -> () in
-                switch sender.state {
-                case .began:
-                    self.delegate?.setState(.Pressed)
-                case .ended:
-                    self.delegate?.setState(.Default)
-                    self.context.dispatch(action: action, value: value["value"])
-                default: ()
+            switch value {
+            case .Object, .Array:
+                self.tapHandler = { sender -> () in
+                    switch sender.state {
+                    case .began:
+                        self.delegate?.setState(.Pressed)
+                    case .ended:
+                        self.delegate?.setState(.Default)
+                        self.context.dispatch(value)
+                    default: ()
+                    }
                 }
+            default: self.tapHandler = nil
             }
             
         default: ()
@@ -9,7 +9,7 @@ import Foundation
 
 public enum JsonValue {
     case Null
-    case Number(Float)
+    case Number(Double)
     case String(String)
     case Boolean(Bool)
     case Object([String: JsonValue])
@@ -18,9 +18,9 @@ public enum JsonValue {
     public init(_ value: Any?) {
         switch value {
         case nil: self = .Null
-        case let value as Int: self = .Number(Float(value))
-        case let value as Float: self = .Number(Float(value))
-        case let value as Double: self = .Number(Float(value))
+        case let value as Int: self = .Number(Double(value))
+        case let value as Float: self = .Number(Double(value))
+        case let value as Double: self = .Number(value)
         case let value as Bool: self = .Boolean(value)
         case let value as String: self = .String(value)
         case let value as [String: Any]: self = .Object(JsonValue.parse(value))
@@ -29,6 +29,45 @@ public enum JsonValue {
         }
     }
     
+    internal init(_ value: IOSValue) {
+        switch shard_value_type(value) {
+        case IOSValueBoolean: self = .Boolean(shard_value_as_bool(value))
+        case IOSValueNumber: self = .Number(shard_value_as_number(value))
+        case IOSValueString: self = .String(String(shard_value_as_string(value)))
+        case IOSValueObject:
+            var object: [String: JsonValue] = [:]
+            for i in 0..<shard_value_len(value) {
+                object[String(shard_value_key_at(value, i))] = JsonValue(shard_value_value_at(value, i))
+            }
+            self = .Object(object)
+        case IOSValueArray:
+            self = .Array((0..<shard_value_len(value)).map({ JsonValue(shard_value_at(value, $0)) }))
+        default: self = .Null
+        }
+    }
+    
+    /// A value owned by the caller until it is handed to a function of the core which takes it over.
+    internal func toIOSValue() -> IOSValue {
+        switch self {
+        case .Null: return shard_value_new_null()
+        case let .Number(value): return shard_value_new_number(value)
+        case let .String(value): return shard_value_new_string((value as NSString).utf8String)
+        case let .Boolean(value): return shard_value_new_bool(value)
+        case let .Object(values):
+            let object = shard_value_new_object()
+            for (key, value) in values {
+                shard_value_insert(object, (key as NSString).utf8String, value.toIOSValue())
+            }
+            return object
+        case let .Array(values):
+            let array = shard_value_new_array()
+            for value in values {
+                shard_value_push(array, value.toIOSValue())
+            }
+            return array
+        }
+    }
+    
     private static func parse(_ json: [String: Any?]) -> [String: JsonValue] {
         return json.mapValues({ JsonValue($0) })
     }
@@ -47,7 +86,7 @@ public enum JsonValue {
         }
     }
     
-    public func asNumber() throws -> Float {
+    public func asNumber() throws -> Double {
         switch self {
         case .Number(let value): return value
         case let value: throw "\(value) is not a number"
@@ -114,7 +153,7 @@ internal extension Dictionary where Key == String, Value == JsonValue {
         
         let value: Float = try get("value") {
             switch $0 {
-            case .Number(let value): return value
+            case .Number(let value): return Float(value)
             case let value: throw "Unexpected value: \(value)"
             }
         }
@@ -132,11 +171,11 @@ internal extension Dictionary where Key == String, Value == JsonValue {
 internal extension JsonValue {
     func asColor() throws -> ShardColor {
         switch self {
-        case .String(let value): return ShardColor(default: try UIColor(hex: value), pressed: nil)
+        case .String(let value): return ShardColor(default: try UIColor(viewColor: value), pressed: nil)
         case .Object(let value):
             return ShardColor(
-                default: try UIColor(hex: value["default"]!.asString()),
-                pressed: value["pressed"] != nil ? try UIColor(hex: value["pressed"]!.asString()) : nil)
+                default: try UIColor(viewColor: value["default"]!.asString()),
+                pressed: value["pressed"] != nil ? try UIColor(viewColor: value["pressed"]!.asString()) : nil)
         case let value: throw "Unexpected value: \(value)"
         }
     }
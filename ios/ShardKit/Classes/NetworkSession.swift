@@ -9,6 +9,14 @@ import Foundation
 
 public protocol NetworkSession {
     func loadData(from url: URL, onComplete: @escaping (Data?, URLResponse?, Error?) -> Void)
+    func loadData(with request: URLRequest, onComplete: @escaping (Data?, URLResponse?, Error?) -> Void)
+}
+
+extension NetworkSession {
+    /// Sessions which only load urls ignore the headers of requests, such as `If-None-Match`.
+    public func loadData(with request: URLRequest, onComplete: @escaping (Data?, URLResponse?, Error?) -> Void) {
+        loadData(from: request.url!, onComplete: onComplete)
+    }
 }
 
 extension URLSession: NetworkSession {
@@ -19,4 +27,12 @@ extension URLSession: NetworkSession {
         
         task.resume()
     }
+    
+    public func loadData(with request: URLRequest, onComplete: @escaping (Data?, URLResponse?, Error?) -> Void) {
+        let task = dataTask(with: request) { (data, response, error) in
+            onComplete(data, response, error)
+        }
+        
+        task.resume()
+    }
 }
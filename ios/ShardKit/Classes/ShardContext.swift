@@ -13,6 +13,18 @@ internal protocol ShardContextDelegate {
 
 public class ShardContext {
     internal var delegate: ShardContextDelegate? = nil
+    internal weak var root: ShardRoot? = nil
+    internal weak var navigator: ShardNavigator? = nil
+    
+    /// Run an action declared in the document, such as the value of an on-click prop. The core
+    /// performs built-in actions and calls back into `dispatch(action:value:)` for the others.
+    public func dispatch(_ action: JsonValue) {
+        if let navigator = navigator {
+            navigator.dispatch(action)
+        } else {
+            root?.dispatch(action)
+        }
+    }
     
     public func dispatch(action: String, value: JsonValue?) {
         delegate?.onActionDispatched(action: action, value: value)
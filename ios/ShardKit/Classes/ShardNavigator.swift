@@ -0,0 +1,187 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+ 
+import UIKit
+
+private func shard_navigator_render(
+    _ self_ptr: UnsafeRawPointer?,
+    _ target: IOSValue,
+    _ err: UnsafeMutablePointer<UnsafePointer<Int8>?>?) -> IOSRoot {
+    
+    let navigator: ShardNavigator = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    
+    switch navigator.render(JsonValue(target)) {
+    case .Success(let root): return root.root
+    case .Failure(let error):
+        err?.initialize(to: (error.localizedDescription as NSString).utf8String)
+        return IOSRoot(root_ptr: nil)
+    }
+}
+
+private func shard_navigator_transition(
+    _ self_ptr: UnsafeRawPointer?,
+    _ transition: IOSTransition,
+    _ from: IOSRoot,
+    _ to: IOSRoot,
+    _ scrollX: Float,
+    _ scrollY: Float,
+    _ err: UnsafeMutablePointer<UnsafePointer<Int8>?>?) {
+    
+    let navigator: ShardNavigator = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    let scrollPosition = CGPoint(x: CGFloat(scrollX), y: CGFloat(scrollY))
+    
+    switch transition {
+    case IOSTransitionPush: navigator.transition(.Push, from, to, scrollPosition)
+    case IOSTransitionPop: navigator.transition(.Pop, from, to, scrollPosition)
+    default: navigator.transition(.Replace, from, to, scrollPosition)
+    }
+}
+
+private func shard_navigator_on_action(
+    _ self_ptr: UnsafeRawPointer?,
+    _ action: UnsafePointer<Int8>?,
+    _ value: IOSValue,
+    _ err: UnsafeMutablePointer<UnsafePointer<Int8>?>?) {
+    
+    let navigator: ShardNavigator = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    let value = JsonValue(value)
+    
+    switch value {
+    case .Null: navigator.top?.context.dispatch(action: String(cString: action!), value: nil)
+    default: navigator.top?.context.dispatch(action: String(cString: action!), value: value)
+    }
+}
+
+public enum ShardTransition {
+    case Push
+    case Pop
+    case Replace
+}
+
+public protocol ShardNavigatorHost: AnyObject {
+    /// Show `to` in place of `from`, which is nil for the first root. When popping, `to` should
+    /// be scrolled back to `scrollPosition`.
+    func transition(_ transition: ShardTransition, from: ShardRoot?, to: ShardRoot, scrollPosition: CGPoint)
+}
+
+/// A stack of roots driven by `push`, `pop` and `replace` actions in the documents, or by the
+/// methods of the same names. Targets are URLs or inline documents. Covered roots keep their
+/// state and scroll position, the host only animates between roots. Roots are owned by the
+/// navigator and must not be used after it is gone.
+public class ShardNavigator {
+    public weak var host: ShardNavigatorHost?
+    
+    private var rust_ptr: OpaquePointer! = nil
+    private var roots: [ShardRoot] = []
+    private var rendered: ShardRoot? = nil
+    
+    public init(host: ShardNavigatorHost) {
+        self.host = host
+        let self_ptr = Unmanaged.passUnretained(self).toOpaque()
+        self.rust_ptr = shard_navigator_new(
+            self_ptr,
+            shard_navigator_render,
+            shard_navigator_transition,
+            shard_navigator_on_action)
+    }
+    
+    deinit {
+        shard_navigator_free(rust_ptr)
+    }
+    
+    public var top: ShardRoot? {
+        return roots.last
+    }
+    
+    public func push(_ target: JsonValue) throws {
+        var error: UnsafePointer<Int8>? = nil
+        shard_navigator_push(rust_ptr, target.toIOSValue(), &error)
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+    }
+    
+    public func replace(_ target: JsonValue) throws {
+        var error: UnsafePointer<Int8>? = nil
+        shard_navigator_replace(rust_ptr, target.toIOSValue(), &error)
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+    }
+    
+    /// Pop up to count roots, always keeping the first one. Returns false if there was nothing
+    /// to pop, in which case the host may want to close the navigator itself.
+    public func pop(count: Int = 1) throws -> Bool {
+        var error: UnsafePointer<Int8>? = nil
+        let popped = shard_navigator_pop(rust_ptr, UInt(max(count, 0)), &error)
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+        
+        return popped
+    }
+    
+    /// Run an action on the top root. Navigation actions in a chain run after the rest of it.
+    public func dispatch(_ action: JsonValue) {
+        var error: UnsafePointer<Int8>? = nil
+        shard_navigator_dispatch(rust_ptr, action.toIOSValue(), &error)
+        
+        #if DEBUG
+        if let error = error {
+            print("Shard: could not dispatch \(action.toString()): \(String(cString: error))")
+        }
+        #endif
+        
+        top?.relayout()
+    }
+    
+    /// Record where the top root is scrolled to, to restore it when it is revealed again.
+    public func scrolled(to position: CGPoint) {
+        shard_navigator_set_scroll_position(rust_ptr, Float(position.x), Float(position.y))
+    }
+    
+    fileprivate func render(_ target: JsonValue) -> Result<ShardRoot> {
+        let result: Result<ShardRoot>
+        switch target {
+        case .String(let url):
+            guard let url = URL(string: url) else { return Result.Failure("Invalid url: \(url)") }
+            result = ShardViewManager.shared.loadUrlBlocking(url: url)
+        default:
+            result = ShardViewManager.shared.loadJson(target)
+        }
+        
+        if case .Success(let root) = result {
+            root.owned = false
+            root.context.navigator = self
+            rendered = root
+        }
+        
+        return result
+    }
+    
+    fileprivate func transition(_ transition: ShardTransition, _ from: IOSRoot, _ to: IOSRoot, _ scrollPosition: CGPoint) {
+        let fromRoot = roots.last(where: { $0.root.root_ptr == from.root_ptr })
+        let toRoot = roots.last(where: { $0.root.root_ptr == to.root_ptr }) ?? rendered!
+        rendered = nil
+        
+        host?.transition(transition, from: fromRoot, to: toRoot, scrollPosition: scrollPosition)
+        
+        switch transition {
+        case .Push: roots.append(toRoot)
+        case .Replace:
+            _ = roots.popLast()
+            roots.append(toRoot)
+        case .Pop:
+            while roots.last !== toRoot {
+                roots.removeLast()
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+/**
+ * Copyright (c) Visly Inc.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+import UIKit
+
+/// A node matched by `ShardRoot.query`. The frame is relative to the root. Nodes which were
+/// flattened into their parent have no view.
+public struct ShardNode {
+    public let kind: String
+    public let id: String?
+    public let frame: CGRect
+    public let view: UIView?
+    
+    internal init(_ node: IOSNode) {
+        self.kind = String(node.kind)
+        self.id = node.id.ptr == nil ? nil : String(node.id)
+        self.frame = CGRect(
+            x: CGFloat(node.frame.start),
+            y: CGFloat(node.frame.top),
+            width: CGFloat(node.frame.end - node.frame.start),
+            height: CGFloat(node.frame.bottom - node.frame.top))
+        
+        if let ptr = node.view {
+            let shardView: ShardView = Unmanaged.fromOpaque(ptr).takeUnretainedValue()
+            self.view = shardView.view
+        } else {
+            self.view = nil
+        }
+    }
+}
@@ -7,17 +7,188 @@
 
 import UIKit
 
+private func shard_root_on_action(
+    _ context_ptr: UnsafeRawPointer?,
+    _ action: UnsafePointer<Int8>?,
+    _ value: IOSValue,
+    _ err: UnsafeMutablePointer<UnsafePointer<Int8>?>?) {
+    
+    let context: ShardContext = Unmanaged.fromOpaque(UnsafeRawPointer(context_ptr!)).takeUnretainedValue()
+    let value = JsonValue(value)
+    
+    switch value {
+    case .Null: context.dispatch(action: String(cString: action!), value: nil)
+    default: context.dispatch(action: String(cString: action!), value: value)
+    }
+}
+
 public class ShardRoot {
     internal let context: ShardContext
-    private let root: IOSRoot
+    internal let root: IOSRoot
+    private var lastLayout: (width: CGFloat?, height: CGFloat?)? = nil
+    
+    // Roots shown by a ShardNavigator are owned by its stack
+    internal var owned = true
     
     internal init(_ context: ShardContext, _ root: IOSRoot) {
         self.context = context
         self.root = root
+        context.root = self
     }
     
     deinit {
-        shard_root_free(self.root)
+        if owned {
+            shard_root_free(self.root)
+        }
+    }
+    
+    /// Problems in the document which did not stop it from rendering, each prefixed with the
+    /// JSON pointer of the offending value.
+    public lazy var warnings: [String] = {
+        return (0..<shard_root_warnings_len(root)).map { i in
+            "\(String(shard_root_warning_path(root, i))): \(String(shard_root_warning_message(root, i)))"
+        }
+    }()
+    
+    /// The fragments of lazy includes which are still showing their placeholder, in document order.
+    public var pendingFragments: [String] {
+        return (0..<shard_root_pending_fragments_len(root)).map { i in
+            String(shard_root_pending_fragment(root, i))
+        }
+    }
+    
+    /// Supply the JSON of a fragment, replacing the placeholders of lazy includes of id with it.
+    /// The fragment is cached so later documents include it directly.
+    public func loadFragment(id: String, json: String) throws {
+        var error: UnsafePointer<Int8>? = nil
+        let context_ptr = Unmanaged.passUnretained(context).toOpaque()
+        shard_root_load_fragment(
+            root,
+            ShardViewManager.shared.rust_ptr,
+            context_ptr,
+            (id as NSString).utf8String,
+            (json as NSString).utf8String,
+            &error)
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+        
+        relayout()
+    }
+    
+    /// The view of the node with the given id, or nil if there is no such node or it was
+    /// flattened into its parent.
+    public func view(id: String) -> UIView? {
+        guard let ptr = shard_root_view_by_id(root, (id as NSString).utf8String) else { return nil }
+        let shardView: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(ptr)).takeUnretainedValue()
+        return shardView.view
+    }
+    
+    /// The frame of the node with the given id relative to the root.
+    public func frame(id: String) -> CGRect? {
+        var frame = CRect(start: 0, end: 0, top: 0, bottom: 0)
+        guard shard_root_frame_of(root, (id as NSString).utf8String, &frame) else { return nil }
+        return CGRect(
+            x: CGFloat(frame.start),
+            y: CGFloat(frame.top),
+            width: CGFloat(frame.end - frame.start),
+            height: CGFloat(frame.bottom - frame.top))
+    }
+    
+    /// The nodes matching a selector such as `list > .item[selected=true] text`, in document order.
+    public func query(_ selector: String) throws -> [ShardNode] {
+        var nodes: [ShardNode] = []
+        var error: UnsafePointer<Int8>? = nil
+        
+        withUnsafeMutablePointer(to: &nodes) { ptr in
+            shard_root_query(root, (selector as NSString).utf8String, ptr, { ptr, node in
+                UnsafeMutableRawPointer(mutating: ptr!).assumingMemoryBound(to: [ShardNode].self).pointee.append(ShardNode(node))
+            }, &error)
+        }
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+        
+        return nodes
+    }
+    
+    /// The nodes under a point relative to the root, deepest first.
+    public func hitTest(_ point: CGPoint) -> [ShardNode] {
+        var nodes: [ShardNode] = []
+        
+        withUnsafeMutablePointer(to: &nodes) { ptr in
+            shard_root_hit_test(root, Float(point.x), Float(point.y), ptr, { ptr, node in
+                UnsafeMutableRawPointer(mutating: ptr!).assumingMemoryBound(to: [ShardNode].self).pointee.append(ShardNode(node))
+            })
+        }
+        
+        return nodes
+    }
+    
+    /// Run an action, or an array of actions, declared in the document. Actions which are not
+    /// built into the core are passed to the handlers registered on the root view.
+    public func dispatch(_ action: JsonValue) {
+        var error: UnsafePointer<Int8>? = nil
+        let context_ptr = Unmanaged.passUnretained(context).toOpaque()
+        shard_root_dispatch(root, (action.toString() as NSString).utf8String, context_ptr, shard_root_on_action, &error)
+        
+        #if DEBUG
+        if let error = error {
+            print("Shard: could not dispatch \(action.toString()): \(String(cString: error))")
+        }
+        #endif
+        
+        relayout()
+    }
+    
+    /// Override a prop of the node with the given id. Call measure afterwards to lay out the
+    /// root again.
+    public func setProp(id: String, key: String, value: JsonValue) throws {
+        var error: UnsafePointer<Int8>? = nil
+        shard_root_set_prop(root, (id as NSString).utf8String, (key as NSString).utf8String, value.toIOSValue(), &error)
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+        
+        if let ptr = shard_root_view_by_id(root, (id as NSString).utf8String) {
+            let shardView: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(ptr)).takeUnretainedValue()
+            shardView.impl.bindView(shardView.view)
+        }
+    }
+    
+    /// The current state of the document, as declared in its `state` section and changed by
+    /// actions since.
+    public var state: JsonValue {
+        return JsonValue(shard_root_state(root))
+    }
+    
+    /// Set the value at a dot separated path of the state, such as `cart.quantity`, and update
+    /// the props and conditional nodes bound to it.
+    public func setState(path: String, value: JsonValue) throws {
+        var error: UnsafePointer<Int8>? = nil
+        shard_root_set_state(root, (path as NSString).utf8String, value.toIOSValue(), &error)
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+        
+        relayout()
+    }
+    
+    /// Apply a JSON Patch to the document, updating nodes in place where possible. A patch which
+    /// fails to apply or render leaves the views untouched.
+    public func applyPatch(_ patch: JsonValue) throws {
+        var error: UnsafePointer<Int8>? = nil
+        let context_ptr = Unmanaged.passUnretained(context).toOpaque()
+        shard_root_apply_patch(root, ShardViewManager.shared.rust_ptr, context_ptr, patch.toIOSValue(), &error)
+        relayout()
+        
+        if let error = error {
+            throw String(cString: error)
+        }
     }
     
     public func measure(width: CGFloat?, height: CGFloat?) -> CGSize {
@@ -26,7 +197,29 @@ public class ShardRoot {
         return rootView.size
     }
     
+    public func setTheme(dark: Bool) {
+        shard_root_set_dark_mode(root, dark, nil)
+        let rootView: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(shard_root_get_view(root)!)).takeUnretainedValue()
+        
+        func rebind(_ root: ShardView) {
+            root.impl.bindView(root.view)
+            for child in root.children {
+                rebind(child)
+            }
+        }
+        
+        rebind(rootView)
+    }
+    
+    /// Lay out the root again with the size it was last laid out with, if any.
+    internal func relayout() {
+        if let lastLayout = lastLayout {
+            _ = layout(width: lastLayout.width, height: lastLayout.height)
+        }
+    }
+    
     func layout(width: CGFloat?, height: CGFloat?) -> CGSize {
+        lastLayout = (width, height)
         shard_root_measure(root, CSize(width: Float(width ?? CGFloat.nan), height: Float(height ?? CGFloat.nan)), nil)
         let rootView: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(shard_root_get_view(root)!)).takeUnretainedValue()
         
@@ -44,20 +237,6 @@ public class ShardRoot {
     
     internal lazy var view: UIView = {
         let rootView: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(shard_root_get_view(root)!)).takeUnretainedValue()
-        
-        func createViewHierarchy(_ root: ShardView) -> UIView {
-            let view = root.view
-            if root.impl is FlexboxViewImpl {
-                for child in root.children {
-                    view.addSubview(createViewHierarchy(child))
-                }
-            } else if root.children.count > 0 {
-                assertionFailure("Only flexbox is allowed to specify children")
-            }
-            
-            return view
-        }
-        
-        return createViewHierarchy(rootView)
+        return rootView.mount()
     }()
 }
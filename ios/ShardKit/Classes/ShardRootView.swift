@@ -42,7 +42,11 @@ public class ShardRootView: UIView, ShardContextDelegate {
     }
     
     func onActionDispatched(action: String, value: JsonValue?) {
-        actionHandlers[action]?(value)
+        if let handler = actionHandlers[action] {
+            handler(value)
+        } else if action == "open-url", case .String(let url)? = value, let url = URL(string: url) {
+            UIApplication.shared.open(url)
+        }
     }
     
     public func on(_ action: String, _ callback: @escaping (JsonValue?) -> ()) {
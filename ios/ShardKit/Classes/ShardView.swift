@@ -22,15 +22,14 @@ private func shard_view_set_frame(
 private func shard_view_set_prop(
     _ self_ptr: UnsafeRawPointer?,
     _ key: UnsafePointer<Int8>?,
-    _ value: UnsafePointer<Int8>?,
+    _ value: IOSValue,
     _ err: UnsafeMutablePointer<UnsafePointer<Int8>?>?) {
     
     let view: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
     let key = String(cString: key!)
-    let value = String(cString: value!)
     
     do {
-        try view.setProp(key, value)
+        try view.impl.setProp(key: key, value: JsonValue(value))
     } catch {
         let error_string = (error.localizedDescription as NSString).utf8String
         err?.initialize(to: error_string)
@@ -44,7 +43,28 @@ private func shard_view_add_child(
     
     let view: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
     let child: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(child_ptr!)).takeUnretainedValue()
-    view.children.append(child)
+    view.addChild(child)
+}
+
+private func shard_view_remove_child(
+    _ self_ptr: UnsafeRawPointer?,
+    _ child_ptr: UnsafeRawPointer?,
+    _ error: UnsafeMutablePointer<UnsafePointer<Int8>?>?) {
+    
+    let view: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    let child: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(child_ptr!)).takeUnretainedValue()
+    view.removeChild(child)
+}
+
+private func shard_view_insert_child(
+    _ self_ptr: UnsafeRawPointer?,
+    _ child_ptr: UnsafeRawPointer?,
+    _ index: UInt,
+    _ error: UnsafeMutablePointer<UnsafePointer<Int8>?>?) {
+    
+    let view: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    let child: ShardView = Unmanaged.fromOpaque(UnsafeRawPointer(child_ptr!)).takeUnretainedValue()
+    view.insertChild(child, at: Int(index))
 }
 
 private func shard_view_measure(
@@ -90,6 +110,8 @@ public class ShardView: ShardViewImplDelegate {
             shard_view_set_frame,
             shard_view_set_prop,
             shard_view_add_child,
+            shard_view_remove_child,
+            shard_view_insert_child,
             shard_view_measure)
     }
     
@@ -105,8 +127,49 @@ public class ShardView: ShardViewImplDelegate {
         return impl.createView()
     }()
     
-    internal func setProp(_ key: String, _ value: String) throws {
-        try impl.setProp(key: key, value: JsonValue(try JSONSerialization.jsonObject(with: value.data(using: .utf8)!, options: [.allowFragments])))
+    // Once the native hierarchy is built, children added and removed by the core are mirrored in it
+    private var mounted = false
+    
+    internal func mount() -> UIView {
+        if !mounted {
+            if impl is FlexboxViewImpl {
+                for child in children {
+                    view.addSubview(child.mount())
+                }
+            } else if children.count > 0 {
+                assertionFailure("Only flexbox is allowed to specify children")
+            }
+            
+            mounted = true
+        }
+        
+        return view
+    }
+    
+    internal func addChild(_ child: ShardView) {
+        children.append(child)
+        
+        if mounted {
+            assert(impl is FlexboxViewImpl, "Only flexbox is allowed to specify children")
+            view.addSubview(child.mount())
+        }
+    }
+    
+    internal func insertChild(_ child: ShardView, at index: Int) {
+        children.insert(child, at: index)
+        
+        if mounted {
+            assert(impl is FlexboxViewImpl, "Only flexbox is allowed to specify children")
+            view.insertSubview(child.mount(), at: index)
+        }
+    }
+    
+    internal func removeChild(_ child: ShardView) {
+        children.removeAll { $0 === child }
+        
+        if mounted {
+            child.view.removeFromSuperview()
+        }
     }
     
     internal func measure(_ size: CSize) -> CSize {
@@ -34,14 +34,124 @@ private func shard_view_manager_create_view(
     return view.rust_ptr
 }
 
+private func shard_view_manager_fetch(
+    _ self_ptr: UnsafeRawPointer?,
+    _ url: UnsafePointer<Int8>?,
+    _ etag: UnsafePointer<Int8>?,
+    _ response: OpaquePointer?,
+    _ err: UnsafeMutablePointer<UnsafePointer<Int8>?>?) {
+    
+    let request: DocumentRequest = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    
+    if let error = request.fetch(String(cString: url!), etag.map { String(cString: $0) }, response) {
+        err?.initialize(to: (error.localizedDescription as NSString).utf8String)
+    }
+}
+
+private func shard_view_manager_on_document(_ self_ptr: UnsafeRawPointer?, _ document: IOSString) {
+    let request: DocumentRequest = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    request.onDocument?(String(document))
+}
+
+/// A document being loaded by the core loader, which calls `fetch` for each request it makes.
+/// The loader only reports errors as strings, so the last one is kept to surface instead.
+/// Requests block the loading thread, which is never the main thread.
+private class DocumentRequest {
+    let session: NetworkSession
+    var error: Error? = nil
+    /// Called with each document the loader passes on when loading stale while revalidating.
+    var onDocument: ((String) -> Void)? = nil
+    
+    init(_ session: NetworkSession) {
+        self.session = session
+    }
+    
+    func fetch(_ url: String, _ etag: String?, _ response: OpaquePointer?) -> Error? {
+        guard let url = URL(string: url) else {
+            error = "Invalid url: \(url)"
+            return error
+        }
+        
+        var request = URLRequest(url: url)
+        request.setValue("application/cbor, application/msgpack, application/json", forHTTPHeaderField: "Accept")
+        if let etag = etag {
+            request.setValue(etag, forHTTPHeaderField: "If-None-Match")
+        }
+        
+        let semaphore = DispatchSemaphore(value: 0)
+        var loaded: (Data?, URLResponse?, Error?) = (nil, nil, nil)
+        session.loadData(with: request) { data, response, httpError in
+            loaded = (data, response, httpError)
+            semaphore.signal()
+        }
+        semaphore.wait()
+        
+        if let httpError = loaded.2 {
+            error = httpError
+            return error
+        }
+        
+        guard let httpResponse = loaded.1 as? HTTPURLResponse else {
+            error = ShardError(type: .UnknownResponseError, message: "Unknown response type.")
+            return error
+        }
+        
+        // The loader fails on these too, unless it has a cached copy to fall back to
+        let status = httpResponse.statusCode
+        if !(200..<300).contains(status) && status != 304 {
+            error = ShardError(
+                type: .HttpStatusCodeError,
+                message: "Server responded with status code \(status)."
+            )
+        }
+        
+        let header = { (name: String) -> String? in
+            httpResponse.allHeaderFields.first(where: { ($0.key as? String)?.lowercased() == name })?.value as? String
+        }
+        let data = loaded.0 ?? Data()
+        data.withUnsafeBytes { bytes in
+            shard_response_set(
+                response,
+                UInt16(clamping: status),
+                bytes.bindMemory(to: UInt8.self).baseAddress,
+                data.count,
+                httpResponse.mimeType.map { ($0 as NSString).utf8String } ?? nil,
+                header("etag").map { ($0 as NSString).utf8String } ?? nil,
+                header("cache-control").map { ($0 as NSString).utf8String } ?? nil)
+        }
+        
+        return nil
+    }
+}
+
+private func shard_view_manager_resolve_fragment(
+    _ self_ptr: UnsafeRawPointer?,
+    _ id: UnsafePointer<Int8>?,
+    _ err: UnsafeMutablePointer<UnsafePointer<Int8>?>?) -> UnsafePointer<Int8>? {
+    
+    let viewManager: ShardViewManager = Unmanaged.fromOpaque(UnsafeRawPointer(self_ptr!)).takeUnretainedValue()
+    
+    do {
+        let json = try viewManager.fragmentResolver!(String(cString: id!))
+        return (json as NSString).utf8String
+    } catch {
+        err?.initialize(to: (error.localizedDescription as NSString).utf8String)
+        return nil
+    }
+}
+
 public typealias ViewImplFactory = (ShardContext) -> ShardViewImpl
 
+/// Returns the JSON of the fragment with an id, included by documents with `{"$include": id}`.
+public typealias FragmentResolver = (String) throws -> String
+
 public class ShardViewManager {
     public static let shared = ShardViewManager()
     public var session: NetworkSession = URLSession(configuration: .default)
     
     internal var rust_ptr: OpaquePointer! = nil
     internal var implFactories: Dictionary<String, ViewImplFactory> = [:]
+    private var fragmentResolver: FragmentResolver? = nil
     
     private init() {
         let self_ptr = Unmanaged.passUnretained(self).toOpaque()
@@ -62,64 +172,132 @@ public class ShardViewManager {
         self.implFactories[kind] = factory
     }
     
+    /// Resolve the fragments included by documents loaded from now on, on the thread they are
+    /// loaded on. Fragments resolved so far are dropped.
+    public func setFragmentResolver(_ resolver: @escaping FragmentResolver) {
+        self.fragmentResolver = resolver
+        let self_ptr = Unmanaged.passUnretained(self).toOpaque()
+        shard_view_manager_set_fragment_resolver(rust_ptr, self_ptr, shard_view_manager_resolve_fragment)
+    }
+    
     func createView(context: ShardContext, kind: String) -> ShardView {
         return ShardView(implFactories[kind]!(context))
     }
     
-    func getResult(_ data: Data?, _ response: URLResponse?, _ httpError: Error?) -> Result<ShardRoot> {
-        guard httpError == nil else {
-            return Result.Failure(httpError!)
+    /// Load a document through the core loader. A stale cached copy is shown right away, in which
+    /// case `onComplete` is called a second time if revalidating it finds a newer document.
+    public func loadUrl(url: URL, onComplete: @escaping (Result<ShardRoot>) -> Void) {
+        DispatchQueue.global().async {
+            let request = DocumentRequest(self.session)
+            request.onDocument = { json in
+                DispatchQueue.main.async { onComplete(self.loadJson(json)) }
+            }
+            
+            var error: UnsafePointer<Int8>? = nil
+            withExtendedLifetime(request) {
+                shard_load_document_stale_while_revalidate(
+                    (url.absoluteString as NSString).utf8String,
+                    (self.cacheDir.path as NSString).utf8String,
+                    Unmanaged.passUnretained(request).toOpaque(),
+                    shard_view_manager_fetch,
+                    shard_view_manager_on_document,
+                    &error)
+            }
+            
+            if let error = error {
+                let failure: Error = request.error ?? String(cString: error)
+                DispatchQueue.main.async { onComplete(Result.Failure(failure)) }
+            }
+        }
+    }
+    
+    /// Load a document while blocking the calling thread, for navigators which render the targets
+    /// of actions as they run.
+    internal func loadUrlBlocking(url: URL) -> Result<ShardRoot> {
+        switch loadDocument(url: url) {
+        case .Success(let json): return loadJson(json)
+        case .Failure(let error): return Result.Failure(error)
         }
+    }
+    
+    /// Fetch a document through the core loader, which caches it and falls back to the cached
+    /// copy when offline. CBOR and MessagePack documents are returned as JSON.
+    private func loadDocument(url: URL) -> Result<String> {
+        let request = DocumentRequest(session)
         
-        if let httpResponse = response as? HTTPURLResponse {
-            if (httpResponse.statusCode != 200) {
-                return Result.Failure(
-                    ShardError(
-                        type: .HttpStatusCodeError,
-                        message: "Server responded with status code \(httpResponse.statusCode)."
-                    )
-                )
-            }
-        } else {
-            return Result.Failure(
-                ShardError(
-                    type: .UnknownResponseError,
-                    message: "Unknown response type."
-                )
-            )
+        var error: UnsafePointer<Int8>? = nil
+        let json = withExtendedLifetime(request) {
+            shard_load_document(
+                (url.absoluteString as NSString).utf8String,
+                (cacheDir.path as NSString).utf8String,
+                Unmanaged.passUnretained(request).toOpaque(),
+                shard_view_manager_fetch,
+                &error)
         }
         
-        let json = String(data: data!, encoding: .utf8)
+        guard let document = json else {
+            return Result.Failure(request.error ?? String(cString: error!))
+        }
         
-        return loadJson(json!)
+        defer { shard_string_free(document) }
+        return Result.Success(String(cString: document))
     }
     
-    public func loadUrl(url: URL, onComplete: @escaping (Result<ShardRoot>) -> Void) {
-        session.loadData(from: url) { data, response, httpError in
-            let result = self.getResult(data, response, httpError)
-            
-            DispatchQueue.main.async {
-                onComplete(result)
-            }
-        }
+    private var cacheDir: URL {
+        return FileManager.default.urls(for: .cachesDirectory, in: .userDomainMask)[0].appendingPathComponent("shard")
     }
     
     public func loadJson(_ json: JsonValue) -> Result<ShardRoot> {
         return loadJson(json.toString())
     }
     
+    func isDarkMode() -> Bool {
+        if #available(iOS 13.0, *) {
+            return UITraitCollection.current.userInterfaceStyle == .dark
+        }
+        return false
+    }
+    
     public func loadJson(_ json: String) -> Result<ShardRoot> {
+        return load { context_ptr, renderContext, error_ptr in
+            shard_render(self.rust_ptr, context_ptr, renderContext, (json as NSString).utf8String, error_ptr)
+        }
+    }
+    
+    /// Load a document encoded as JSON, CBOR or MessagePack. The encoding is taken from
+    /// contentType when it names one and detected from the first byte otherwise.
+    public func loadData(_ data: Data, contentType: String? = nil) -> Result<ShardRoot> {
+        return load { context_ptr, renderContext, error_ptr in
+            data.withUnsafeBytes { bytes in
+                shard_render_bytes(
+                    self.rust_ptr,
+                    context_ptr,
+                    renderContext,
+                    bytes.bindMemory(to: UInt8.self).baseAddress,
+                    data.count,
+                    contentType.map { ($0 as NSString).utf8String } ?? nil,
+                    error_ptr)
+            }
+        }
+    }
+    
+    private func load(_ render: (UnsafeMutableRawPointer, IOSRenderContext, UnsafeMutablePointer<UnsafePointer<Int8>?>) -> IOSRoot) -> Result<ShardRoot> {
         let context = ShardContext()
         let context_ptr = Unmanaged.passUnretained(context).toOpaque()
         
         var error: UnsafePointer<Int8>? = nil
         let error_ptr = UnsafeMutablePointer(&error)
-        let ios_root = shard_render(self.rust_ptr, context_ptr, (json as NSString).utf8String, error_ptr)
+        let renderContext = IOSRenderContext(locale: (Locale.current.identifier as NSString).utf8String, dark_mode: isDarkMode())
+        let ios_root = render(context_ptr, renderContext, error_ptr)
         
         if let error = error {
             return Result.Failure(String(cString: error))
         } else {
-            return Result.Success(ShardRoot(context, ios_root))
+            let root = ShardRoot(context, ios_root)
+            #if DEBUG
+            root.warnings.forEach { print("Shard: \($0)") }
+            #endif
+            return Result.Success(root)
         }
     }
 }
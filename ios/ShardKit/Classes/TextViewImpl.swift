@@ -8,6 +8,7 @@
 import UIKit
 
 private let systemFont = UIFont.systemFont(ofSize: 12)
+private let monospaceFont = UIFont(name: "Menlo", size: 12)!
 
 internal struct SubstringTapEvent {
     var range: NSRange
@@ -33,9 +34,9 @@ internal class TextViewImpl: BaseViewImpl {
         tapEvents = []
         
         switch key {
-        case "span": self.text = try attributedString(from: try value.asObject(), attributes: [:])
+        case "runs": self.text = try attributedString(from: try value.asArray())
         case "max-lines": self.numberOfLines = Int(try value.asNumber())
-        case "line-height": self.lineHeightMultiple = try value.asObject()["value"]!.asNumber()
+        case "line-height": self.lineHeightMultiple = Float(try value.asObject()["value"]!.asNumber())
         case "text-align":
             switch value {
             case .String(let value) where value == "start": self.textAlignment = .left
@@ -85,115 +86,62 @@ internal class TextViewImpl: BaseViewImpl {
         view.numberOfLines = self.numberOfLines
     }
     
-    func attributedString(from props: [String: JsonValue], attributes: [NSAttributedString.Key : Any], location: Int? = nil) throws -> NSAttributedString {
-        var attributes = attributes
-        let currentFont = attributes[.font] as! UIFont?
-        
-        let family: String = try props.get("font-family") {
-            switch $0 {
-            case .String(let value):
-                if !UIFont.familyNames.contains(value) {
-                    throw "Unexpected value for font-family: \(value)"
-                }
-                return value
-            case .Null: return currentFont?.familyName ?? systemFont.familyName
-            case let value: throw "Unexpected value for font-family: \(value)"
-            }
-        }
-        
-        let size: CGFloat = try props.get("font-size") {
-            switch $0 {
-            case .Object(let value): return CGFloat(try value.asDimension())
-            case .Null: return currentFont?.pointSize ?? systemFont.pointSize
-            case let value: throw "Unexpected value for font-size: \(value)"
-            }
-        }
-        
-        let italic: Bool? = try props.get("font-style") {
-            switch $0 {
-            case .String(let value) where value == "normal": return false
-            case .String(let value) where value == "italic": return true
-            case .Null: return nil
-            case let value: throw "Unexpected value for font-style: \(value)"
-            }
-        }
+    func attributedString(from runs: [JsonValue]) throws -> NSAttributedString {
+        let string = NSMutableAttributedString()
         
-        let weight: UIFont.Weight? = try props.get("font-weight") {
-            switch $0 {
-            case .String(let value) where value == "regular": return UIFont.Weight.regular
-            case .String(let value) where value == "bold": return UIFont.Weight.bold
-            case .Null: return nil
-            case let value: throw "Unexpected value for font-weight: \(value)"
-            }
-        }
-        
-        if weight != nil || italic != nil {
-            var traits: UIFontDescriptor.SymbolicTraits = currentFont?.fontDescriptor.symbolicTraits ?? []
+        for run in runs {
+            let props = try run.asObject()
+            let text = try props["text"]!.asString()
+            let size = CGFloat(try props["font-size"]!.asObject().asDimension())
             
-            if let weight = weight {
-                if weight == UIFont.Weight.bold {
-                    traits.insert(.traitBold)
-                } else {
-                    traits.remove(.traitBold)
-                }
+            var traits: UIFontDescriptor.SymbolicTraits = []
+            if case .String("bold")? = props["font-weight"] {
+                traits.insert(.traitBold)
+            }
+            if case .String("italic")? = props["font-style"] {
+                traits.insert(.traitItalic)
             }
             
-            if let italic = italic {
-                if italic {
-                    traits.insert(.traitItalic)
-                } else {
-                    traits.remove(.traitItalic)
+            let family: String = try props.get("font-family") {
+                switch $0 {
+                case .String(let value) where value == "monospace": return monospaceFont.familyName
+                case .String(let value):
+                    if !UIFont.familyNames.contains(value) {
+                        throw "Unexpected value for font-family: \(value)"
+                    }
+                    return value
+                default: return systemFont.familyName
                 }
             }
             
             let descriptor = UIFontDescriptor(fontAttributes: [.family: family]).withSymbolicTraits(traits)!
-            attributes[.font] = UIFont(descriptor: descriptor, size: size)
-        } else if attributes[.font] == nil {
-            attributes[.font] = UIFont(name: family, size: size)
-        }
-        
-        try props.get("font-color") {
-            switch $0 {
-            case .Null: ()
-            case let value:
-                attributes[.foregroundColor] =  try value.asColor().default
+            var attributes: [NSAttributedString.Key : Any] = [
+                .font: UIFont(descriptor: descriptor, size: size),
+                .foregroundColor: try props["font-color"]!.asColor().default,
+            ]
+            
+            switch props["text-decoration"] {
+            case .String("underline")?: attributes[.underlineStyle] = NSUnderlineStyle.single.rawValue
+            case .String("line-through")?: attributes[.strikethroughStyle] = NSUnderlineStyle.single.rawValue
+            default: ()
             }
-        }
-        
-        let string: NSMutableAttributedString = try props.get("text") {
-            switch $0 {
-            case .String(let value):
-                let string = NSMutableAttributedString(string: value)
-                string.addAttributes(attributes, range: NSRange(location: 0, length: string.length))
-                
-                return string
-            case .Array(let values):
-                var location = location ?? 0
-                let parts = try values.map({ (value) -> NSAttributedString in
-                    let part = try attributedString(from: value.asObject(), attributes: attributes, location: location)
-                    location += part.length
-                    return part
-                })
-                return NSMutableAttributedString(
-                    attributedString: parts.reduce(NSAttributedString(), { $0 + $1 })
-                )
-            case let value: throw "Unexpected value for text: \(value)"
+            
+            if case .Number(let lineHeight)? = props["line-height"] {
+                let paragraphStyle = NSMutableParagraphStyle()
+                paragraphStyle.lineBreakMode = .byWordWrapping
+                paragraphStyle.lineHeightMultiple = CGFloat(lineHeight)
+                attributes[.paragraphStyle] = paragraphStyle
             }
-        }
-        
-        try props.get("on-click") {
-            switch $0 {
-            case .Null: ()
-            case let value:
-                let value = try value.asObject()
-                let action = try value["action"]!.asString()
-                let range = NSRange(location: location ?? 0, length: string.length)
-                
-                let tapEvent = SubstringTapEvent(range: range, handler: {
-                    self.context.dispatch(action: action, value: value["value"])
-                })
-                
-                tapEvents = tapEvents + [tapEvent]
+            
+            let range = NSRange(location: string.length, length: (text as NSString).length)
+            string.append(NSAttributedString(string: text, attributes: attributes))
+            
+            switch props["action"] {
+            case let action? where (try? action.asObject()) != nil || (try? action.asArray()) != nil:
+                tapEvents = tapEvents + [SubstringTapEvent(range: range, handler: {
+                    self.context.dispatch(action)
+                })]
+            default: ()
             }
         }
         
@@ -206,10 +154,12 @@ internal class TextViewImpl: BaseViewImpl {
         paragraphStyle.lineBreakMode = .byWordWrapping
         paragraphStyle.lineHeightMultiple = CGFloat(lineHeightMultiple)
         
-        string.addAttribute(
-            .paragraphStyle,
-            value: paragraphStyle,
-            range: NSRange(location: 0, length: string.length))
+        // Runs with their own line height keep it
+        string.enumerateAttribute(.paragraphStyle, in: NSRange(location: 0, length: string.length)) { value, range, _ in
+            if value == nil {
+                string.addAttribute(.paragraphStyle, value: paragraphStyle, range: range)
+            }
+        }
         
         return string
     }
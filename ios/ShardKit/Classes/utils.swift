@@ -12,6 +12,24 @@ extension String: LocalizedError {
 }
 
 public extension UIColor {
+    /// Parse a CSS color. 8 digit hex colors are read as `#rrggbbaa`.
+    convenience init(css: String) throws {
+        var error: UnsafePointer<Int8>? = nil
+        let color = shard_color_parse((css as NSString).utf8String, &error)
+        
+        if let error = error {
+            throw String(cString: error)
+        }
+        
+        self.init(
+            red: CGFloat(color.red),
+            green: CGFloat(color.green),
+            blue: CGFloat(color.blue),
+            alpha: CGFloat(color.alpha)
+        )
+    }
+    
+    @available(*, deprecated, message: "Use init(css:), which reads 8 digit hex colors as #rrggbbaa rather than #aarrggbb")
     convenience init(hex: String) throws {
         var normalized = hex
         
@@ -36,6 +54,15 @@ public extension UIColor {
             alpha: CGFloat((argb & 0xFF000000) >> 24) / 255.0
         )
     }
+    
+    /// The core normalizes colors it sets on views to `#aarrggbb`, other colors are parsed as CSS.
+    internal convenience init(viewColor color: String) throws {
+        if color.count == 9 && color.hasPrefix("#") {
+            try self.init(css: "#" + color[3...] + color[1..<3])
+        } else {
+            try self.init(css: color)
+        }
+    }
 }
 
 internal extension String {
@@ -87,3 +114,9 @@ extension NSAttributedString {
     }
 }
 
+
+internal extension String {
+    init(_ value: IOSString) {
+        self = String(decoding: UnsafeBufferPointer(start: value.ptr, count: Int(value.len)), as: UTF8.self)
+    }
+}